@@ -7,9 +7,15 @@ use crate::{ParseError, Rule};
 use super::*;
 
 /// A complete unit of code, like a source file.
-#[derive(Default, Debug)]
+///
+/// Documents compare structurally: two documents are equal if they define the
+/// same imports and functions, regardless of formatting or ordering.
+#[derive(Default, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Document<'src> {
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub imports: HashMap<&'src str, SpannedImport<'src>>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub funcs: HashMap<&'src str, SpannedFuncDef<'src>>,
 }
 
@@ -26,14 +32,14 @@ impl<'src> TryFrom<Pair<'src, Rule>> for Document<'src> {
                     let new = SpannedFuncDef::try_from(statement)?;
 
                     if let Some(old) = document.funcs.insert(new.name.text, new.clone()) {
-                        return Err(ParseError::DuplicateFuncDef(old, new));
+                        return Err(ParseError::DuplicateFuncDef(Box::new(old), Box::new(new)));
                     }
                 }
                 Rule::import => {
                     let new = SpannedImport::try_from(statement)?;
 
                     if let Some(old) = document.imports.insert(new.alias, new.clone()) {
-                        return Err(ParseError::DumplicateImport(old, new));
+                        return Err(ParseError::DumplicateImport(Box::new(old), Box::new(new)));
                     }
                 }
                 Rule::EOI => {}
@@ -44,3 +50,19 @@ impl<'src> TryFrom<Pair<'src, Rule>> for Document<'src> {
         Ok(document)
     }
 }
+
+impl<'src> Document<'src> {
+    /// The names of the functions defined in the document, in no particular
+    /// order.
+    pub fn function_names(&self) -> impl Iterator<Item = &str> {
+        self.funcs.keys().copied()
+    }
+
+    /// The argument definitions of the function `name`.
+    ///
+    /// This is `None` both when there's no such function and when the function
+    /// doesn't take any arguments.
+    pub fn arg_defs(&self, name: &str) -> Option<&SpannedArgDefs<'src>> {
+        self.funcs.get(name)?.args.as_ref()
+    }
+}
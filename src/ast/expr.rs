@@ -1,14 +1,28 @@
+use std::collections::BTreeMap;
+
 use pest::pratt_parser::{Assoc, Op, PrattParser};
 
 use super::*;
 
 /// An expression, or part of one such as `sin(1.2 * pi) * 0.5`.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Expr<'src> {
     Number(Number),
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    Str(StringLit<'src>),
+    #[cfg_attr(feature = "serde", serde(borrow))]
     Unary(UnaryExpr<'src>),
+    #[cfg_attr(feature = "serde", serde(borrow))]
     Binary(BinaryExpr<'src>),
+    #[cfg_attr(feature = "serde", serde(borrow))]
     FuncCall(FuncCallExpr<'src>),
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    List(ListExpr<'src>),
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    Dict(DictExpr<'src>),
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    Let(LetExpr<'src>),
 }
 
 /// [`Expr`] but [`Spanned`].
@@ -29,9 +43,16 @@ impl<'src> TryFrom<Pair<'src, Rule>> for SpannedExpr<'src> {
 impl<'src> SpannedExpr<'src> {
     fn pratt() -> PrattParser<Rule> {
         PrattParser::new()
+            .op(Op::infix(Rule::eq, Assoc::Left) | Op::infix(Rule::ne, Assoc::Left))
+            .op(Op::infix(Rule::lt, Assoc::Left)
+                | Op::infix(Rule::le, Assoc::Left)
+                | Op::infix(Rule::gt, Assoc::Left)
+                | Op::infix(Rule::ge, Assoc::Left))
             .op(Op::infix(Rule::add, Assoc::Left) | Op::infix(Rule::sub, Assoc::Left))
             .op(Op::infix(Rule::mul, Assoc::Left) | Op::infix(Rule::div, Assoc::Left))
             .op(Op::prefix(Rule::neg))
+            // Binds tighter than negation so `-2 ^ 2` is `-(2 ^ 2)`.
+            .op(Op::infix(Rule::pow, Assoc::Right))
     }
 
     fn primary(primary: Pair<'src, Rule>) -> ParseResult<'src, Self> {
@@ -39,8 +60,25 @@ impl<'src> SpannedExpr<'src> {
 
         match primary.as_rule() {
             Rule::number => Ok(Expr::Number(Number::try_from(primary)?).spanned(&span)),
-            Rule::func_call => Ok(Expr::FuncCall(primary.try_into()?).spanned(&span)),
-            Rule::paren_expr => primary.into_inner().try_next()?.try_into(),
+            Rule::string => Ok(Expr::Str(StringLit::try_from(primary)?).spanned(&span)),
+            Rule::func_call => {
+                // A call without arguments ends with whitespace skipped while
+                // looking for them, which shouldn't be part of its span.
+                let end = span.start() + span.as_str().trim_end().len();
+                let span = Span::new(span.get_input(), span.start(), end)
+                    .ok_or(ParseError::ExpectedUnwrap)?;
+                Ok(Expr::FuncCall(primary.try_into()?).spanned(&span))
+            }
+            Rule::list => Ok(Expr::List(primary.try_into()?).spanned(&span)),
+            Rule::dict => Ok(Expr::Dict(primary.try_into()?).spanned(&span)),
+            Rule::let_expr => Ok(Expr::Let(primary.try_into()?).spanned(&span)),
+            Rule::paren_expr => {
+                // Keep the parentheses in the span so that expressions built
+                // around this one, like `-(-a)`, cover their full source.
+                let mut inner: Self = primary.into_inner().try_next()?.try_into()?;
+                inner.span = span;
+                Ok(inner)
+            }
             _ => Err(ParseError::UnexpectedFieldType),
         }
     }
@@ -56,16 +94,24 @@ impl<'src> SpannedExpr<'src> {
         let span = Span::new(lspan.get_input(), lspan.start(), rspan.end())
             .ok_or(ParseError::ExpectedUnwrap)?;
 
+        let op_span = op.as_span();
         let op = match op.as_rule() {
             Rule::add => BinaryOp::Add,
             Rule::sub => BinaryOp::Sub,
             Rule::mul => BinaryOp::Mul,
             Rule::div => BinaryOp::Div,
+            Rule::pow => BinaryOp::Pow,
+            Rule::lt => BinaryOp::Lt,
+            Rule::le => BinaryOp::Le,
+            Rule::gt => BinaryOp::Gt,
+            Rule::ge => BinaryOp::Ge,
+            Rule::eq => BinaryOp::Eq,
+            Rule::ne => BinaryOp::Ne,
             _ => return Err(ParseError::UnexpectedFieldType),
         };
         Ok(Expr::Binary(BinaryExpr {
             lhs: Box::new(lhs),
-            op,
+            op: op.spanned(&op_span),
             rhs: Box::new(rhs),
         })
         .spanned(&span))
@@ -91,7 +137,8 @@ impl<'src> SpannedExpr<'src> {
 }
 
 /// A single scalar value literal.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Number {
     pub val: f64,
 }
@@ -100,41 +147,89 @@ impl<'src> TryFrom<Pair<'src, Rule>> for Number {
     type Error = ParseError<'src>;
 
     fn try_from(value: Pair<'src, Rule>) -> ParseResult<'src, Self> {
-        Ok(Self {
-            val: value
-                .as_str()
-                .parse()
-                .map_err(|e| ParseError::Float(e, value.as_span()))?,
-        })
+        // Rust's float parser doesn't accept digit separators.
+        let val: f64 = value
+            .as_str()
+            .replace('_', "")
+            .parse()
+            .map_err(|e| ParseError::Float(e, value.as_span()))?;
+        // Literals too large for an f64 parse as infinity.
+        if !val.is_finite() {
+            return Err(ParseError::NonFiniteNumber(value.as_span()));
+        }
+
+        Ok(Self { val })
     }
 }
 
 /// [`Number`] but [`Spanned`].
 pub type SpannedNumber<'src> = Spanned<'src, Number>;
 
+/// A string literal like `"Bracket"`.
+///
+/// Strings can't contain `"` or line breaks and there are no escape sequences.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StringLit<'src> {
+    pub val: &'src str,
+}
+
+impl<'src> TryFrom<Pair<'src, Rule>> for StringLit<'src> {
+    type Error = ParseError<'src>;
+
+    fn try_from(value: Pair<'src, Rule>) -> ParseResult<'src, Self> {
+        // string = ${ "\"" ~ string_inner ~ "\"" }
+        Ok(Self {
+            val: value.into_inner().try_next()?.as_str(),
+        })
+    }
+}
+
+/// [`StringLit`] but [`Spanned`].
+pub type SpannedStringLit<'src> = Spanned<'src, StringLit<'src>>;
+
 /// A unary operator such as `-` (negation).
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum UnaryOp {
     Neg,
 }
 
+impl UnaryOp {
+    pub(crate) fn op_name(&self) -> &'static str {
+        match self {
+            UnaryOp::Neg => "Negation",
+        }
+    }
+}
+
 /// A unary expression like `-a`.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct UnaryExpr<'src> {
     pub op: UnaryOp,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub unit: Box<SpannedExpr<'src>>,
 }
 
 /// [`UnaryExpr`] but [`Spanned`].
 pub type SpannedUnaryExpr<'src> = Spanned<'src, UnaryExpr<'src>>;
 
-/// A binary operator such as `+` or `-`.
-#[derive(Clone, Copy, Debug)]
+/// A binary operator such as `+` or `<`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum BinaryOp {
     Add,
     Sub,
     Mul,
     Div,
+    Pow,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+    Ne,
 }
 
 impl BinaryOp {
@@ -144,15 +239,29 @@ impl BinaryOp {
             BinaryOp::Sub => "Subtraction",
             BinaryOp::Mul => "Multiplication",
             BinaryOp::Div => "Division",
+            BinaryOp::Pow => "Exponentiation",
+            BinaryOp::Lt => "Less than comparison",
+            BinaryOp::Le => "Less than or equal comparison",
+            BinaryOp::Gt => "Greater than comparison",
+            BinaryOp::Ge => "Greater than or equal comparison",
+            BinaryOp::Eq => "Equality comparison",
+            BinaryOp::Ne => "Inequality comparison",
         }
     }
 }
 
+/// [`BinaryOp`] but [`Spanned`].
+pub type SpannedBinaryOp<'src> = Spanned<'src, BinaryOp>;
+
 /// A binary expression like `a + b`.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BinaryExpr<'src> {
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub lhs: Box<SpannedExpr<'src>>,
-    pub op: BinaryOp,
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub op: SpannedBinaryOp<'src>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub rhs: Box<SpannedExpr<'src>>,
 }
 
@@ -160,9 +269,12 @@ pub struct BinaryExpr<'src> {
 pub type SpannedBinaryExpr<'src> = Spanned<'src, BinaryExpr<'src>>;
 
 /// A function call like `foo` or `bar(1, 2)`.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FuncCallExpr<'src> {
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub name: FuncName<'src>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub args: CallArgs<'src>,
 }
 
@@ -184,3 +296,146 @@ impl<'src> TryFrom<Pair<'src, Rule>> for FuncCallExpr<'src> {
         Ok(FuncCallExpr { name, args })
     }
 }
+
+/// A list literal like `[1, 2, Cube()]`.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ListExpr<'src> {
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub items: Vec<SpannedExpr<'src>>,
+}
+
+/// [`ListExpr`] but [`Spanned`].
+pub type SpannedListExpr<'src> = Spanned<'src, ListExpr<'src>>;
+
+impl<'src> TryFrom<Pair<'src, Rule>> for ListExpr<'src> {
+    type Error = ParseError<'src>;
+
+    fn try_from(value: Pair<'src, Rule>) -> ParseResult<'src, Self> {
+        // list = { "[" ~ (expr ~ ("," ~ expr)*)? ~ "]" }
+        let items: Result<Vec<_>, _> = value.into_inner().map(SpannedExpr::try_from).collect();
+        Ok(Self { items: items? })
+    }
+}
+
+/// A dictionary literal like `{"small": Cube(1), "large": Cube(2)}`.
+///
+/// Dictionaries aren't values. They're only used as the cases of `Switch`,
+/// which evaluates just the entry it picks.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DictExpr<'src> {
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub entries: BTreeMap<&'src str, SpannedDictEntry<'src>>,
+}
+
+/// [`DictExpr`] but [`Spanned`].
+pub type SpannedDictExpr<'src> = Spanned<'src, DictExpr<'src>>;
+
+impl<'src> TryFrom<Pair<'src, Rule>> for DictExpr<'src> {
+    type Error = ParseError<'src>;
+
+    fn try_from(value: Pair<'src, Rule>) -> ParseResult<'src, Self> {
+        // dict = { "{" ~ (dict_entry ~ ("," ~ dict_entry)*)? ~ "}" }
+        let mut entries = BTreeMap::new();
+
+        for pair in value.into_inner() {
+            let new = SpannedDictEntry::try_from(pair)?;
+            if let Some(old) = entries.insert(new.key.val, new.clone()) {
+                return Err(ParseError::DuplicateDictKey(Box::new(old), Box::new(new)));
+            }
+        }
+
+        Ok(Self { entries })
+    }
+}
+
+/// A single entry in a dictionary literal. Like `"small": Cube(1)`.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DictEntry<'src> {
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub key: SpannedStringLit<'src>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub expr: Box<SpannedExpr<'src>>,
+}
+
+/// [`DictEntry`] but [`Spanned`].
+pub type SpannedDictEntry<'src> = Spanned<'src, DictEntry<'src>>;
+
+impl<'src> TryFrom<Pair<'src, Rule>> for DictEntry<'src> {
+    type Error = ParseError<'src>;
+
+    fn try_from(value: Pair<'src, Rule>) -> ParseResult<'src, Self> {
+        // dict_entry = { string ~ ":" ~ expr }
+        let mut inner = value.into_inner();
+        let key = SpannedStringLit::try_from(inner.try_next()?)?;
+        let expr = Box::new(SpannedExpr::try_from(inner.try_next()?)?);
+        Ok(Self { key, expr })
+    }
+}
+
+/// A let expression like `let r = 2, d = r * 2 in Cylinder(r, d)`.
+///
+/// Each binding can refer to the ones before it, and the body can refer to
+/// all of them.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LetExpr<'src> {
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub bindings: Vec<SpannedLetBinding<'src>>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub body: Box<SpannedExpr<'src>>,
+}
+
+/// [`LetExpr`] but [`Spanned`].
+pub type SpannedLetExpr<'src> = Spanned<'src, LetExpr<'src>>;
+
+impl<'src> TryFrom<Pair<'src, Rule>> for LetExpr<'src> {
+    type Error = ParseError<'src>;
+
+    fn try_from(value: Pair<'src, Rule>) -> ParseResult<'src, Self> {
+        // let_expr = { let_kw ~ let_binding ~ ("," ~ let_binding)* ~ in_kw ~ expr }
+        let mut bindings = Vec::new();
+        let mut body = None;
+
+        for pair in value.into_inner() {
+            match pair.as_rule() {
+                Rule::let_kw | Rule::in_kw => {}
+                Rule::let_binding => bindings.push(SpannedLetBinding::try_from(pair)?),
+                Rule::expr => body = Some(Box::new(SpannedExpr::try_from(pair)?)),
+                _ => return Err(ParseError::UnexpectedFieldType),
+            }
+        }
+
+        Ok(Self {
+            bindings,
+            body: body.ok_or(ParseError::ExpectedUnwrap)?,
+        })
+    }
+}
+
+/// A single binding in a let expression. Like `r = 2`.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LetBinding<'src> {
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub name: SpannedIdentifier<'src>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub expr: Box<SpannedExpr<'src>>,
+}
+
+/// [`LetBinding`] but [`Spanned`].
+pub type SpannedLetBinding<'src> = Spanned<'src, LetBinding<'src>>;
+
+impl<'src> TryFrom<Pair<'src, Rule>> for LetBinding<'src> {
+    type Error = ParseError<'src>;
+
+    fn try_from(value: Pair<'src, Rule>) -> ParseResult<'src, Self> {
+        // let_binding = { identifier ~ "=" ~ expr }
+        let mut inner = value.into_inner();
+        let name = SpannedIdentifier::try_from(inner.try_next()?)?;
+        let expr = Box::new(SpannedExpr::try_from(inner.try_next()?)?);
+        Ok(Self { name, expr })
+    }
+}
@@ -4,9 +4,12 @@ use super::*;
 
 /// A single argument in a function definition, and maybe an expression for its
 /// default value.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ArgDef<'src> {
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub name: SpannedIdentifier<'src>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub default: Option<SpannedExpr<'src>>,
 }
 
@@ -32,8 +35,10 @@ impl<'src> TryFrom<Pair<'src, Rule>> for ArgDef<'src> {
 }
 
 /// A collection of all the arguments in a function definition.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ArgDefs<'src> {
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub args: Vec<SpannedArgDef<'src>>,
 }
 
@@ -44,8 +49,20 @@ impl<'src> TryFrom<Pair<'src, Rule>> for ArgDefs<'src> {
     type Error = ParseError<'src>;
 
     fn try_from(value: Pair<'src, Rule>) -> Result<Self, Self::Error> {
-        let args: Result<Vec<_>, _> = value.into_inner().map(SpannedArgDef::try_from).collect();
-        Ok(Self { args: args? })
+        let mut args: Vec<SpannedArgDef> = Vec::new();
+
+        for pair in value.into_inner() {
+            let new = SpannedArgDef::try_from(pair)?;
+            if let Some(old) = args.iter().find(|old| old.name.text == new.name.text) {
+                return Err(ParseError::DuplicateArgDef(
+                    Box::new(old.clone()),
+                    Box::new(new),
+                ));
+            }
+            args.push(new);
+        }
+
+        Ok(Self { args })
     }
 }
 
@@ -57,12 +74,81 @@ impl<'src> ArgDefs<'src> {
 
 /// A collection of all of the expressions passed as arguments in a single
 /// function call.
-#[derive(Clone, Default, Debug)]
+#[derive(Clone, Default, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum CallArgs<'src> {
     #[default]
     None,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     Positional(Vec<Box<SpannedExpr<'src>>>),
+    #[cfg_attr(feature = "serde", serde(borrow))]
     Named(BTreeMap<&'src str, SpannedNamedCallArg<'src>>),
+    /// Positional arguments followed by named ones.
+    Mixed {
+        #[cfg_attr(feature = "serde", serde(borrow))]
+        positional: Vec<Box<SpannedExpr<'src>>>,
+        #[cfg_attr(feature = "serde", serde(borrow))]
+        named: BTreeMap<&'src str, SpannedNamedCallArg<'src>>,
+    },
+}
+
+impl<'src> CallArgs<'src> {
+    /// The arguments passed by position, in order.
+    pub fn positional(&self) -> &[Box<SpannedExpr<'src>>] {
+        match self {
+            CallArgs::Positional(positional) | CallArgs::Mixed { positional, .. } => positional,
+            CallArgs::None | CallArgs::Named(_) => &[],
+        }
+    }
+
+    /// The arguments passed by name.
+    pub fn named(&self) -> impl Iterator<Item = (&&'src str, &SpannedNamedCallArg<'src>)> {
+        match self {
+            CallArgs::Named(named) | CallArgs::Mixed { named, .. } => Some(named.iter()),
+            CallArgs::None | CallArgs::Positional(_) => None,
+        }
+        .into_iter()
+        .flatten()
+    }
+
+    /// Like [`CallArgs::positional`] but mutable.
+    pub fn positional_mut(&mut self) -> &mut [Box<SpannedExpr<'src>>] {
+        match self {
+            CallArgs::Positional(positional) | CallArgs::Mixed { positional, .. } => positional,
+            CallArgs::None | CallArgs::Named(_) => &mut [],
+        }
+    }
+
+    /// Like [`CallArgs::named`] but mutable.
+    pub fn named_mut(
+        &mut self,
+    ) -> impl Iterator<Item = (&&'src str, &mut SpannedNamedCallArg<'src>)> {
+        match self {
+            CallArgs::Named(named) | CallArgs::Mixed { named, .. } => Some(named.iter_mut()),
+            CallArgs::None | CallArgs::Positional(_) => None,
+        }
+        .into_iter()
+        .flatten()
+    }
+}
+
+/// Collects named arguments by name, failing on the first duplicate.
+fn named_arg_map<'src>(
+    pairs: impl Iterator<Item = Pair<'src, Rule>>,
+) -> ParseResult<'src, BTreeMap<&'src str, SpannedNamedCallArg<'src>>> {
+    let mut arg_map = BTreeMap::new();
+
+    for pair in pairs {
+        let new = SpannedNamedCallArg::try_from(pair)?;
+        if let Some(old) = arg_map.insert(new.name.text, new.clone()) {
+            return Err(ParseError::DuplicateNamedArgument(
+                Box::new(old),
+                Box::new(new),
+            ));
+        }
+    }
+
+    Ok(arg_map)
 }
 
 /// [`CallArgs`] but [`Spanned`].
@@ -81,21 +167,20 @@ impl<'src> TryFrom<Pair<'src, Rule>> for CallArgs<'src> {
                     .collect();
                 Ok(CallArgs::Positional(args?))
             }
-            Rule::named_call_args => {
-                let named_args: Result<Vec<_>, ParseError> = value
+            Rule::named_call_args => Ok(CallArgs::Named(named_arg_map(value.into_inner())?)),
+            Rule::mixed_call_args => {
+                let (positional, named): (Vec<_>, Vec<_>) = value
                     .into_inner()
-                    .map(SpannedNamedCallArg::try_from)
+                    .partition(|pair| pair.as_rule() == Rule::expr);
+                let positional: Result<Vec<_>, ParseError> = positional
+                    .into_iter()
+                    .map(|pair| SpannedExpr::try_from(pair).map(Box::new))
                     .collect();
 
-                let mut arg_map = BTreeMap::new();
-
-                for new in named_args? {
-                    if let Some(old) = arg_map.insert(new.name.text, new.clone()) {
-                        return Err(ParseError::DuplicateNamedArgument(old, new));
-                    }
-                }
-
-                Ok(CallArgs::Named(arg_map))
+                Ok(CallArgs::Mixed {
+                    positional: positional?,
+                    named: named_arg_map(named.into_iter())?,
+                })
             }
             _ => return Err(ParseError::UnexpectedFieldType),
         }
@@ -103,9 +188,12 @@ impl<'src> TryFrom<Pair<'src, Rule>> for CallArgs<'src> {
 }
 
 /// A single named argument in a function call. Like `foo = 1`.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NamedCallArg<'src> {
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub name: SpannedIdentifier<'src>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub expr: Box<SpannedExpr<'src>>,
 }
 
@@ -125,10 +213,14 @@ impl<'src> TryFrom<Pair<'src, Rule>> for NamedCallArg<'src> {
 }
 
 /// A complete function definition, including its arguments and body.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FuncDef<'src> {
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub name: SpannedIdentifier<'src>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub args: Option<SpannedArgDefs<'src>>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub body: SpannedExpr<'src>,
 }
 
@@ -6,7 +6,8 @@ use super::Spanned;
 
 /// A single instance of an identifier such as a function or parameter name in a
 /// function call or definition.
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Identifier<'src> {
     pub text: &'src str,
 }
@@ -25,9 +26,16 @@ impl<'src> TryFrom<Pair<'src, Rule>> for Identifier<'src> {
 }
 
 /// A potentially qualified function name in a function call.
-#[derive(Clone, Debug)]
+///
+/// A qualified name is resolved through each import in turn, so in `a.b.c`
+/// the function `c` is in the document `b` imported by the document `a`
+/// imported by the calling document.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FuncName<'src> {
-    pub import_part: Option<SpannedIdentifier<'src>>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub import_parts: Vec<SpannedIdentifier<'src>>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub name_part: SpannedIdentifier<'src>,
 }
 
@@ -38,18 +46,16 @@ impl<'src> TryFrom<Pair<'src, Rule>> for FuncName<'src> {
     type Error = ParseError<'src>;
 
     fn try_from(value: Pair<'src, Rule>) -> Result<Self, Self::Error> {
-        // func_name = ${ identifier ~ ("." ~ identifier)? }
-        let mut inner = value.into_inner();
-        match (inner.next(), inner.next()) {
-            (Some(name), None) => Ok(Self {
-                import_part: None,
-                name_part: name.try_into()?,
-            }),
-            (Some(import), Some(name)) => Ok(Self {
-                import_part: Some(import.try_into()?),
-                name_part: name.try_into()?,
-            }),
-            _ => Err(ParseError::ExpectedUnwrap),
-        }
+        // func_name = ${ identifier ~ ("." ~ identifier)* }
+        let mut parts: Vec<SpannedIdentifier> = value
+            .into_inner()
+            .map(SpannedIdentifier::try_from)
+            .collect::<Result<_, _>>()?;
+        let name_part = parts.pop().ok_or(ParseError::ExpectedUnwrap)?;
+
+        Ok(Self {
+            import_parts: parts,
+            name_part,
+        })
     }
 }
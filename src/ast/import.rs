@@ -6,12 +6,23 @@ use super::{Spanned, TryNext};
 
 /// An import directive.
 #[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Import<'src> {
     pub alias: &'src str,
     pub file: &'src str,
+    /// Whether the imported functions can also be called unqualified.
+    pub wildcard: bool,
+    #[cfg_attr(feature = "serde", serde(with = "crate::ast::span_offsets"))]
     pub span: Span<'src>,
 }
 
+// Imports compare by what they import, not where.
+impl<'src> PartialEq for Import<'src> {
+    fn eq(&self, other: &Self) -> bool {
+        self.alias == other.alias && self.file == other.file && self.wildcard == other.wildcard
+    }
+}
+
 /// [`Import`], but [`Spanned`].
 pub type SpannedImport<'src> = Spanned<'src, Import<'src>>;
 
@@ -20,7 +31,12 @@ impl<'src> TryFrom<Pair<'src, Rule>> for Import<'src> {
 
     fn try_from(value: Pair<'src, Rule>) -> Result<Self, Self::Error> {
         let span = value.as_span();
-        let id = value.into_inner().try_next()?;
+        let mut inner = value.into_inner();
+        let mut id = inner.try_next()?;
+        let wildcard = id.as_rule() == Rule::wildcard;
+        if wildcard {
+            id = inner.try_next()?;
+        }
         match id.as_rule() {
             Rule::file_name => {
                 let file = id.as_str();
@@ -29,7 +45,12 @@ impl<'src> TryFrom<Pair<'src, Rule>> for Import<'src> {
                     .last()
                     .ok_or(ParseError::ExpectedUnwrap)?
                     .as_str();
-                Ok(Self { alias, file, span })
+                Ok(Self {
+                    alias,
+                    file,
+                    wildcard,
+                    span,
+                })
             }
             _ => Err(ParseError::UnexpectedFieldType),
         }
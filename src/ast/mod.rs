@@ -9,8 +9,10 @@ pub use import::{Import, SpannedImport};
 
 mod expr;
 pub use expr::{
-    BinaryExpr, BinaryOp, Expr, FuncCallExpr, Number, SpannedBinaryExpr, SpannedExpr,
-    SpannedFuncCallExpr, SpannedNumber, SpannedUnaryExpr, UnaryExpr, UnaryOp,
+    BinaryExpr, BinaryOp, DictEntry, DictExpr, Expr, FuncCallExpr, LetBinding, LetExpr, ListExpr,
+    Number, SpannedBinaryExpr, SpannedBinaryOp, SpannedDictEntry, SpannedDictExpr, SpannedExpr,
+    SpannedFuncCallExpr, SpannedLetBinding, SpannedLetExpr, SpannedListExpr, SpannedNumber,
+    SpannedStringLit, SpannedUnaryExpr, StringLit, UnaryExpr, UnaryOp,
 };
 
 mod function;
@@ -19,6 +21,14 @@ pub use function::{
     SpannedCallArgs, SpannedFuncDef, SpannedNamedCallArg,
 };
 
+mod visit;
+pub use visit::{
+    walk_binary, walk_binary_mut, walk_dict, walk_dict_mut, walk_document, walk_document_mut,
+    walk_expr, walk_expr_mut, walk_func_call, walk_func_call_mut, walk_func_def, walk_func_def_mut,
+    walk_let, walk_let_mut, walk_list, walk_list_mut, walk_unary, walk_unary_mut, Visitor,
+    VisitorMut,
+};
+
 use std::{
     fmt::{Debug, Display},
     ops::Deref,
@@ -29,7 +39,7 @@ use pest::{
     Span,
 };
 
-use crate::{error::ParseResult, ParseError, Rule};
+use crate::{error::ParseResult, line_col, ParseError, Rule};
 
 // This is a conveniece trait to return an error if case the code doesn't match
 // the grammar and we unwrap somewhere we shouldn't.
@@ -44,22 +54,56 @@ impl<'src> TryNext<'src, Pair<'src, Rule>> for Pairs<'src, Rule> {
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Spanned<'src, T>
 where
     T: Clone + Debug,
 {
     pub inner: T,
+    #[cfg_attr(feature = "serde", serde(with = "span_offsets"))]
     pub span: Span<'src>,
 }
 
+/// Serializes spans as `[start, end]` byte offsets into their source.
+///
+/// A span can't be rebuilt without the source it points into, so spans are
+/// deserialized as empty spans. Spans are ignored when comparing syntax trees,
+/// so a deserialized tree is still equal to the one that was serialized.
+#[cfg(feature = "serde")]
+mod span_offsets {
+    use pest::Span;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub(super) fn serialize<S: Serializer>(span: &Span, serializer: S) -> Result<S::Ok, S::Error> {
+        (span.start(), span.end()).serialize(serializer)
+    }
+
+    pub(super) fn deserialize<'de, 'src, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Span<'src>, D::Error> {
+        <(usize, usize)>::deserialize(deserializer)?;
+        Ok(Span::new("", 0, 0).expect("An empty span is always valid"))
+    }
+}
+
 impl<'src, T> Copy for Spanned<'src, T> where T: Clone + Debug + Copy {}
 
+// Spans are ignored so that equality is structural.
+impl<'src, T> PartialEq for Spanned<'src, T>
+where
+    T: Clone + Debug + PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.inner == other.inner
+    }
+}
+
 impl<'src, T> Display for Spanned<'src, T>
 where
     T: Clone + Debug,
 {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let (line, col) = self.span.start_pos().line_col();
+        let (line, col) = line_col(self.span.get_input(), self.span.start());
         write!(
             f,
             "\"{0}\" on line {1} col {2}",
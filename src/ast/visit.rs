@@ -0,0 +1,263 @@
+use super::*;
+
+/// A walk over the expressions in a syntax tree.
+///
+/// Every method recurses into its children by default through the matching
+/// `walk_` function, so an analysis only overrides the nodes it cares about.
+/// An override that still wants the children visited calls the `walk_`
+/// function itself.
+///
+/// The nodes inside an expression are passed without their spans, which are
+/// on the [`SpannedExpr`] given to [`Visitor::visit_expr`].
+pub trait Visitor<'src> {
+    fn visit_func_def(&mut self, func: &SpannedFuncDef<'src>) {
+        walk_func_def(self, func)
+    }
+
+    fn visit_expr(&mut self, expr: &SpannedExpr<'src>) {
+        walk_expr(self, expr)
+    }
+
+    fn visit_number(&mut self, _number: &Number) {}
+
+    fn visit_string(&mut self, _string: &StringLit<'src>) {}
+
+    fn visit_unary(&mut self, unary: &UnaryExpr<'src>) {
+        walk_unary(self, unary)
+    }
+
+    fn visit_binary(&mut self, binary: &BinaryExpr<'src>) {
+        walk_binary(self, binary)
+    }
+
+    fn visit_func_call(&mut self, call: &FuncCallExpr<'src>) {
+        walk_func_call(self, call)
+    }
+
+    fn visit_list(&mut self, list: &ListExpr<'src>) {
+        walk_list(self, list)
+    }
+
+    fn visit_dict(&mut self, dict: &DictExpr<'src>) {
+        walk_dict(self, dict)
+    }
+
+    fn visit_let(&mut self, let_expr: &LetExpr<'src>) {
+        walk_let(self, let_expr)
+    }
+}
+
+/// Visits every function in `doc` in the order they were defined.
+pub fn walk_document<'src, V: Visitor<'src> + ?Sized>(visitor: &mut V, doc: &Document<'src>) {
+    let mut funcs: Vec<_> = doc.funcs.values().collect();
+    funcs.sort_by_key(|func| (func.span.start(), func.name.text));
+    for func in funcs {
+        visitor.visit_func_def(func);
+    }
+}
+
+/// Visits the default argument values of `func`, then its body.
+pub fn walk_func_def<'src, V: Visitor<'src> + ?Sized>(
+    visitor: &mut V,
+    func: &SpannedFuncDef<'src>,
+) {
+    if let Some(args) = &func.args {
+        for default in args.args.iter().filter_map(|arg| arg.default.as_ref()) {
+            visitor.visit_expr(default);
+        }
+    }
+    visitor.visit_expr(&func.body);
+}
+
+pub fn walk_expr<'src, V: Visitor<'src> + ?Sized>(visitor: &mut V, expr: &SpannedExpr<'src>) {
+    match &expr.inner {
+        Expr::Number(number) => visitor.visit_number(number),
+        Expr::Str(string) => visitor.visit_string(string),
+        Expr::Unary(unary) => visitor.visit_unary(unary),
+        Expr::Binary(binary) => visitor.visit_binary(binary),
+        Expr::FuncCall(call) => visitor.visit_func_call(call),
+        Expr::List(list) => visitor.visit_list(list),
+        Expr::Dict(dict) => visitor.visit_dict(dict),
+        Expr::Let(let_expr) => visitor.visit_let(let_expr),
+    }
+}
+
+pub fn walk_unary<'src, V: Visitor<'src> + ?Sized>(visitor: &mut V, unary: &UnaryExpr<'src>) {
+    visitor.visit_expr(&unary.unit);
+}
+
+pub fn walk_binary<'src, V: Visitor<'src> + ?Sized>(visitor: &mut V, binary: &BinaryExpr<'src>) {
+    visitor.visit_expr(&binary.lhs);
+    visitor.visit_expr(&binary.rhs);
+}
+
+/// Visits positional arguments in order, then named arguments by name.
+pub fn walk_func_call<'src, V: Visitor<'src> + ?Sized>(visitor: &mut V, call: &FuncCallExpr<'src>) {
+    for arg in call.args.positional() {
+        visitor.visit_expr(arg);
+    }
+    for (_, arg) in call.args.named() {
+        visitor.visit_expr(&arg.expr);
+    }
+}
+
+pub fn walk_list<'src, V: Visitor<'src> + ?Sized>(visitor: &mut V, list: &ListExpr<'src>) {
+    for item in &list.items {
+        visitor.visit_expr(item);
+    }
+}
+
+pub fn walk_dict<'src, V: Visitor<'src> + ?Sized>(visitor: &mut V, dict: &DictExpr<'src>) {
+    for entry in dict.entries.values() {
+        visitor.visit_expr(&entry.expr);
+    }
+}
+
+/// Visits the bound values in order, then the body.
+pub fn walk_let<'src, V: Visitor<'src> + ?Sized>(visitor: &mut V, let_expr: &LetExpr<'src>) {
+    for binding in &let_expr.bindings {
+        visitor.visit_expr(&binding.expr);
+    }
+    visitor.visit_expr(&let_expr.body);
+}
+
+/// Like [`Visitor`] but each node can be changed in place, so a pass can
+/// rewrite the tree as it walks it.
+///
+/// Nodes are visited in the same order as by [`Visitor`], and an override that
+/// still wants the children visited calls the matching `walk_..._mut`
+/// function itself.
+pub trait VisitorMut<'src> {
+    fn visit_func_def_mut(&mut self, func: &mut SpannedFuncDef<'src>) {
+        walk_func_def_mut(self, func)
+    }
+
+    fn visit_expr_mut(&mut self, expr: &mut SpannedExpr<'src>) {
+        walk_expr_mut(self, expr)
+    }
+
+    fn visit_number_mut(&mut self, _number: &mut Number) {}
+
+    fn visit_string_mut(&mut self, _string: &mut StringLit<'src>) {}
+
+    fn visit_unary_mut(&mut self, unary: &mut UnaryExpr<'src>) {
+        walk_unary_mut(self, unary)
+    }
+
+    fn visit_binary_mut(&mut self, binary: &mut BinaryExpr<'src>) {
+        walk_binary_mut(self, binary)
+    }
+
+    fn visit_func_call_mut(&mut self, call: &mut FuncCallExpr<'src>) {
+        walk_func_call_mut(self, call)
+    }
+
+    fn visit_list_mut(&mut self, list: &mut ListExpr<'src>) {
+        walk_list_mut(self, list)
+    }
+
+    fn visit_dict_mut(&mut self, dict: &mut DictExpr<'src>) {
+        walk_dict_mut(self, dict)
+    }
+
+    fn visit_let_mut(&mut self, let_expr: &mut LetExpr<'src>) {
+        walk_let_mut(self, let_expr)
+    }
+}
+
+/// Like [`walk_document`] but mutable.
+pub fn walk_document_mut<'src, V: VisitorMut<'src> + ?Sized>(
+    visitor: &mut V,
+    doc: &mut Document<'src>,
+) {
+    let mut funcs: Vec<_> = doc.funcs.values_mut().collect();
+    funcs.sort_by_key(|func| (func.span.start(), func.name.text));
+    for func in funcs {
+        visitor.visit_func_def_mut(func);
+    }
+}
+
+pub fn walk_func_def_mut<'src, V: VisitorMut<'src> + ?Sized>(
+    visitor: &mut V,
+    func: &mut SpannedFuncDef<'src>,
+) {
+    if let Some(args) = &mut func.inner.args {
+        for arg in &mut args.inner.args {
+            if let Some(default) = &mut arg.inner.default {
+                visitor.visit_expr_mut(default);
+            }
+        }
+    }
+    visitor.visit_expr_mut(&mut func.inner.body);
+}
+
+pub fn walk_expr_mut<'src, V: VisitorMut<'src> + ?Sized>(
+    visitor: &mut V,
+    expr: &mut SpannedExpr<'src>,
+) {
+    match &mut expr.inner {
+        Expr::Number(number) => visitor.visit_number_mut(number),
+        Expr::Str(string) => visitor.visit_string_mut(string),
+        Expr::Unary(unary) => visitor.visit_unary_mut(unary),
+        Expr::Binary(binary) => visitor.visit_binary_mut(binary),
+        Expr::FuncCall(call) => visitor.visit_func_call_mut(call),
+        Expr::List(list) => visitor.visit_list_mut(list),
+        Expr::Dict(dict) => visitor.visit_dict_mut(dict),
+        Expr::Let(let_expr) => visitor.visit_let_mut(let_expr),
+    }
+}
+
+pub fn walk_unary_mut<'src, V: VisitorMut<'src> + ?Sized>(
+    visitor: &mut V,
+    unary: &mut UnaryExpr<'src>,
+) {
+    visitor.visit_expr_mut(&mut unary.unit);
+}
+
+pub fn walk_binary_mut<'src, V: VisitorMut<'src> + ?Sized>(
+    visitor: &mut V,
+    binary: &mut BinaryExpr<'src>,
+) {
+    visitor.visit_expr_mut(&mut binary.lhs);
+    visitor.visit_expr_mut(&mut binary.rhs);
+}
+
+pub fn walk_func_call_mut<'src, V: VisitorMut<'src> + ?Sized>(
+    visitor: &mut V,
+    call: &mut FuncCallExpr<'src>,
+) {
+    for arg in call.args.positional_mut() {
+        visitor.visit_expr_mut(arg);
+    }
+    for (_, arg) in call.args.named_mut() {
+        visitor.visit_expr_mut(&mut arg.inner.expr);
+    }
+}
+
+pub fn walk_list_mut<'src, V: VisitorMut<'src> + ?Sized>(
+    visitor: &mut V,
+    list: &mut ListExpr<'src>,
+) {
+    for item in &mut list.items {
+        visitor.visit_expr_mut(item);
+    }
+}
+
+pub fn walk_dict_mut<'src, V: VisitorMut<'src> + ?Sized>(
+    visitor: &mut V,
+    dict: &mut DictExpr<'src>,
+) {
+    for entry in dict.entries.values_mut() {
+        visitor.visit_expr_mut(&mut entry.inner.expr);
+    }
+}
+
+pub fn walk_let_mut<'src, V: VisitorMut<'src> + ?Sized>(
+    visitor: &mut V,
+    let_expr: &mut LetExpr<'src>,
+) {
+    for binding in &mut let_expr.bindings {
+        visitor.visit_expr_mut(&mut binding.inner.expr);
+    }
+    visitor.visit_expr_mut(&mut let_expr.body);
+}
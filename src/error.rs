@@ -3,25 +3,36 @@ use std::{fmt::Display, io::Error as IoError, num::ParseFloatError};
 use pest::{error::Error as PestError, Span};
 use thiserror::Error;
 
-use crate::{ast::*, eval::ContextEntry, FQPath, Rule};
+use crate::{ast::*, eval::ContextEntry, FQPath, Rule, SourceLocation};
 
 /// An error in parsing a document.
+///
+/// Large payloads are boxed so results carrying the error stay small.
 #[derive(Error, Debug)]
 pub enum ParseError<'src> {
     #[error("Parsing error:\n{0}")]
-    Parse(#[from] PestError<Rule>),
+    Parse(Box<PestError<Rule>>),
     #[error("Duplicate imports:\n\t{0}\nthen\n\t{1}")]
-    DumplicateImport(SpannedImport<'src>, SpannedImport<'src>),
+    DumplicateImport(Box<SpannedImport<'src>>, Box<SpannedImport<'src>>),
     #[error("Import path is above entry point: \n\t{0}\n")]
-    ImportNotInDir(SpannedImport<'src>),
+    ImportNotInDir(Box<SpannedImport<'src>>),
+    #[error("Document imports itself: \n\t{0}\n")]
+    SelfImport(Box<SpannedImport<'src>>),
     #[error("Duplicate function definition:\n\t{0}\n\t\tthen\n\t{1}")]
-    DuplicateFuncDef(SpannedFuncDef<'src>, SpannedFuncDef<'src>),
+    DuplicateFuncDef(Box<SpannedFuncDef<'src>>, Box<SpannedFuncDef<'src>>),
     #[error("Float parsing error:\n\t{0}")]
     Float(ParseFloatError, Span<'src>),
+    #[error("Number literal is not finite:\n\t{}", .0.as_str())]
+    NonFiniteNumber(Span<'src>),
     #[error("Duplicate argument definition:\n\t{0}\nthen\n\t{1}")]
-    DuplicateArgDef(SpannedArgDef<'src>, SpannedArgDef<'src>),
+    DuplicateArgDef(Box<SpannedArgDef<'src>>, Box<SpannedArgDef<'src>>),
     #[error("Duplicate named argument {0} then {1}")]
-    DuplicateNamedArgument(SpannedNamedCallArg<'src>, SpannedNamedCallArg<'src>),
+    DuplicateNamedArgument(
+        Box<SpannedNamedCallArg<'src>>,
+        Box<SpannedNamedCallArg<'src>>,
+    ),
+    #[error("Duplicate dictionary key:\n\t{0}\nthen\n\t{1}")]
+    DuplicateDictKey(Box<SpannedDictEntry<'src>>, Box<SpannedDictEntry<'src>>),
     #[error("IO Error \"{0}\"")]
     IO(#[from] IoError),
     #[error("Entry point is not a file")]
@@ -34,6 +45,12 @@ pub enum ParseError<'src> {
     UnexpectedFieldType,
 }
 
+impl<'src> From<PestError<Rule>> for ParseError<'src> {
+    fn from(value: PestError<Rule>) -> Self {
+        Self::Parse(Box::new(value))
+    }
+}
+
 pub(crate) type ParseResult<'src, T> = Result<T, ParseError<'src>>;
 
 /// An error in evaluating a function.
@@ -53,10 +70,58 @@ impl<'src> Display for EvalError<'src> {
     }
 }
 
+impl<'src> EvalError<'src> {
+    /// Where the error occurred: the failing expression if it's known,
+    /// otherwise the innermost context entry with a position.
+    pub fn location(&self) -> Option<&SourceLocation> {
+        self.error_type
+            .location()
+            .or_else(|| self.context_entries.iter().find_map(ContextEntry::pos))
+    }
+
+    /// Like the [`Display`] output but followed by the line of `src` where the
+    /// error occurred, with a `^` under the column.
+    ///
+    /// `src` should be the source of the document in [`EvalError::location`].
+    /// The snippet is left out if there's no location or `src` is too short.
+    pub fn render_with_source(&self, src: &str) -> String {
+        let mut text = self.to_string();
+        let Some(location) = self.location() else {
+            return text;
+        };
+
+        let normalized = src.replace("\r\n", "\n").replace('\r', "\n");
+        let Some(line) = normalized.split('\n').nth(location.line.saturating_sub(1)) else {
+            return text;
+        };
+
+        // Keep tabs so the caret lines up however they're displayed.
+        let indent: String = line
+            .chars()
+            .take(location.col.saturating_sub(1))
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+        let number = location.line.to_string();
+        let gutter = " ".repeat(number.len());
+
+        if !text.ends_with('\n') {
+            text.push('\n');
+        }
+        text.push_str(&format!(
+            "{gutter} --> line {}, col {} of \"{}\"\n\
+             {gutter} |\n\
+             {number} | {line}\n\
+             {gutter} | {indent}^\n",
+            location.line, location.col, location.doc
+        ));
+        text
+    }
+}
+
 impl<'src> From<ParseError<'src>> for EvalError<'src> {
     fn from(value: ParseError<'src>) -> Self {
         Self {
-            error_type: EvalErrorType::Parse(value),
+            error_type: EvalErrorType::Parse(Box::new(value)),
             context_entries: Vec::default(),
         }
     }
@@ -64,14 +129,69 @@ impl<'src> From<ParseError<'src>> for EvalError<'src> {
 
 pub(crate) type EvalResult<'src, T> = Result<T, EvalError<'src>>;
 
+/// An error in exporting a solid from a [`crate::SolidSet`].
+#[derive(Error, Debug)]
+pub enum ExportError<'src> {
+    #[error("{0}")]
+    Eval(EvalError<'src>),
+    #[error("IO Error \"{0}\"")]
+    IO(#[from] IoError),
+}
+
+impl<'src> From<EvalError<'src>> for ExportError<'src> {
+    fn from(value: EvalError<'src>) -> Self {
+        Self::Eval(value)
+    }
+}
+
+/// The number of significant digits numbers are rounded to in error messages.
+///
+/// This hides floating point noise like `0.30000000000000004` while keeping
+/// enough precision to tell apart any values a user is likely to write.
+pub(crate) const NUMBER_DISPLAY_DIGITS: i32 = 10;
+
+/// Formats a number for an error message, rounded to
+/// [`NUMBER_DISPLAY_DIGITS`] significant digits without trailing zeros.
+pub(crate) fn format_number(num: f64) -> String {
+    if num == 0. || !num.is_finite() {
+        return num.to_string();
+    }
+
+    let magnitude = num.abs().log10().floor() as i32;
+    let decimals = NUMBER_DISPLAY_DIGITS - 1 - magnitude;
+    if decimals < 0 {
+        // Rounding to a power of ten and printing without decimals would still
+        // print every digit of the float, so the significant digits are taken
+        // from scientific notation and padded with zeros instead.
+        let text = format!("{:.*e}", NUMBER_DISPLAY_DIGITS as usize - 1, num.abs());
+        let Some((mantissa, exponent)) = text.split_once('e') else {
+            return text;
+        };
+        let digits = mantissa.replace('.', "");
+        let zeros = exponent.parse::<usize>().unwrap_or_default() + 1 - digits.len();
+        let sign = if num < 0. { "-" } else { "" };
+        return format!("{sign}{digits}{}", "0".repeat(zeros));
+    }
+
+    let decimals = decimals as usize;
+    let text = format!("{num:.decimals$}");
+    if text.contains('.') {
+        text.trim_end_matches('0').trim_end_matches('.').into()
+    } else {
+        text
+    }
+}
+
 // The type of an EvalError.
 #[derive(Error, Debug)]
 pub enum EvalErrorType<'src> {
     #[error("Parsing error:\n{0}")]
-    Parse(ParseError<'src>),
+    Parse(Box<ParseError<'src>>),
 
     #[error("Numeric expression was not finite")]
-    NumExprNotFinite,
+    NumExprNotFinite { location: Option<SourceLocation> },
+    #[error("Division by zero")]
+    DivisionByZero { location: Option<SourceLocation> },
 
     #[error("The import \"{name}\" was not found")]
     ImportNotFound { name: String },
@@ -79,6 +199,8 @@ pub enum EvalErrorType<'src> {
     DocNotFound { path: FQPath },
     #[error("The function \"{name}\" was not found")]
     FuncNotFound { name: String },
+    #[error("The function \"{name}\" is defined by more than one wildcard import")]
+    AmbiguousFunction { name: String },
     #[error("The argument \"{name}\" was not found")]
     ArgNotFound { name: String },
     #[error("The built-in function \"{name}\" was not found")]
@@ -88,25 +210,76 @@ pub enum EvalErrorType<'src> {
     TooManyArgs,
     #[error("No argument named \"{name}\" in function definition")]
     InvalidNamedArg { name: String },
+    #[error("The argument \"{name}\" was supplied both by position and by name")]
+    ArgSuppliedTwice { name: String },
     #[error("No supplied or default value of argument \"{name}\"")]
     NoSuppliedOrDefaultArg { name: String },
+    #[error("The arguments \"{name}\" and \"{other}\" can't both be supplied")]
+    ConflictingArgs { name: String, other: String },
     #[error("The supplied argument \"{name}\" is the wrong type: expected a \"{expected}\"; got a \"{got}\"")]
     ArgWrongType {
         name: String,
         expected: &'static str,
         got: &'static str,
     },
+    #[error("The supplied argument \"{name}\" must be an integer")]
+    ArgNotInteger { name: String },
+    #[error("The supplied argument \"{name}\" must be at most {max}")]
+    ArgAboveMax { name: String, max: usize },
+    #[error("The supplied argument \"{name}\" has a point with {got} coordinates; expected 3")]
+    PointWrongDimension { name: String, got: usize },
 
+    #[error("Cannot perform {op} on a {operand_type}")]
+    UnaryOpWrongType {
+        op: &'static str,
+        operand_type: &'static str,
+        location: Option<SourceLocation>,
+    },
     #[error("Cannot perform {op} between a {lhs_type} and a {rhs_type}")]
     BinaryOpWrongTypes {
         op: &'static str,
         lhs_type: &'static str,
         rhs_type: &'static str,
+        location: Option<SourceLocation>,
     },
 
     #[error("Infinite recursion")]
     InfiniteRecursion,
 
+    #[error("Assertion failed")]
+    AssertionFailed,
+
     #[error("Invalid Solid ID")]
     InvalidSolidId,
+    #[error("The solid is empty")]
+    EmptySolid,
+    #[error("The solid is universal and has no boundary")]
+    UniversalSolid,
+    #[error("The solid is inside out and unbounded")]
+    InvertedSolid,
+    #[error("The geometry operation \"{op}\" failed")]
+    GeometryOperationFailed { op: &'static str },
+    #[error("Invalid dimension \"{name}\": {reason}")]
+    InvalidDimension { name: String, reason: &'static str },
+    #[error("Invalid mesh: {reason}")]
+    InvalidMesh { reason: String },
+    #[error("Invalid solid: {reason}")]
+    InvalidSolid { reason: String },
+    #[error("Dictionaries can only be used as the cases of Switch")]
+    UnexpectedDict,
+    #[error("No case matches \"{key}\" and there's no default")]
+    NoMatchingCase { key: String },
+}
+
+impl<'src> EvalErrorType<'src> {
+    /// The location of the failing expression, for errors that record one.
+    pub fn location(&self) -> Option<&SourceLocation> {
+        match self {
+            EvalErrorType::NumExprNotFinite { location }
+            | EvalErrorType::DivisionByZero { location }
+            | EvalErrorType::UnaryOpWrongType { location, .. }
+            | EvalErrorType::BinaryOpWrongTypes { location, .. } => location.as_ref(),
+            _ => None,
+        }
+    }
 }
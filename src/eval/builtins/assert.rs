@@ -0,0 +1,99 @@
+use std::collections::BTreeMap;
+
+use truck_meshalgo::prelude::*;
+
+use crate::{EvalErrorType, SolidId, SolidSet, Value};
+
+use super::{BuiltInArgDef, BuiltInStatic, EvalContext, EvalResult};
+
+/// Evaluates to `value` if `condition` is truthy and fails otherwise.
+///
+/// Evaluation is lazy, so an assertion only runs if its result is used.
+/// Passing `value` through lets an assertion guard the expression that
+/// depends on it.
+pub(super) struct Assert();
+
+impl BuiltInStatic for Assert {
+    const ARGS: &[BuiltInArgDef] = &[
+        BuiltInArgDef {
+            name: "condition",
+            default: None,
+        },
+        BuiltInArgDef {
+            name: "value",
+            default: None,
+        },
+    ];
+
+    fn eval_static<'src>(
+        _solids: &mut SolidSet,
+        args: &BTreeMap<String, Value>,
+        context: &EvalContext,
+    ) -> EvalResult<'src, Value> {
+        if !Self::truthy_arg("condition", args, context)? {
+            return context.eval_err(EvalErrorType::AssertionFailed);
+        }
+
+        let Some(value) = args.get("value") else {
+            return context.eval_err(EvalErrorType::ArgNotFound {
+                name: "value".into(),
+            });
+        };
+
+        Ok(value.clone())
+    }
+}
+
+/// Describes what's wrong with a shell in the given condition, if anything.
+fn shell_defect(condition: ShellCondition) -> Option<&'static str> {
+    match condition {
+        ShellCondition::Closed => None,
+        ShellCondition::Oriented => Some("open edges"),
+        ShellCondition::Regular => Some("inconsistently oriented faces"),
+        ShellCondition::Irregular => Some("non-manifold edges"),
+    }
+}
+
+/// Evaluates to `solid` if it's a closed manifold and fails otherwise.
+///
+/// Each boundary shell is checked both as a BRep and as the triangulation
+/// that would be exported, so a solid that would produce a broken mesh is
+/// caught here rather than after export. Empty and universal solids have no
+/// boundary to be defective and are passed through.
+pub(super) struct AssertValid();
+
+impl BuiltInStatic for AssertValid {
+    const ARGS: &[BuiltInArgDef] = &[BuiltInArgDef {
+        name: "solid",
+        default: None,
+    }];
+
+    fn eval_static<'src>(
+        solids: &mut SolidSet,
+        args: &BTreeMap<String, Value>,
+        context: &EvalContext,
+    ) -> EvalResult<'src, Value> {
+        let id = Self::solid_arg("solid", args, context)?;
+        let SolidId::Regular(_) = id else {
+            return Ok(Value::Solid(id));
+        };
+
+        let invalid = |reason: String| context.eval_err(EvalErrorType::InvalidSolid { reason });
+        for (i, shell) in solids.try_get(&id)?.boundaries().iter().enumerate() {
+            if let Some(defect) = shell_defect(shell.shell_condition()) {
+                return invalid(format!("shell {i} has {defect}"));
+            }
+            if !shell.singular_vertices().is_empty() {
+                return invalid(format!("shell {i} has non-manifold vertices"));
+            }
+
+            let mut mesh = shell.triangulation(solids.tolerance).to_polygon();
+            mesh.put_together_same_attrs(solids.tolerance);
+            if let Some(defect) = shell_defect(mesh.shell_condition()) {
+                return invalid(format!("the triangulation of shell {i} has {defect}"));
+            }
+        }
+
+        Ok(Value::Solid(id))
+    }
+}
@@ -0,0 +1,129 @@
+use std::collections::BTreeMap;
+
+use crate::{ast::*, error::EvalResult, EvalErrorType, SpannedFuncCallExpr, Value};
+
+use super::{EvalCache, EvalContext, Scope};
+
+/// The name of the `Cache` special form.
+///
+/// `Cache(solid)` evaluates to `solid` but stores the result under a
+/// [`Scope::Inline`] keyed on the span of the wrapped expression.
+///
+/// Function calls are already cached on their name and all of their
+/// arguments. `Cache` instead memoizes an inline expression on only the
+/// arguments it refers to, so an expression inside a function that doesn't
+/// depend on some argument is evaluated once and its solid shared across calls
+/// that differ in that argument.
+pub(super) const CACHE: &str = "Cache";
+
+const CACHE_ARG: &str = "solid";
+
+impl<'set, 'src> EvalCache<'set, 'src> {
+    pub(super) fn eval_cache_call(
+        &mut self,
+        call_expr: &SpannedFuncCallExpr<'src>,
+        scope: &Scope,
+        context: &EvalContext,
+    ) -> EvalResult<'src, Value> {
+        let expr = match &call_expr.args {
+            CallArgs::None => {
+                return context.eval_err(EvalErrorType::NoSuppliedOrDefaultArg {
+                    name: CACHE_ARG.into(),
+                })
+            }
+            CallArgs::Positional(args) => match &args[..] {
+                [expr] => expr.as_ref(),
+                _ => return context.eval_err(EvalErrorType::TooManyArgs),
+            },
+            CallArgs::Named(args) => {
+                if let Some(name) = args.keys().find(|name| **name != CACHE_ARG) {
+                    return context.eval_err(EvalErrorType::InvalidNamedArg {
+                        name: (*name).into(),
+                    });
+                }
+                &args[CACHE_ARG].expr
+            }
+            // `Cache` has only one argument, so anything named after it is
+            // either unknown or supplied twice.
+            CallArgs::Mixed { named, .. } => {
+                if let Some(name) = named.keys().find(|name| **name != CACHE_ARG) {
+                    return context.eval_err(EvalErrorType::InvalidNamedArg {
+                        name: (*name).into(),
+                    });
+                }
+                return context.eval_err(EvalErrorType::ArgSuppliedTwice {
+                    name: CACHE_ARG.into(),
+                });
+            }
+        };
+
+        let doc_path = scope
+            .doc()
+            .expect("Wrong kind of scope for func call evaluation");
+        let mut args = BTreeMap::new();
+        referenced_args(expr, scope.args(), &mut args);
+
+        let inline = Scope::Inline {
+            doc_path: doc_path.clone(),
+            start: expr.span.start(),
+            end: expr.span.end(),
+            args,
+        };
+
+        if let Some(cached) = self.cache.get(&inline) {
+            return Ok(cached.clone());
+        }
+
+        let val = self.eval_expr(expr, scope, context)?;
+        self.cache.insert(inline, val.clone());
+        Ok(val)
+    }
+}
+
+/// Copies the arguments in `args` that `expr` refers to into `referenced`.
+fn referenced_args(
+    expr: &SpannedExpr,
+    args: &BTreeMap<String, Value>,
+    referenced: &mut BTreeMap<String, Value>,
+) {
+    match &expr.inner {
+        Expr::Number(_) | Expr::Str(_) => {}
+        Expr::Unary(unary) => referenced_args(&unary.unit, args, referenced),
+        Expr::Binary(binary) => {
+            referenced_args(&binary.lhs, args, referenced);
+            referenced_args(&binary.rhs, args, referenced);
+        }
+        Expr::FuncCall(call) => {
+            if call.name.import_parts.is_empty() {
+                if let Some(val) = args.get(call.name.name_part.text) {
+                    referenced.insert(call.name.name_part.text.into(), val.clone());
+                }
+            }
+
+            for arg in call.args.positional() {
+                referenced_args(arg, args, referenced);
+            }
+            for (_, arg) in call.args.named() {
+                referenced_args(&arg.expr, args, referenced);
+            }
+        }
+        Expr::List(list) => {
+            for item in &list.items {
+                referenced_args(item, args, referenced);
+            }
+        }
+        Expr::Dict(dict) => {
+            for entry in dict.entries.values() {
+                referenced_args(&entry.expr, args, referenced);
+            }
+        }
+        // Names bound by the let that shadow arguments are copied too, which
+        // only makes the cache key more specific than it needs to be.
+        Expr::Let(let_expr) => {
+            for binding in &let_expr.bindings {
+                referenced_args(&binding.expr, args, referenced);
+            }
+            referenced_args(&let_expr.body, args, referenced);
+        }
+    }
+}
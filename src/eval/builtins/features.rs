@@ -0,0 +1,616 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    f64::consts::PI,
+};
+
+use truck_meshalgo::analyzers::CalcVolume;
+use truck_modeling::{
+    BoundingBox, Deg, EuclideanSpace, InnerSpace, Matrix4, Point3, Solid, Surface, Vector3,
+};
+
+use crate::{EvalErrorType, SolidId, SolidSet, Value};
+
+use super::{
+    import_mesh::solid_from_polygons,
+    measure::{bounding_box, mesh},
+    shapes::cylinder,
+    transforms::{chamfer_planes, convex_face_planes, half_space_intersection},
+    BuiltInArgDef, BuiltInStatic, EvalContext, EvalResult,
+};
+
+/// Drills a `count_x` by `count_y` grid of cylindrical holes of `radius` and
+/// `depth` down into the top of a solid.
+///
+/// The grid is centered on the solid's bounding box, with holes `spacing_x`
+/// and `spacing_y` apart, and `depth` is measured down from the top of the
+/// bounding box. A hole is only as deep as `depth`, so a through hole needs a
+/// `depth` greater than the thickness of the solid rather than equal to it.
+///
+/// Holes that don't fit within the bounding box are still drilled but a
+/// warning is recorded in the [`SolidSet`]. Like the copies of a
+/// [`LinearPattern`], each count can be at most [`MAX_COUNT`].
+///
+/// Like [`super::shapes::Cylinder`], the holes are very slow to cut at the
+/// default tolerance.
+pub(super) struct HoleGrid();
+
+impl BuiltInStatic for HoleGrid {
+    const ARGS: &[BuiltInArgDef] = &[
+        BuiltInArgDef {
+            name: "solid",
+            default: None,
+        },
+        BuiltInArgDef {
+            name: "radius",
+            default: None,
+        },
+        BuiltInArgDef {
+            name: "spacing_x",
+            default: None,
+        },
+        BuiltInArgDef {
+            name: "spacing_y",
+            default: None,
+        },
+        BuiltInArgDef {
+            name: "count_x",
+            default: None,
+        },
+        BuiltInArgDef {
+            name: "count_y",
+            default: None,
+        },
+        BuiltInArgDef {
+            name: "depth",
+            default: None,
+        },
+    ];
+
+    fn eval_static<'src>(
+        solids: &mut SolidSet,
+        args: &BTreeMap<String, Value>,
+        context: &EvalContext,
+    ) -> EvalResult<'src, Value> {
+        let solid = Self::solid_arg("solid", args, context)?;
+        let radius = Self::positive_arg("radius", args, context)?;
+        let spacing_x = Self::num_arg("spacing_x", args, context)?;
+        let spacing_y = Self::num_arg("spacing_y", args, context)?;
+        let depth = Self::positive_arg("depth", args, context)?;
+        let count_x = count_arg("count_x", args, context)?;
+        let count_y = count_arg("count_y", args, context)?;
+
+        let bbox = bounding_box(solids, &solid, context)?;
+        let (min, max) = (bbox.min(), bbox.max());
+        let center = min.midpoint(max);
+
+        let first = Vector3::new(
+            center.x - (count_x - 1) as f64 / 2. * spacing_x,
+            center.y - (count_y - 1) as f64 / 2. * spacing_y,
+            max.z - depth,
+        );
+        // Holes stick out of the top so their ends aren't coplanar with it.
+        let hole = solids.push(cylinder(radius, depth + radius));
+        let hole = solids.transform(&hole, Matrix4::from_translation(first))?;
+
+        // A row of holes along X, each patterned along Y.
+        let mut holes = Vec::with_capacity(count_x * count_y);
+        for hole in linear_copies(solids, &hole, Vector3::unit_x() * spacing_x, count_x)? {
+            let column = linear_copies(solids, &hole, Vector3::unit_y() * spacing_y, count_y)?;
+            holes.extend(column);
+        }
+
+        // A hole is outside if it's outside along either axis.
+        let fitting = |first: f64, spacing: f64, count: usize, min: f64, max: f64| {
+            (0..count)
+                .map(|i| first + i as f64 * spacing)
+                .filter(|c| c - radius >= min && c + radius <= max)
+                .count()
+        };
+        let outside = count_x * count_y
+            - fitting(first.x, spacing_x, count_x, min.x, max.x)
+                * fitting(first.y, spacing_y, count_y, min.y, max.y);
+
+        // Neighbouring holes are closest, so if they're apart all of them are
+        // and their shells make up the holes without any boolean operations.
+        let apart = |count: usize, spacing: f64| {
+            count == 1 || spacing.abs() > 2. * radius + solids.tolerance
+        };
+        let holes = if apart(count_x, spacing_x) && apart(count_y, spacing_y) {
+            let mut shells = Vec::new();
+            for hole in &holes {
+                shells.extend(solids.try_get(hole)?.boundaries().iter().cloned());
+            }
+            solids.push(Solid::new(shells))
+        } else {
+            union_balanced(solids, holes, "HoleGrid", context)?
+        };
+
+        if outside > 0 {
+            solids.warn(format!(
+                "HoleGrid: {outside} of {} holes don't fit within the solid's bounds",
+                count_x * count_y
+            ));
+        }
+
+        // truck sometimes gives nothing rather than failing, which can't be
+        // right if the solid is bigger than all the holes put together.
+        let drilled = solids.difference(&solid, &holes)?;
+        let hole_volume = (count_x * count_y) as f64 * PI * radius * radius * (depth + radius);
+        if drilled == SolidId::Empty && mesh(solids, &solid, context)?.volume() > hole_volume {
+            return context.eval_err(EvalErrorType::GeometryOperationFailed { op: "HoleGrid" });
+        }
+
+        Ok(Value::Solid(drilled))
+    }
+}
+
+/// The bounding box of a solid with only planar faces, which is the box around
+/// its vertices, or `None` if it has curved faces.
+fn planar_bounds(solid: &Solid) -> Option<BoundingBox<Point3>> {
+    if solid
+        .face_iter()
+        .any(|face| !matches!(face.surface(), Surface::Plane(_)))
+    {
+        return None;
+    }
+
+    Some(solid.vertex_iter().map(|v| v.point()).collect())
+}
+
+/// Unions the copies of a solid in a pattern.
+///
+/// truck's boolean operations often fail between solids that don't touch but
+/// have faces in the same plane, as the copies in a pattern usually do. When
+/// every copy has only planar faces and their bounding boxes are all at least
+/// the set's tolerance apart, the copies are instead combined by collecting
+/// their shells into one solid. Otherwise they're unioned by
+/// [`union_balanced`], which fails for `op` if truck can't union them.
+pub(super) fn union_copies<'src>(
+    solids: &mut SolidSet,
+    copies: Vec<SolidId>,
+    op: &'static str,
+    context: &EvalContext,
+) -> EvalResult<'src, SolidId> {
+    let mut bounds = Vec::with_capacity(copies.len());
+    for copy in &copies {
+        let SolidId::Regular(_) = copy else {
+            return union_balanced(solids, copies, op, context);
+        };
+        let Some(copy_bounds) = planar_bounds(solids.try_get(copy)?) else {
+            return union_balanced(solids, copies, op, context);
+        };
+        bounds.push(copy_bounds);
+    }
+
+    let tolerance = solids.tolerance;
+    let apart = |a: &BoundingBox<Point3>, b: &BoundingBox<Point3>| {
+        (0..3).any(|axis| {
+            a.max()[axis] + tolerance < b.min()[axis] || b.max()[axis] + tolerance < a.min()[axis]
+        })
+    };
+    let all_apart = bounds
+        .iter()
+        .enumerate()
+        .all(|(i, a)| bounds[i + 1..].iter().all(|b| apart(a, b)));
+    if copies.len() < 2 || !all_apart {
+        return union_balanced(solids, copies, op, context);
+    }
+
+    let mut shells = Vec::new();
+    for copy in &copies {
+        shells.extend(solids.try_get(copy)?.boundaries().iter().cloned());
+    }
+    Ok(solids.push(Solid::new(shells)))
+}
+
+/// Unions a list of solids pairwise in a balanced tree.
+///
+/// Each boolean operation costs more the more faces its operands have, so
+/// combining similar sized halves is much faster than folding copies one at a
+/// time into a growing solid. An empty list gives the empty solid.
+///
+/// The union of two regular solids is never empty, so truck giving nothing
+/// fails with [`EvalErrorType::GeometryOperationFailed`] for `op`, like truck
+/// panicking does in [`SolidSet::union`].
+fn union_balanced<'src>(
+    solids: &mut SolidSet,
+    mut ids: Vec<SolidId>,
+    op: &'static str,
+    context: &EvalContext,
+) -> EvalResult<'src, SolidId> {
+    while ids.len() > 1 {
+        let mut combined = Vec::with_capacity(ids.len().div_ceil(2));
+        let mut pairs = ids.chunks_exact(2);
+        for pair in pairs.by_ref() {
+            let (lhs, rhs) = (&pair[0], &pair[1]);
+            match solids.union(lhs, rhs)? {
+                SolidId::Empty
+                    if matches!((lhs, rhs), (SolidId::Regular(_), SolidId::Regular(_))) =>
+                {
+                    return context.eval_err(EvalErrorType::GeometryOperationFailed { op })
+                }
+                union => combined.push(union),
+            }
+        }
+        combined.extend_from_slice(pairs.remainder());
+        ids = combined;
+    }
+
+    Ok(ids.pop().unwrap_or(SolidId::Empty))
+}
+
+/// Unions `count` copies of a solid, the `i`th moved by `i` times (`x`, `y`,
+/// `z`).
+///
+/// The first copy is the solid itself. Any of `x`, `y` and `z` left out are
+/// zero, or the offset can instead be a vector passed as `offset`, as in
+/// `LinearPattern(s, count=3, offset=Vec3(2, 0, 0))`. Copies that touch
+/// share faces, which truck can't always combine, so copies are best spaced
+/// apart or overlapped by a clear margin. `count` must be a positive integer
+/// and can be at most [`MAX_COUNT`], which is 100.
+pub(super) struct LinearPattern();
+
+impl BuiltInStatic for LinearPattern {
+    const ARGS: &[BuiltInArgDef] = &[
+        BuiltInArgDef {
+            name: "solid",
+            default: None,
+        },
+        BuiltInArgDef {
+            name: "x",
+            default: None,
+        },
+        BuiltInArgDef {
+            name: "y",
+            default: None,
+        },
+        BuiltInArgDef {
+            name: "z",
+            default: None,
+        },
+        BuiltInArgDef {
+            name: "count",
+            default: None,
+        },
+        BuiltInArgDef {
+            name: "offset",
+            default: None,
+        },
+    ];
+    const OPTIONAL_ARGS: &[&str] = &["x", "y", "z", "offset"];
+
+    fn eval_static<'src>(
+        solids: &mut SolidSet,
+        args: &BTreeMap<String, Value>,
+        context: &EvalContext,
+    ) -> EvalResult<'src, Value> {
+        let solid = Self::solid_arg("solid", args, context)?;
+        let offset = Self::vector_or_components_arg("offset", ["x", "y", "z"], args, context)?;
+        let count = count_arg("count", args, context)?;
+
+        let copies = linear_copies(solids, &solid, offset, count)?;
+        Ok(Value::Solid(union_copies(
+            solids,
+            copies,
+            "LinearPattern",
+            context,
+        )?))
+    }
+}
+
+/// The `count` copies of `solid` in a [`LinearPattern`], the `i`th moved by
+/// `i` times `offset`.
+fn linear_copies<'src>(
+    solids: &mut SolidSet,
+    solid: &SolidId,
+    offset: Vector3,
+    count: usize,
+) -> EvalResult<'src, Vec<SolidId>> {
+    let mut copies = Vec::with_capacity(count);
+    for i in 0..count {
+        let mat = Matrix4::from_translation(offset * i as f64);
+        copies.push(solids.transform(solid, mat)?);
+    }
+
+    Ok(copies)
+}
+
+/// The most copies a pattern makes, or holes [`HoleGrid`] drills along either
+/// axis.
+const MAX_COUNT: usize = 100;
+
+/// A count of copies or holes, which must be a whole number from 1 to
+/// [`MAX_COUNT`].
+///
+/// A count that isn't a positive integer is an
+/// [`EvalErrorType::ArgNotInteger`] error and one over the cap is an
+/// [`EvalErrorType::ArgAboveMax`] error.
+fn count_arg<'src>(
+    name: &str,
+    args: &BTreeMap<String, Value>,
+    context: &EvalContext,
+) -> EvalResult<'src, usize> {
+    let count = LinearPattern::int_arg(name, args, context)?;
+    if count < 1. {
+        return context.eval_err(EvalErrorType::ArgNotInteger { name: name.into() });
+    }
+    if count > MAX_COUNT as f64 {
+        return context.eval_err(EvalErrorType::ArgAboveMax {
+            name: name.into(),
+            max: MAX_COUNT,
+        });
+    }
+
+    Ok(count as usize)
+}
+
+/// Unions `count` copies of a solid, the `i`th rotated about the Z axis by `i`
+/// times `angle / count` degrees.
+///
+/// The first copy is the solid itself. With the default `angle` of 360 the
+/// copies are spread evenly around a full turn, as for a bolt circle or the
+/// teeth of a gear. Like [`LinearPattern`], `count` can be at most
+/// [`MAX_COUNT`].
+pub(super) struct PolarPattern();
+
+impl BuiltInStatic for PolarPattern {
+    const ARGS: &[BuiltInArgDef] = &[
+        BuiltInArgDef {
+            name: "solid",
+            default: None,
+        },
+        BuiltInArgDef {
+            name: "count",
+            default: None,
+        },
+        BuiltInArgDef {
+            name: "angle",
+            default: Some(Value::Number(360.)),
+        },
+    ];
+
+    fn eval_static<'src>(
+        solids: &mut SolidSet,
+        args: &BTreeMap<String, Value>,
+        context: &EvalContext,
+    ) -> EvalResult<'src, Value> {
+        let solid = Self::solid_arg("solid", args, context)?;
+        let count = count_arg("count", args, context)?;
+        let angle = Self::num_arg("angle", args, context)?;
+        if !angle.is_finite() {
+            return context.eval_err(EvalErrorType::NumExprNotFinite {
+                location: context.location(),
+            });
+        }
+
+        let step = angle / count as f64;
+        let mut copies = Vec::new();
+        for i in 0..count {
+            let mat = Matrix4::from_angle_z(Deg(step * i as f64));
+            copies.push(solids.transform(&solid, mat)?);
+        }
+
+        Ok(Value::Solid(union_copies(
+            solids,
+            copies,
+            "PolarPattern",
+            context,
+        )?))
+    }
+}
+
+/// A straight edge between two planar faces.
+struct PlanarEdge {
+    start: Point3,
+    end: Point3,
+    // The outward normal of each face and the direction within each face
+    // pointing away from the edge.
+    normals: [Vector3; 2],
+    inward: [Vector3; 2],
+}
+
+/// Finds the edges of `solid` with both ends within `tolerance` of the height
+/// `z`, along with the number of those edges that aren't between two planar
+/// faces.
+fn horizontal_edges_at(solid: &Solid, z: f64, tolerance: f64) -> (Vec<PlanarEdge>, usize) {
+    // Edges in the order they're found, so wedges are always combined in the
+    // same order.
+    let mut order = Vec::new();
+    let mut sides = HashMap::new();
+    let mut curved = HashSet::new();
+    for face in solid.face_iter() {
+        let normal = match face.oriented_surface() {
+            Surface::Plane(plane) => Some(plane.normal()),
+            _ => None,
+        };
+
+        for boundary in face.boundary_iters() {
+            for edge in boundary {
+                let (start, end) = (edge.front().point(), edge.back().point());
+                if (start.z - z).abs() > tolerance || (end.z - z).abs() > tolerance {
+                    continue;
+                }
+
+                let Some(normal) = normal else {
+                    curved.insert(edge.id());
+                    continue;
+                };
+
+                // Boundaries run counter-clockwise around the outward normal,
+                // so the face lies to the left of each edge.
+                let inward = normal.cross(end - start).normalize();
+                sides
+                    .entry(edge.id())
+                    .or_insert_with(|| {
+                        order.push(edge.id());
+                        Vec::new()
+                    })
+                    .push((start, end, normal, inward));
+            }
+        }
+    }
+
+    let edges = order
+        .into_iter()
+        .filter(|id| !curved.contains(id))
+        .filter_map(|id| match &sides[&id][..] {
+            [(start, end, n0, t0), (_, _, n1, t1)] => Some(PlanarEdge {
+                start: *start,
+                end: *end,
+                normals: [*n0, *n1],
+                inward: [*t0, *t1],
+            }),
+            _ => None,
+        })
+        .collect();
+
+    (edges, curved.len())
+}
+
+/// The triangular prism cut away from `edge` to chamfer it by `distance`.
+///
+/// The prism overhangs the solid at the edge and past both of its ends so
+/// only its chamfer face cuts through the solid.
+fn chamfer_wedge(edge: &PlanarEdge, distance: f64) -> Result<Solid, String> {
+    // The overhangs differ so the wedges of neighbouring edges don't share
+    // any planes.
+    let (overhang, overrun) = (2. * distance, 3. * distance);
+    let dir = (edge.end - edge.start).normalize();
+    let start = edge.start - dir * overrun;
+    let length = (edge.end - edge.start).magnitude() + 2. * overrun;
+
+    // The chamfer runs from `distance` along one face to `distance` along the
+    // other, and is extended past both faces.
+    let [t0, t1] = edge.inward;
+    let (cut0, cut1) = (start + t0 * distance, start + t1 * distance);
+    let mut a = cut0 + (cut0 - cut1);
+    let mut b = cut1 + (cut1 - cut0);
+    let c = start - (t0 + t1) * overhang;
+    // The cap at the start must face back along the edge.
+    if (b - a).cross(c - a).dot(dir) > 0. {
+        std::mem::swap(&mut a, &mut b);
+    }
+
+    let mut points = vec![a, b, c];
+    points.extend([a, b, c].map(|pt| pt + dir * length));
+    let faces = [
+        vec![0, 1, 2],
+        vec![3, 5, 4],
+        vec![1, 0, 3, 4],
+        vec![2, 1, 4, 5],
+        vec![0, 2, 5, 3],
+    ];
+
+    solid_from_polygons(&points, &faces)
+}
+
+/// Chamfers the horizontal edges around the top and/or bottom of a solid.
+///
+/// `top` and `bottom` are conditions selecting which edges are chamfered. An
+/// edge is on the top (or bottom) when both of its ends are within the set's
+/// tolerance of the top (or bottom) of the solid's bounding box, so edges of
+/// steps or pockets at other heights are left alone.
+///
+/// Only convex edges between two planar faces are chamfered, by cutting
+/// `distance` back along each face. Edges touching curved faces are skipped
+/// and a warning is recorded in the [`SolidSet`].
+///
+/// Like `Chamfer`, a convex solid with planar faces is chamfered by cutting it
+/// with a plane across each edge. Any other solid has each edge cut away with
+/// its own wedge. truck often can't combine the wedges of edges that meet at a
+/// corner, in which case an error is returned rather than a partly chamfered
+/// solid.
+pub(super) struct ChamferZ();
+
+impl BuiltInStatic for ChamferZ {
+    const ARGS: &[BuiltInArgDef] = &[
+        BuiltInArgDef {
+            name: "solid",
+            default: None,
+        },
+        BuiltInArgDef {
+            name: "distance",
+            default: None,
+        },
+        BuiltInArgDef {
+            name: "top",
+            default: Some(Value::Number(1.)),
+        },
+        BuiltInArgDef {
+            name: "bottom",
+            default: Some(Value::Number(1.)),
+        },
+    ];
+
+    fn eval_static<'src>(
+        solids: &mut SolidSet,
+        args: &BTreeMap<String, Value>,
+        context: &EvalContext,
+    ) -> EvalResult<'src, Value> {
+        let solid = Self::solid_arg("solid", args, context)?;
+        let distance = Self::positive_arg("distance", args, context)?;
+        let top = Self::truthy_arg("top", args, context)?;
+        let bottom = Self::truthy_arg("bottom", args, context)?;
+
+        let bbox = bounding_box(solids, &solid, context)?;
+        let mut heights = Vec::new();
+        if top {
+            heights.push(bbox.max().z);
+        }
+        if bottom {
+            heights.push(bbox.min().z);
+        }
+
+        let tolerance = solids.tolerance;
+        let at_height = |start: Point3, end: Point3| {
+            heights
+                .iter()
+                .any(|z| (start.z - z).abs() <= tolerance && (end.z - z).abs() <= tolerance)
+        };
+        if let Ok(mut planes) = convex_face_planes(solids.try_get(&solid)?, tolerance, "chamfered")
+        {
+            planes.extend(chamfer_planes(solids.try_get(&solid)?, distance, at_height));
+            return match half_space_intersection(&planes, tolerance) {
+                Ok(Some(chamfered)) => Ok(Value::Solid(solids.push(chamfered))),
+                Ok(None) | Err(_) => {
+                    context.eval_err(EvalErrorType::GeometryOperationFailed { op: "ChamferZ" })
+                }
+            };
+        }
+
+        let mut edges = Vec::new();
+        let mut skipped = 0;
+        for z in heights {
+            let (found, curved) = horizontal_edges_at(solids.try_get(&solid)?, z, solids.tolerance);
+            edges.extend(found);
+            skipped += curved;
+        }
+
+        let mut wedges = SolidId::Empty;
+        for edge in edges {
+            // Faces of a convex edge fall away from each other's planes.
+            if edge.inward[0].dot(edge.normals[1]) >= 0. {
+                continue;
+            }
+
+            let wedge = chamfer_wedge(&edge, distance)
+                .or_else(|reason| context.eval_err(EvalErrorType::InvalidSolid { reason }))?;
+            let wedge = solids.push(wedge);
+            let combined = solids.union(&wedges, &wedge)?;
+            if combined == SolidId::Empty {
+                return context.eval_err(EvalErrorType::InvalidSolid {
+                    reason: "the chamfers of neighbouring edges couldn't be combined".into(),
+                });
+            }
+            wedges = combined;
+        }
+
+        if skipped > 0 {
+            solids.warn(format!(
+                "ChamferZ: {skipped} edges next to curved faces weren't chamfered"
+            ));
+        }
+
+        Ok(Value::Solid(solids.difference(&solid, &wedges)?))
+    }
+}
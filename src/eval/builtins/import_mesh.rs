@@ -0,0 +1,163 @@
+use std::collections::{BTreeMap, HashMap};
+
+use truck_modeling::{
+    builder, Edge, EuclideanSpace, InnerSpace, Point3, Shell, Solid, Vertex, Wire,
+};
+
+use crate::{EvalErrorType, SolidSet, Value};
+
+use super::{BuiltInArgDef, BuiltInStatic, EvalContext, EvalResult};
+
+/// Builds a solid from triangles indexing into `points`.
+///
+/// Triangles must be wound counter-clockwise when viewed from outside the
+/// solid and together form one or more closed, manifold surfaces. On failure
+/// a description of the defect is returned.
+pub(super) fn solid_from_triangles(
+    points: &[Point3],
+    triangles: &[[usize; 3]],
+) -> Result<Solid, String> {
+    if triangles.is_empty() {
+        return Err("a mesh needs at least one face".into());
+    }
+
+    // Every directed edge must appear exactly once and be matched by the same
+    // edge in the opposite direction in a neighbouring triangle.
+    let mut directed = HashMap::new();
+    for (face, tri) in triangles.iter().enumerate() {
+        if tri[0] == tri[1] || tri[1] == tri[2] || tri[2] == tri[0] {
+            return Err(format!("face {face} uses the same vertex twice"));
+        }
+
+        for (a, b) in [(tri[0], tri[1]), (tri[1], tri[2]), (tri[2], tri[0])] {
+            if directed.insert((a, b), face).is_some() {
+                return Err(format!(
+                    "edge ({a}, {b}) is shared by more than two faces or faces are inconsistently wound"
+                ));
+            }
+        }
+    }
+
+    if let Some((a, b)) = directed
+        .keys()
+        .find(|(a, b)| !directed.contains_key(&(*b, *a)))
+    {
+        return Err(format!("the mesh is not closed at edge ({a}, {b})"));
+    }
+
+    let signed_volume: f64 = triangles
+        .iter()
+        .map(|[a, b, c]| {
+            let (a, b, c) = (
+                points[*a].to_vec(),
+                points[*b].to_vec(),
+                points[*c].to_vec(),
+            );
+            a.dot(b.cross(c)) / 6.
+        })
+        .sum();
+    if signed_volume < 0. {
+        return Err("faces are wound clockwise when viewed from outside".into());
+    }
+
+    let faces: Vec<Vec<usize>> = triangles.iter().map(|tri| tri.to_vec()).collect();
+    solid_from_polygons(points, &faces)
+}
+
+/// Builds a solid from planar polygons indexing into `points`.
+///
+/// Like [`solid_from_triangles`] faces must be wound counter-clockwise when
+/// viewed from outside, but the faces aren't checked beyond what truck checks
+/// when building the solid.
+pub(super) fn solid_from_polygons(
+    points: &[Point3],
+    faces: &[Vec<usize>],
+) -> Result<Solid, String> {
+    let vertices: Vec<Vertex> = points.iter().map(|pt| builder::vertex(*pt)).collect();
+    let mut edges: HashMap<(usize, usize), Edge> = HashMap::new();
+    let mut edge = |a: usize, b: usize| -> Edge {
+        let key = (a.min(b), a.max(b));
+        let edge = edges
+            .entry(key)
+            .or_insert_with(|| builder::line(&vertices[key.0], &vertices[key.1]));
+        if a < b {
+            edge.clone()
+        } else {
+            edge.inverse()
+        }
+    };
+
+    let mut shell = Shell::new();
+    for (index, face) in faces.iter().enumerate() {
+        let wire: Wire = face
+            .iter()
+            .zip(face.iter().cycle().skip(1))
+            .map(|(a, b)| edge(*a, *b))
+            .collect();
+        let face = builder::try_attach_plane(&[wire])
+            .map_err(|_| format!("face {index} is degenerate"))?;
+        shell.push(face);
+    }
+
+    Solid::try_new(shell.connected_components()).map_err(|e| e.to_string())
+}
+
+/// A solid built from explicit vertices and triangles.
+///
+/// `vertices` is a flat list of coordinates `[x0, y0, z0, x1, ...]` and `faces`
+/// a flat list of vertex indices with three per triangle.
+pub(super) struct Mesh();
+
+impl BuiltInStatic for Mesh {
+    const ARGS: &[BuiltInArgDef] = &[
+        BuiltInArgDef {
+            name: "vertices",
+            default: None,
+        },
+        BuiltInArgDef {
+            name: "faces",
+            default: None,
+        },
+    ];
+
+    fn eval_static<'src>(
+        solids: &mut SolidSet,
+        args: &BTreeMap<String, Value>,
+        context: &EvalContext,
+    ) -> EvalResult<'src, Value> {
+        let coords = Self::num_list_arg("vertices", args, context)?;
+        let indices = Self::num_list_arg("faces", args, context)?;
+
+        let invalid = |reason: String| context.eval_err(EvalErrorType::InvalidMesh { reason });
+
+        if coords.len() % 3 != 0 {
+            return invalid("the number of vertex coordinates must be a multiple of 3".into());
+        }
+        if indices.len() % 3 != 0 {
+            return invalid("the number of face indices must be a multiple of 3".into());
+        }
+
+        let points: Vec<Point3> = coords
+            .chunks_exact(3)
+            .map(|c| Point3::new(c[0], c[1], c[2]))
+            .collect();
+
+        let mut vert_indices = Vec::with_capacity(indices.len());
+        for index in indices {
+            if index.fract() != 0. || index < 0. || index >= points.len() as f64 {
+                return invalid(format!("face index {index} is not a valid vertex index"));
+            }
+            vert_indices.push(index as usize);
+        }
+
+        let triangles: Vec<[usize; 3]> = vert_indices
+            .chunks_exact(3)
+            .map(|i| [i[0], i[1], i[2]])
+            .collect();
+
+        match solid_from_triangles(&points, &triangles) {
+            Ok(solid) => Ok(Value::Solid(solids.push(solid))),
+            Err(reason) => invalid(reason),
+        }
+    }
+}
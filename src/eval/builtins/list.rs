@@ -0,0 +1,99 @@
+use std::collections::BTreeMap;
+
+use crate::{EvalErrorType, SolidSet, Value};
+
+use super::{BuiltInArgDef, BuiltInStatic, EvalContext, EvalResult};
+
+/// The most numbers a range can have.
+const MAX_RANGE_LEN: f64 = 10_000_000.;
+
+/// Numbers from `start` counting by `step` up to but not including `end`.
+fn range<'src>(start: f64, end: f64, step: f64, context: &EvalContext) -> EvalResult<'src, Value> {
+    let count = ((end - start) / step).ceil();
+    if !count.is_finite() {
+        return context.eval_err(EvalErrorType::NumExprNotFinite {
+            location: context.location(),
+        });
+    }
+    if count > MAX_RANGE_LEN {
+        return context.eval_err(EvalErrorType::InvalidDimension {
+            name: "end".into(),
+            reason: "gives a range with too many numbers",
+        });
+    }
+
+    let count = count.max(0.) as usize;
+    Ok(Value::List(
+        (0..count)
+            .map(|i| Value::Number(start + i as f64 * step))
+            .collect(),
+    ))
+}
+
+/// The integers from `start` up to but not including `end`.
+pub(super) struct Range();
+
+impl BuiltInStatic for Range {
+    const ARGS: &[BuiltInArgDef] = &[
+        BuiltInArgDef {
+            name: "start",
+            default: None,
+        },
+        BuiltInArgDef {
+            name: "end",
+            default: None,
+        },
+    ];
+
+    fn eval_static<'src>(
+        _solids: &mut SolidSet,
+        args: &BTreeMap<String, Value>,
+        context: &EvalContext,
+    ) -> EvalResult<'src, Value> {
+        let start = Self::int_arg("start", args, context)?;
+        let end = Self::int_arg("end", args, context)?;
+
+        range(start, end, 1., context)
+    }
+}
+
+/// Numbers from `start` counting by `step` up to but not including `end`.
+///
+/// A negative `step` counts down to `end`. Each number is computed from
+/// `start` rather than by repeatedly adding `step` so errors don't accumulate.
+pub(super) struct RangeStep();
+
+impl BuiltInStatic for RangeStep {
+    const ARGS: &[BuiltInArgDef] = &[
+        BuiltInArgDef {
+            name: "start",
+            default: None,
+        },
+        BuiltInArgDef {
+            name: "end",
+            default: None,
+        },
+        BuiltInArgDef {
+            name: "step",
+            default: None,
+        },
+    ];
+
+    fn eval_static<'src>(
+        _solids: &mut SolidSet,
+        args: &BTreeMap<String, Value>,
+        context: &EvalContext,
+    ) -> EvalResult<'src, Value> {
+        let start = Self::num_arg("start", args, context)?;
+        let end = Self::num_arg("end", args, context)?;
+        let step = Self::num_arg("step", args, context)?;
+
+        if step == 0. {
+            return context.eval_err(EvalErrorType::NumExprNotFinite {
+                location: context.location(),
+            });
+        }
+
+        range(start, end, step, context)
+    }
+}
@@ -0,0 +1,89 @@
+use std::collections::BTreeMap;
+
+use crate::{EvalErrorType, SpannedFuncCallExpr, Value};
+
+use super::{BuiltInArgDef, EvalCache, EvalContext, EvalResult, Scope};
+
+/// The name of the `Map` special form.
+///
+/// `Map(func, list)` calls the function named by the string `func` with each
+/// item of `list` as its first argument and evaluates to a list of the
+/// results. Any other arguments of the function take their defaults.
+///
+/// There are no closures, so `func` is resolved by name in the document
+/// containing the call and only functions defined in that document can be
+/// mapped. Unlike other names, `func` isn't checked until evaluation; an
+/// unknown name is a [`EvalErrorType::FuncNotFound`] error and a function
+/// without arguments is a [`EvalErrorType::TooManyArgs`] error.
+pub(super) const MAP: &str = "Map";
+
+const MAP_ARGS: &[BuiltInArgDef] = &[
+    BuiltInArgDef {
+        name: "func",
+        default: None,
+    },
+    BuiltInArgDef {
+        name: "list",
+        default: None,
+    },
+];
+
+impl<'set, 'src> EvalCache<'set, 'src> {
+    pub(super) fn eval_map_call(
+        &mut self,
+        call_expr: &SpannedFuncCallExpr<'src>,
+        scope: &Scope,
+        context: &EvalContext,
+    ) -> EvalResult<'src, Value> {
+        let args =
+            self.eval_supplied_built_in_call_args(call_expr, MAP_ARGS, false, scope, context)?;
+        let arg = |name: &str| {
+            args.get(name).ok_or_else(|| {
+                context.error(EvalErrorType::NoSuppliedOrDefaultArg { name: name.into() })
+            })
+        };
+
+        let Value::String(func_name) = arg("func")? else {
+            return context.eval_err(EvalErrorType::ArgWrongType {
+                name: "func".into(),
+                expected: Value::STRING_TYPE_NAME,
+                got: arg("func")?.type_name(),
+            });
+        };
+        let Value::List(items) = arg("list")? else {
+            return context.eval_err(EvalErrorType::ArgWrongType {
+                name: "list".into(),
+                expected: Value::LIST_TYPE_NAME,
+                got: arg("list")?.type_name(),
+            });
+        };
+
+        let doc_path = scope
+            .doc()
+            .expect("Wrong kind of scope for func call evaluation");
+        let doc = self.doc(doc_path, context)?;
+        let Some(func) = doc.funcs.get(func_name.as_str()) else {
+            return context.eval_err(EvalErrorType::FuncNotFound {
+                name: func_name.clone(),
+            });
+        };
+        let Some(arg_def) = func.args.as_ref().and_then(|defs| defs.args.first()) else {
+            return context.eval_err(EvalErrorType::TooManyArgs);
+        };
+
+        let mut results = Vec::with_capacity(items.len());
+        for item in items {
+            let mut func_args = BTreeMap::from([(arg_def.name.text.into(), item.clone())]);
+            self.add_default_func_def_args(&mut func_args, func, doc_path, context)?;
+
+            let scope = Scope::FuncCall {
+                name: func_name.clone(),
+                args: func_args,
+                doc_path: doc_path.clone(),
+            };
+            results.push(self.eval_scope(&scope, context)?);
+        }
+
+        Ok(Value::List(results))
+    }
+}
@@ -1,4 +1,4 @@
-use std::collections::BTreeMap;
+use std::{collections::BTreeMap, f64::consts};
 
 use truck_modeling::cgmath::AbsDiffEq;
 
@@ -63,9 +63,743 @@ impl BuiltInStatic for Tan {
         // radians and PI/2 is irrational.
         // When dealing with degrees, Tan(90) should be undefined though.
         if angle.rem_euclid(90.).abs_diff_eq(&0., solids.tolerance) {
-            return context.eval_err(EvalErrorType::NumExprNotFinite);
+            return context.eval_err(EvalErrorType::NumExprNotFinite {
+                location: context.location(),
+            });
         }
 
         Ok(Value::Number(f64::tan(angle.to_radians())))
     }
 }
+
+/// The angle in degrees whose sine is `x`.
+///
+/// `x` outside `[-1, 1]` has no real angle.
+pub(super) struct Asin();
+
+impl BuiltInStatic for Asin {
+    const ARGS: &[BuiltInArgDef] = &[BuiltInArgDef {
+        name: "x",
+        default: None,
+    }];
+
+    fn eval_static<'src>(
+        _solids: &mut SolidSet,
+        args: &BTreeMap<String, Value>,
+        context: &EvalContext,
+    ) -> EvalResult<'src, Value> {
+        let x = Self::num_arg("x", args, context)?;
+
+        if !(-1. ..=1.).contains(&x) {
+            return context.eval_err(EvalErrorType::NumExprNotFinite {
+                location: context.location(),
+            });
+        }
+
+        Ok(Value::Number(f64::asin(x).to_degrees()))
+    }
+}
+
+/// The angle in degrees whose cosine is `x`.
+///
+/// `x` outside `[-1, 1]` has no real angle.
+pub(super) struct Acos();
+
+impl BuiltInStatic for Acos {
+    const ARGS: &[BuiltInArgDef] = &[BuiltInArgDef {
+        name: "x",
+        default: None,
+    }];
+
+    fn eval_static<'src>(
+        _solids: &mut SolidSet,
+        args: &BTreeMap<String, Value>,
+        context: &EvalContext,
+    ) -> EvalResult<'src, Value> {
+        let x = Self::num_arg("x", args, context)?;
+
+        if !(-1. ..=1.).contains(&x) {
+            return context.eval_err(EvalErrorType::NumExprNotFinite {
+                location: context.location(),
+            });
+        }
+
+        Ok(Value::Number(f64::acos(x).to_degrees()))
+    }
+}
+
+/// The angle in degrees whose tangent is `x`.
+pub(super) struct Atan();
+
+impl BuiltInStatic for Atan {
+    const ARGS: &[BuiltInArgDef] = &[BuiltInArgDef {
+        name: "x",
+        default: None,
+    }];
+
+    fn eval_static<'src>(
+        _solids: &mut SolidSet,
+        args: &BTreeMap<String, Value>,
+        context: &EvalContext,
+    ) -> EvalResult<'src, Value> {
+        let x = Self::num_arg("x", args, context)?;
+
+        Ok(Value::Number(f64::atan(x).to_degrees()))
+    }
+}
+
+/// The angle in degrees from the positive x axis to the point (`x`, `y`),
+/// between -180 and 180.
+pub(super) struct Atan2();
+
+impl BuiltInStatic for Atan2 {
+    const ARGS: &[BuiltInArgDef] = &[
+        BuiltInArgDef {
+            name: "y",
+            default: None,
+        },
+        BuiltInArgDef {
+            name: "x",
+            default: None,
+        },
+    ];
+
+    fn eval_static<'src>(
+        _solids: &mut SolidSet,
+        args: &BTreeMap<String, Value>,
+        context: &EvalContext,
+    ) -> EvalResult<'src, Value> {
+        let y = Self::num_arg("y", args, context)?;
+        let x = Self::num_arg("x", args, context)?;
+
+        Ok(Value::Number(y.atan2(x).to_degrees()))
+    }
+}
+
+/// A deterministic pseudo-random number in `[0, 1)` derived from the integer
+/// `seed`.
+///
+/// This is a splitmix64 hash of the seed's bits rather than a stateful RNG:
+/// built-ins must be pure for the [`Scope`](super::Scope) cache to stay valid,
+/// so the same seed always gives the same number. It is not suitable for
+/// anything cryptographic.
+pub(super) struct Rand();
+
+impl Rand {
+    fn splitmix64(seed: u64) -> u64 {
+        let mut z = seed.wrapping_add(0x9E3779B97F4A7C15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^ (z >> 31)
+    }
+}
+
+impl BuiltInStatic for Rand {
+    const ARGS: &[BuiltInArgDef] = &[BuiltInArgDef {
+        name: "seed",
+        default: None,
+    }];
+
+    fn eval_static<'src>(
+        _solids: &mut SolidSet,
+        args: &BTreeMap<String, Value>,
+        context: &EvalContext,
+    ) -> EvalResult<'src, Value> {
+        // Adding zero normalizes -0 to 0 so they produce the same number.
+        let seed = Self::int_arg("seed", args, context)? + 0.;
+
+        // Use the top 53 bits so every output is exactly representable.
+        let bits = Self::splitmix64(seed.to_bits()) >> 11;
+        Ok(Value::Number(bits as f64 / (1u64 << 53) as f64))
+    }
+}
+
+/// `a / b`, or `fallback` where that would divide by zero or not be finite.
+///
+/// The `/` operator stays strict; this lets authors choose what happens at the
+/// edge case instead of aborting the whole evaluation.
+pub(super) struct SafeDiv();
+
+impl BuiltInStatic for SafeDiv {
+    const ARGS: &[BuiltInArgDef] = &[
+        BuiltInArgDef {
+            name: "a",
+            default: None,
+        },
+        BuiltInArgDef {
+            name: "b",
+            default: None,
+        },
+        BuiltInArgDef {
+            name: "fallback",
+            default: None,
+        },
+    ];
+
+    fn eval_static<'src>(
+        _solids: &mut SolidSet,
+        args: &BTreeMap<String, Value>,
+        context: &EvalContext,
+    ) -> EvalResult<'src, Value> {
+        let a = Self::num_arg("a", args, context)?;
+        let b = Self::num_arg("b", args, context)?;
+        let fallback = Self::num_arg("fallback", args, context)?;
+
+        let quotient = a / b;
+        if b == 0. || !quotient.is_finite() {
+            return Ok(Value::Number(fallback));
+        }
+
+        Ok(Value::Number(quotient))
+    }
+}
+
+/// The `n`th root of `x`.
+///
+/// Unlike `x ^ (1 / n)` this gives the real root of a negative `x` when `n` is
+/// an odd integer. Negative `x` with any other `n` has no real root.
+pub(super) struct Root();
+
+impl BuiltInStatic for Root {
+    const ARGS: &[BuiltInArgDef] = &[
+        BuiltInArgDef {
+            name: "x",
+            default: None,
+        },
+        BuiltInArgDef {
+            name: "n",
+            default: None,
+        },
+    ];
+
+    fn eval_static<'src>(
+        _solids: &mut SolidSet,
+        args: &BTreeMap<String, Value>,
+        context: &EvalContext,
+    ) -> EvalResult<'src, Value> {
+        let x = Self::num_arg("x", args, context)?;
+        let n = Self::num_arg("n", args, context)?;
+
+        // The zeroth root would be x ^ inf, which is 0 or 1 rather than an
+        // error for some x.
+        if n == 0. {
+            return context.eval_err(EvalErrorType::NumExprNotFinite {
+                location: context.location(),
+            });
+        }
+
+        let root = if x < 0. {
+            if n.fract() != 0. || n.rem_euclid(2.) != 1. {
+                return context.eval_err(EvalErrorType::NumExprNotFinite {
+                    location: context.location(),
+                });
+            }
+            -(-x).powf(n.recip())
+        } else {
+            x.powf(n.recip())
+        };
+
+        if !root.is_finite() {
+            return context.eval_err(EvalErrorType::NumExprNotFinite {
+                location: context.location(),
+            });
+        }
+
+        Ok(Value::Number(root))
+    }
+}
+
+/// `base` raised to the power `exp`, like `base ^ exp`.
+pub(super) struct Pow();
+
+impl BuiltInStatic for Pow {
+    const ARGS: &[BuiltInArgDef] = &[
+        BuiltInArgDef {
+            name: "base",
+            default: None,
+        },
+        BuiltInArgDef {
+            name: "exp",
+            default: None,
+        },
+    ];
+
+    fn eval_static<'src>(
+        _solids: &mut SolidSet,
+        args: &BTreeMap<String, Value>,
+        context: &EvalContext,
+    ) -> EvalResult<'src, Value> {
+        let base = Self::num_arg("base", args, context)?;
+        let exp = Self::num_arg("exp", args, context)?;
+
+        let pow = base.powf(exp);
+        if !pow.is_finite() {
+            return context.eval_err(EvalErrorType::NumExprNotFinite {
+                location: context.location(),
+            });
+        }
+
+        Ok(Value::Number(pow))
+    }
+}
+
+/// The square root of `x`.
+///
+/// Negative `x` has no real square root.
+pub(super) struct Sqrt();
+
+impl BuiltInStatic for Sqrt {
+    const ARGS: &[BuiltInArgDef] = &[BuiltInArgDef {
+        name: "x",
+        default: None,
+    }];
+
+    fn eval_static<'src>(
+        _solids: &mut SolidSet,
+        args: &BTreeMap<String, Value>,
+        context: &EvalContext,
+    ) -> EvalResult<'src, Value> {
+        let x = Self::num_arg("x", args, context)?;
+
+        if x < 0. {
+            return context.eval_err(EvalErrorType::NumExprNotFinite {
+                location: context.location(),
+            });
+        }
+
+        Ok(Value::Number(x.sqrt()))
+    }
+}
+
+/// The natural logarithm of `x`.
+///
+/// Non-positive `x` has no real logarithm.
+pub(super) struct Ln();
+
+impl BuiltInStatic for Ln {
+    const ARGS: &[BuiltInArgDef] = &[BuiltInArgDef {
+        name: "x",
+        default: None,
+    }];
+
+    fn eval_static<'src>(
+        _solids: &mut SolidSet,
+        args: &BTreeMap<String, Value>,
+        context: &EvalContext,
+    ) -> EvalResult<'src, Value> {
+        let x = Self::num_arg("x", args, context)?;
+
+        if x <= 0. {
+            return context.eval_err(EvalErrorType::NumExprNotFinite {
+                location: context.location(),
+            });
+        }
+
+        Ok(Value::Number(x.ln()))
+    }
+}
+
+/// The logarithm of `x` in `base`.
+///
+/// Non-positive `x` has no real logarithm, and neither does any `x` in a
+/// non-positive base or base 1.
+pub(super) struct Log();
+
+impl BuiltInStatic for Log {
+    const ARGS: &[BuiltInArgDef] = &[
+        BuiltInArgDef {
+            name: "x",
+            default: None,
+        },
+        BuiltInArgDef {
+            name: "base",
+            default: Some(Value::Number(10.)),
+        },
+    ];
+
+    fn eval_static<'src>(
+        _solids: &mut SolidSet,
+        args: &BTreeMap<String, Value>,
+        context: &EvalContext,
+    ) -> EvalResult<'src, Value> {
+        let x = Self::num_arg("x", args, context)?;
+        let base = Self::num_arg("base", args, context)?;
+
+        let log = x.log(base);
+        if x <= 0. || !log.is_finite() {
+            return context.eval_err(EvalErrorType::NumExprNotFinite {
+                location: context.location(),
+            });
+        }
+
+        Ok(Value::Number(log))
+    }
+}
+
+/// e raised to the power `x`.
+pub(super) struct Exp();
+
+impl BuiltInStatic for Exp {
+    const ARGS: &[BuiltInArgDef] = &[BuiltInArgDef {
+        name: "x",
+        default: None,
+    }];
+
+    fn eval_static<'src>(
+        _solids: &mut SolidSet,
+        args: &BTreeMap<String, Value>,
+        context: &EvalContext,
+    ) -> EvalResult<'src, Value> {
+        let x = Self::num_arg("x", args, context)?;
+
+        let exp = x.exp();
+        if !exp.is_finite() {
+            return context.eval_err(EvalErrorType::NumExprNotFinite {
+                location: context.location(),
+            });
+        }
+
+        Ok(Value::Number(exp))
+    }
+}
+
+/// The absolute value of `value`.
+pub(super) struct Abs();
+
+impl BuiltInStatic for Abs {
+    const ARGS: &[BuiltInArgDef] = &[BuiltInArgDef {
+        name: "value",
+        default: None,
+    }];
+
+    fn eval_static<'src>(
+        _solids: &mut SolidSet,
+        args: &BTreeMap<String, Value>,
+        context: &EvalContext,
+    ) -> EvalResult<'src, Value> {
+        let value = Self::num_arg("value", args, context)?;
+
+        Ok(Value::Number(value.abs()))
+    }
+}
+
+/// The largest integer no greater than `value`.
+pub(super) struct Floor();
+
+impl BuiltInStatic for Floor {
+    const ARGS: &[BuiltInArgDef] = &[BuiltInArgDef {
+        name: "value",
+        default: None,
+    }];
+
+    fn eval_static<'src>(
+        _solids: &mut SolidSet,
+        args: &BTreeMap<String, Value>,
+        context: &EvalContext,
+    ) -> EvalResult<'src, Value> {
+        let value = Self::num_arg("value", args, context)?;
+
+        Ok(Value::Number(value.floor()))
+    }
+}
+
+/// The smallest integer no less than `value`.
+pub(super) struct Ceil();
+
+impl BuiltInStatic for Ceil {
+    const ARGS: &[BuiltInArgDef] = &[BuiltInArgDef {
+        name: "value",
+        default: None,
+    }];
+
+    fn eval_static<'src>(
+        _solids: &mut SolidSet,
+        args: &BTreeMap<String, Value>,
+        context: &EvalContext,
+    ) -> EvalResult<'src, Value> {
+        let value = Self::num_arg("value", args, context)?;
+
+        Ok(Value::Number(value.ceil()))
+    }
+}
+
+/// The nearest integer to `value`.
+///
+/// Halfway cases round away from zero, so `Round(2.5)` is `3` and
+/// `Round(-2.5)` is `-3`.
+pub(super) struct Round();
+
+impl BuiltInStatic for Round {
+    const ARGS: &[BuiltInArgDef] = &[BuiltInArgDef {
+        name: "value",
+        default: None,
+    }];
+
+    fn eval_static<'src>(
+        _solids: &mut SolidSet,
+        args: &BTreeMap<String, Value>,
+        context: &EvalContext,
+    ) -> EvalResult<'src, Value> {
+        let value = Self::num_arg("value", args, context)?;
+
+        Ok(Value::Number(value.round()))
+    }
+}
+
+/// Whether `a` and `b` differ by no more than `tol`.
+pub(super) struct ApproxEq();
+
+impl BuiltInStatic for ApproxEq {
+    const ARGS: &[BuiltInArgDef] = &[
+        BuiltInArgDef {
+            name: "a",
+            default: None,
+        },
+        BuiltInArgDef {
+            name: "b",
+            default: None,
+        },
+        BuiltInArgDef {
+            name: "tol",
+            default: Some(Value::Number(SolidSet::DEFAULT_TOLERANCE)),
+        },
+    ];
+
+    fn eval_static<'src>(
+        _solids: &mut SolidSet,
+        args: &BTreeMap<String, Value>,
+        context: &EvalContext,
+    ) -> EvalResult<'src, Value> {
+        let a = Self::num_arg("a", args, context)?;
+        let b = Self::num_arg("b", args, context)?;
+        let tol = Self::num_arg("tol", args, context)?;
+
+        Ok(Value::Bool((a - b).abs() <= tol))
+    }
+}
+
+pub(super) struct DegToRad();
+
+impl BuiltInStatic for DegToRad {
+    const ARGS: &[BuiltInArgDef] = &[BuiltInArgDef {
+        name: "x",
+        default: None,
+    }];
+
+    fn eval_static<'src>(
+        _solids: &mut SolidSet,
+        args: &BTreeMap<String, Value>,
+        context: &EvalContext,
+    ) -> EvalResult<'src, Value> {
+        let x = Self::num_arg("x", args, context)?;
+
+        Ok(Value::Number(x.to_radians()))
+    }
+}
+
+pub(super) struct RadToDeg();
+
+impl BuiltInStatic for RadToDeg {
+    const ARGS: &[BuiltInArgDef] = &[BuiltInArgDef {
+        name: "x",
+        default: None,
+    }];
+
+    fn eval_static<'src>(
+        _solids: &mut SolidSet,
+        args: &BTreeMap<String, Value>,
+        context: &EvalContext,
+    ) -> EvalResult<'src, Value> {
+        let x = Self::num_arg("x", args, context)?;
+
+        Ok(Value::Number(x.to_degrees()))
+    }
+}
+
+/// Whether `x` has no fractional part.
+pub(super) struct IsInt();
+
+impl BuiltInStatic for IsInt {
+    const ARGS: &[BuiltInArgDef] = &[BuiltInArgDef {
+        name: "x",
+        default: None,
+    }];
+
+    fn eval_static<'src>(
+        _solids: &mut SolidSet,
+        args: &BTreeMap<String, Value>,
+        context: &EvalContext,
+    ) -> EvalResult<'src, Value> {
+        let x = Self::num_arg("x", args, context)?;
+
+        Ok(Value::Bool(x.fract() == 0.))
+    }
+}
+
+/// The numbers in `list` in ascending order.
+pub(super) struct Sort();
+
+impl BuiltInStatic for Sort {
+    const ARGS: &[BuiltInArgDef] = &[BuiltInArgDef {
+        name: "list",
+        default: None,
+    }];
+
+    fn eval_static<'src>(
+        _solids: &mut SolidSet,
+        args: &BTreeMap<String, Value>,
+        context: &EvalContext,
+    ) -> EvalResult<'src, Value> {
+        let mut nums = Self::num_list_arg("list", args, context)?;
+
+        // NaN has no place in a total order, but it should never get this far.
+        if nums.iter().any(|num| num.is_nan()) {
+            return context.eval_err(EvalErrorType::NumExprNotFinite {
+                location: context.location(),
+            });
+        }
+        nums.sort_by(f64::total_cmp);
+
+        Ok(Value::List(nums.into_iter().map(Value::Number).collect()))
+    }
+}
+
+/// The largest integer below which every integer is exactly representable as
+/// an `f64`.
+const MAX_EXACT_INT: u128 = 1 << f64::MANTISSA_DIGITS;
+
+/// `n!` for a non-negative integer `n`.
+///
+/// Results too large to be represented exactly are an error rather than
+/// silently losing precision.
+pub(super) struct Factorial();
+
+impl BuiltInStatic for Factorial {
+    const ARGS: &[BuiltInArgDef] = &[BuiltInArgDef {
+        name: "n",
+        default: None,
+    }];
+
+    fn eval_static<'src>(
+        _solids: &mut SolidSet,
+        args: &BTreeMap<String, Value>,
+        context: &EvalContext,
+    ) -> EvalResult<'src, Value> {
+        let n = Self::int_arg("n", args, context)?;
+        if n < 0. {
+            return context.eval_err(EvalErrorType::NumExprNotFinite {
+                location: context.location(),
+            });
+        }
+
+        let mut result: u128 = 1;
+        for i in 2..=(n as u128) {
+            result *= i;
+            if result > MAX_EXACT_INT {
+                return context.eval_err(EvalErrorType::NumExprNotFinite {
+                    location: context.location(),
+                });
+            }
+        }
+
+        Ok(Value::Number(result as f64))
+    }
+}
+
+/// The number of ways to choose `k` items from `n`.
+///
+/// `k` outside of `0..=n` gives 0. Like [`Factorial`], results too large to be
+/// represented exactly are an error.
+pub(super) struct Binomial();
+
+impl BuiltInStatic for Binomial {
+    const ARGS: &[BuiltInArgDef] = &[
+        BuiltInArgDef {
+            name: "n",
+            default: None,
+        },
+        BuiltInArgDef {
+            name: "k",
+            default: None,
+        },
+    ];
+
+    fn eval_static<'src>(
+        _solids: &mut SolidSet,
+        args: &BTreeMap<String, Value>,
+        context: &EvalContext,
+    ) -> EvalResult<'src, Value> {
+        let n = Self::int_arg("n", args, context)?;
+        let k = Self::int_arg("k", args, context)?;
+        if n < 0. || n as u128 > MAX_EXACT_INT {
+            return context.eval_err(EvalErrorType::NumExprNotFinite {
+                location: context.location(),
+            });
+        }
+        if k < 0. || k > n {
+            return Ok(Value::Number(0.));
+        }
+
+        let (n, k) = (n as u128, k as u128);
+        let k = k.min(n - k);
+
+        // Each partial product is itself a binomial coefficient so the
+        // division is always exact.
+        let mut result: u128 = 1;
+        for i in 0..k {
+            result = result * (n - i) / (i + 1);
+            if result > MAX_EXACT_INT {
+                return context.eval_err(EvalErrorType::NumExprNotFinite {
+                    location: context.location(),
+                });
+            }
+        }
+
+        Ok(Value::Number(result as f64))
+    }
+}
+
+/// The ratio of a circle's circumference to its diameter.
+pub(super) struct Pi();
+
+impl BuiltInStatic for Pi {
+    const ARGS: &[BuiltInArgDef] = &[];
+
+    fn eval_static<'src>(
+        _solids: &mut SolidSet,
+        _args: &BTreeMap<String, Value>,
+        _context: &EvalContext,
+    ) -> EvalResult<'src, Value> {
+        Ok(Value::Number(consts::PI))
+    }
+}
+
+/// Euler's number, the base of [`Ln`].
+pub(super) struct E();
+
+impl BuiltInStatic for E {
+    const ARGS: &[BuiltInArgDef] = &[];
+
+    fn eval_static<'src>(
+        _solids: &mut SolidSet,
+        _args: &BTreeMap<String, Value>,
+        _context: &EvalContext,
+    ) -> EvalResult<'src, Value> {
+        Ok(Value::Number(consts::E))
+    }
+}
+
+/// The ratio of a circle's circumference to its radius, `2 * Pi`.
+pub(super) struct Tau();
+
+impl BuiltInStatic for Tau {
+    const ARGS: &[BuiltInArgDef] = &[];
+
+    fn eval_static<'src>(
+        _solids: &mut SolidSet,
+        _args: &BTreeMap<String, Value>,
+        _context: &EvalContext,
+    ) -> EvalResult<'src, Value> {
+        Ok(Value::Number(consts::TAU))
+    }
+}
@@ -0,0 +1,482 @@
+use std::collections::BTreeMap;
+
+use truck_meshalgo::prelude::*;
+
+use truck_modeling::Solid;
+
+use crate::{EvalErrorType, SolidId, SolidSet, Value};
+
+use super::{
+    shapes::box_between,
+    transforms::{rotate_about_matrix, scale_about_matrix, scale_factor_arg},
+    BuiltInArgDef, BuiltInStatic, EvalContext, EvalResult,
+};
+
+/// Triangulates a solid so it can be measured.
+///
+/// Curved faces are approximated to within the set's tolerance, so any
+/// measurement taken from the mesh is only as accurate as that tolerance.
+pub(super) fn mesh<'src>(
+    solids: &SolidSet,
+    solid: &SolidId,
+    context: &EvalContext,
+) -> EvalResult<'src, PolygonMesh> {
+    mesh_with_tolerance(solids, solid, solids.tolerance, context)
+}
+
+/// Like [`mesh`] but curved faces are approximated to within `tolerance`.
+fn mesh_with_tolerance<'src>(
+    solids: &SolidSet,
+    solid: &SolidId,
+    tolerance: f64,
+    context: &EvalContext,
+) -> EvalResult<'src, PolygonMesh> {
+    match solid {
+        SolidId::Regular(_) => Ok(solids.try_get(solid)?.triangulation(tolerance).to_polygon()),
+        SolidId::Empty => context.eval_err(EvalErrorType::EmptySolid),
+        SolidId::Universal => context.eval_err(EvalErrorType::UniversalSolid),
+    }
+}
+
+/// Triangulates each boundary shell of a solid separately, pairing each mesh
+/// with its signed volume.
+///
+/// Outer shells have a positive volume and the shells of voids a negative one.
+fn shell_meshes(solid: &Solid, tolerance: f64) -> Vec<(PolygonMesh, f64)> {
+    solid
+        .boundaries()
+        .iter()
+        .map(|shell| {
+            let mesh = shell.triangulation(tolerance).to_polygon();
+            let volume = mesh.volume();
+            (mesh, volume)
+        })
+        .collect()
+}
+
+/// The axis-aligned bounding box of a solid's triangulation.
+pub(super) fn bounding_box<'src>(
+    solids: &SolidSet,
+    solid: &SolidId,
+    context: &EvalContext,
+) -> EvalResult<'src, BoundingBox<Point3>> {
+    Ok(mesh(solids, solid, context)?.bounding_box())
+}
+
+/// The volumetric center of mass of a solid's triangulation.
+pub(super) fn centroid<'src>(
+    solids: &SolidSet,
+    solid: &SolidId,
+    context: &EvalContext,
+) -> EvalResult<'src, Point3> {
+    // The weight of the homogeneous center of gravity is the mesh volume.
+    let center = mesh(solids, solid, context)?.center_of_gravity();
+    if center.w.abs() < solids.tolerance {
+        return context.eval_err(EvalErrorType::EmptySolid);
+    }
+
+    Ok(Point3::from_homogeneous(center))
+}
+
+/// One coordinate of the volumetric center of mass of a solid.
+///
+/// `AXIS` selects the coordinate with 0, 1 and 2 being x, y and z. The
+/// centroid is computed from a triangulation of the solid, so for curved
+/// solids it is an approximation.
+pub(super) struct Centroid<const AXIS: usize>();
+
+impl<const AXIS: usize> BuiltInStatic for Centroid<AXIS> {
+    const ARGS: &[BuiltInArgDef] = &[BuiltInArgDef {
+        name: "solid",
+        default: None,
+    }];
+
+    fn eval_static<'src>(
+        solids: &mut SolidSet,
+        args: &BTreeMap<String, Value>,
+        context: &EvalContext,
+    ) -> EvalResult<'src, Value> {
+        let solid = Self::solid_arg("solid", args, context)?;
+
+        Ok(Value::Number(centroid(solids, &solid, context)?[AXIS]))
+    }
+}
+
+/// The axis-aligned box enclosing every solid in a list.
+///
+/// Empty solids take up no space, so a list with nothing but empty solids,
+/// including an empty list, gives the empty solid rather than an error.
+pub(super) struct EnclosingBox();
+
+impl BuiltInStatic for EnclosingBox {
+    const ARGS: &[BuiltInArgDef] = &[BuiltInArgDef {
+        name: "solids",
+        default: None,
+    }];
+
+    fn eval_static<'src>(
+        solids: &mut SolidSet,
+        args: &BTreeMap<String, Value>,
+        context: &EvalContext,
+    ) -> EvalResult<'src, Value> {
+        let mut bbox = BoundingBox::new();
+        for solid in Self::solid_list_arg("solids", args, context)? {
+            if solid != SolidId::Empty {
+                bbox += bounding_box(solids, &solid, context)?;
+            }
+        }
+
+        if bbox.is_empty() {
+            return Ok(Value::Solid(SolidId::Empty));
+        }
+
+        let id = solids.push(box_between(bbox.min(), bbox.max()));
+        Ok(Value::Solid(id))
+    }
+}
+
+/// The length of the space diagonal of a solid's bounding box.
+///
+/// This is the longest straight line that fits in the box, so it's handy for
+/// checking whether a part fits in an enclosure or on a print bed.
+pub(super) struct Diagonal();
+
+impl BuiltInStatic for Diagonal {
+    const ARGS: &[BuiltInArgDef] = &[BuiltInArgDef {
+        name: "solid",
+        default: None,
+    }];
+
+    fn eval_static<'src>(
+        solids: &mut SolidSet,
+        args: &BTreeMap<String, Value>,
+        context: &EvalContext,
+    ) -> EvalResult<'src, Value> {
+        let solid = Self::solid_arg("solid", args, context)?;
+        let bbox = bounding_box(solids, &solid, context)?;
+
+        Ok(Value::Number(bbox.diagonal().magnitude()))
+    }
+}
+
+/// The volume of a solid.
+///
+/// The volume is computed from a triangulation of the solid, so for curved
+/// solids it is an approximation. Voids are subtracted, and the empty solid
+/// has a volume of 0.
+pub(super) struct Volume();
+
+impl BuiltInStatic for Volume {
+    const ARGS: &[BuiltInArgDef] = &[BuiltInArgDef {
+        name: "solid",
+        default: None,
+    }];
+
+    fn eval_static<'src>(
+        solids: &mut SolidSet,
+        args: &BTreeMap<String, Value>,
+        context: &EvalContext,
+    ) -> EvalResult<'src, Value> {
+        let solid = Self::solid_arg("solid", args, context)?;
+        if solid == SolidId::Empty {
+            return Ok(Value::Number(0.));
+        }
+
+        Ok(Value::Number(mesh(solids, &solid, context)?.volume()))
+    }
+}
+
+/// The size of a solid's bounding box along each axis, as a vector.
+///
+/// The empty solid takes up no space so its size is a zero vector.
+pub(super) struct BBoxSize();
+
+impl BuiltInStatic for BBoxSize {
+    const ARGS: &[BuiltInArgDef] = &[BuiltInArgDef {
+        name: "solid",
+        default: None,
+    }];
+
+    fn eval_static<'src>(
+        solids: &mut SolidSet,
+        args: &BTreeMap<String, Value>,
+        context: &EvalContext,
+    ) -> EvalResult<'src, Value> {
+        let solid = Self::solid_arg("solid", args, context)?;
+        if solid == SolidId::Empty {
+            return Ok(Value::Vector(0., 0., 0.));
+        }
+
+        let size = bounding_box(solids, &solid, context)?.diagonal();
+        Ok(Value::Vector(size.x, size.y, size.z))
+    }
+}
+
+/// The bounding box a solid would have after being scaled by `factor`, then
+/// rotated by `angle` degrees about the axis through the origin with direction
+/// (`axis_x`, `axis_y`, `axis_z`), then translated by (`x`, `y`, `z`), as a
+/// list of its min and max corners.
+///
+/// This is the same as transforming with `Scale`, `Rotate` and `Translate` in
+/// that order, and the arguments are checked the same way, so the offset can
+/// instead be passed as `offset` and the axis as `axis`. The axis only has to
+/// be given along with a non-zero angle. Only the corners of the
+/// untransformed bounding box are moved, so no transformed solid is built. A
+/// rotation that isn't a multiple of 90 degrees about a coordinate axis gives
+/// a box that's larger than the transformed solid's own bounding box.
+pub(super) struct TransformedBounds();
+
+impl BuiltInStatic for TransformedBounds {
+    const ARGS: &[BuiltInArgDef] = &[
+        BuiltInArgDef {
+            name: "solid",
+            default: None,
+        },
+        BuiltInArgDef {
+            name: "x",
+            default: None,
+        },
+        BuiltInArgDef {
+            name: "y",
+            default: None,
+        },
+        BuiltInArgDef {
+            name: "z",
+            default: None,
+        },
+        BuiltInArgDef {
+            name: "axis_x",
+            default: None,
+        },
+        BuiltInArgDef {
+            name: "axis_y",
+            default: None,
+        },
+        BuiltInArgDef {
+            name: "axis_z",
+            default: None,
+        },
+        BuiltInArgDef {
+            name: "angle",
+            default: Some(Value::Number(0.)),
+        },
+        BuiltInArgDef {
+            name: "factor",
+            default: Some(Value::Number(1.)),
+        },
+        BuiltInArgDef {
+            name: "offset",
+            default: None,
+        },
+        BuiltInArgDef {
+            name: "axis",
+            default: None,
+        },
+    ];
+    const OPTIONAL_ARGS: &[&str] = &[
+        "x", "y", "z", "axis_x", "axis_y", "axis_z", "offset", "axis",
+    ];
+
+    fn eval_static<'src>(
+        solids: &mut SolidSet,
+        args: &BTreeMap<String, Value>,
+        context: &EvalContext,
+    ) -> EvalResult<'src, Value> {
+        let solid = Self::solid_arg("solid", args, context)?;
+        let offset = Self::vector_or_components_arg("offset", ["x", "y", "z"], args, context)?;
+        let axis =
+            Self::vector_or_components_arg("axis", ["axis_x", "axis_y", "axis_z"], args, context)?;
+        let angle = Self::num_arg("angle", args, context)?;
+        let factor = scale_factor_arg("factor", args, context)?;
+
+        let rotation = if angle == 0. {
+            Matrix4::identity()
+        } else {
+            let Some(rotation) =
+                rotate_about_matrix(axis, angle, Point3::origin(), solids.tolerance)
+            else {
+                return context.eval_err(EvalErrorType::NumExprNotFinite {
+                    location: context.location(),
+                });
+            };
+            rotation
+        };
+        let mat = Matrix4::from_translation(offset)
+            * rotation
+            * scale_about_matrix(factor, Point3::origin());
+
+        let bbox = bounding_box(solids, &solid, context)?;
+        let (min, max) = (bbox.min(), bbox.max());
+        let mut transformed = BoundingBox::new();
+        for corner in 0..8 {
+            let pick = |axis: usize| {
+                if corner & (1 << axis) == 0 {
+                    min[axis]
+                } else {
+                    max[axis]
+                }
+            };
+            transformed.push(mat.transform_point(Point3::new(pick(0), pick(1), pick(2))));
+        }
+
+        let point = |p: Point3| Value::Vector(p.x, p.y, p.z);
+        Ok(Value::List(vec![
+            point(transformed.min()),
+            point(transformed.max()),
+        ]))
+    }
+}
+
+/// The number of disconnected pieces a solid is made of.
+///
+/// Voids don't count as pieces, so a hollow solid is still one piece. The
+/// empty solid has no pieces.
+pub(super) struct ComponentCount();
+
+impl BuiltInStatic for ComponentCount {
+    const ARGS: &[BuiltInArgDef] = &[BuiltInArgDef {
+        name: "solid",
+        default: None,
+    }];
+
+    fn eval_static<'src>(
+        solids: &mut SolidSet,
+        args: &BTreeMap<String, Value>,
+        context: &EvalContext,
+    ) -> EvalResult<'src, Value> {
+        let solid = Self::solid_arg("solid", args, context)?;
+
+        let count = match solid {
+            SolidId::Regular(_) => shell_meshes(solids.try_get(&solid)?, solids.tolerance)
+                .iter()
+                .filter(|(_, volume)| *volume > 0.)
+                .count(),
+            SolidId::Empty => 0,
+            SolidId::Universal => return context.eval_err(EvalErrorType::UniversalSolid),
+        };
+
+        Ok(Value::Number(count as f64))
+    }
+}
+
+/// The disconnected piece of a solid with the largest volume.
+///
+/// This cleans up slivers left behind by boolean operations. Each void is
+/// assigned to the smallest piece whose bounding box contains it, which is
+/// exact unless pieces are nested inside each other's voids.
+pub(super) struct LargestComponent();
+
+impl BuiltInStatic for LargestComponent {
+    const ARGS: &[BuiltInArgDef] = &[BuiltInArgDef {
+        name: "solid",
+        default: None,
+    }];
+
+    fn eval_static<'src>(
+        solids: &mut SolidSet,
+        args: &BTreeMap<String, Value>,
+        context: &EvalContext,
+    ) -> EvalResult<'src, Value> {
+        let id = Self::solid_arg("solid", args, context)?;
+        let solid = match id {
+            SolidId::Regular(_) => solids.try_get(&id)?,
+            SolidId::Empty => return context.eval_err(EvalErrorType::EmptySolid),
+            SolidId::Universal => return context.eval_err(EvalErrorType::UniversalSolid),
+        };
+
+        let shells = solid.boundaries();
+        let meshes = shell_meshes(solid, solids.tolerance);
+        let (outer, voids): (Vec<usize>, Vec<usize>) =
+            (0..shells.len()).partition(|i| meshes[*i].1 > 0.);
+        if outer.len() <= 1 {
+            return Ok(Value::Solid(id));
+        }
+
+        // Components as the index of their outer shell then their voids.
+        let mut components: Vec<(usize, Vec<usize>)> =
+            outer.iter().map(|i| (*i, Vec::new())).collect();
+        for void in voids {
+            let void_bbox = meshes[void].0.bounding_box();
+            let container = components
+                .iter_mut()
+                .filter(|(outer, _)| {
+                    let bbox = meshes[*outer].0.bounding_box();
+                    bbox.contains(void_bbox.min()) && bbox.contains(void_bbox.max())
+                })
+                .min_by(|(a, _), (b, _)| meshes[*a].1.total_cmp(&meshes[*b].1));
+            if let Some((_, component_voids)) = container {
+                component_voids.push(void);
+            }
+        }
+
+        let volume = |(outer, voids): &(usize, Vec<usize>)| {
+            meshes[*outer].1 + voids.iter().map(|v| meshes[*v].1).sum::<f64>()
+        };
+        let Some((outer, voids)) = components
+            .iter()
+            .max_by(|a, b| volume(a).total_cmp(&volume(b)))
+        else {
+            return Ok(Value::Solid(id));
+        };
+
+        let boundaries = std::iter::once(*outer)
+            .chain(voids.iter().copied())
+            .map(|i| shells[i].clone())
+            .collect();
+        match Solid::try_new(boundaries) {
+            Ok(largest) => Ok(Value::Solid(solids.push(largest))),
+            Err(_) => context.eval_err(EvalErrorType::GeometryOperationFailed {
+                op: "LargestComponent",
+            }),
+        }
+    }
+}
+
+/// The number of triangles a solid is meshed into with curved faces
+/// approximated to within `tolerance`, which is the solid tolerance unless
+/// it's given.
+///
+/// Without a `tolerance` this is the triangle count of an exported mesh, so it
+/// can be used to keep the complexity of a model in check. The empty solid has
+/// no triangles.
+pub(super) struct TriangleCount();
+
+impl BuiltInStatic for TriangleCount {
+    const ARGS: &[BuiltInArgDef] = &[
+        BuiltInArgDef {
+            name: "solid",
+            default: None,
+        },
+        BuiltInArgDef {
+            name: "tolerance",
+            default: None,
+        },
+    ];
+    const OPTIONAL_ARGS: &[&str] = &["tolerance"];
+
+    fn eval_static<'src>(
+        solids: &mut SolidSet,
+        args: &BTreeMap<String, Value>,
+        context: &EvalContext,
+    ) -> EvalResult<'src, Value> {
+        let solid = Self::solid_arg("solid", args, context)?;
+        let tolerance = if args.contains_key("tolerance") {
+            Self::num_arg("tolerance", args, context)?
+        } else {
+            solids.tolerance
+        };
+        if tolerance <= 0. {
+            return context.eval_err(EvalErrorType::InvalidDimension {
+                name: "tolerance".into(),
+                reason: "must be positive",
+            });
+        }
+
+        if solid == SolidId::Empty {
+            return Ok(Value::Number(0.));
+        }
+
+        let mesh = mesh_with_tolerance(solids, &solid, tolerance, context)?;
+        Ok(Value::Number(mesh.faces().triangle_iter().count() as f64))
+    }
+}
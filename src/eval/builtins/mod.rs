@@ -1,19 +1,109 @@
+mod cache;
+use cache::CACHE;
+
+mod map;
+use map::MAP;
+
+mod switch;
+use switch::SWITCH;
+
 mod shapes;
 
+mod import_mesh;
+
 mod math;
 
-use std::collections::BTreeMap;
+mod list;
+
+mod assert;
+
+mod measure;
+
+mod transforms;
+
+mod features;
+
+mod param;
+
+mod vector;
+
+mod sketch;
+pub(crate) use sketch::{combine_sketches, SketchOp};
+
+mod registry;
+pub use registry::{BuiltInRegistry, CustomBuiltIn};
 
-use crate::{ast::*, error::EvalResult, EvalErrorType, SolidSet, SpannedFuncCallExpr, Value};
+use std::collections::{BTreeMap, HashMap};
+
+use truck_modeling::{Face, Point3, Vector3};
+
+use crate::{
+    ast::SpannedExpr, error::EvalResult, EvalErrorType, SolidId, SolidSet, SpannedFuncCallExpr,
+    Value,
+};
 
 use super::{EvalCache, EvalContext, Scope};
 
+/// Returns true if `name` is a built-in that's evaluated by [`EvalCache`]
+/// itself rather than from the values of its arguments.
+pub(crate) fn is_special_form(name: &str) -> bool {
+    matches!(name, CACHE | MAP | SWITCH)
+}
+
+/// Collects the argument expressions of a special form by name, with
+/// positional arguments taking the names in `names` in order.
+fn special_form_exprs<'e, 'src>(
+    call_expr: &'e SpannedFuncCallExpr<'src>,
+    names: &[&'static str],
+    context: &EvalContext,
+) -> EvalResult<'src, BTreeMap<&'static str, &'e SpannedExpr<'src>>> {
+    let mut exprs = BTreeMap::new();
+    let positional = call_expr.args.positional();
+    if positional.len() > names.len() {
+        return context.eval_err(EvalErrorType::TooManyArgs);
+    }
+    exprs.extend(
+        names
+            .iter()
+            .copied()
+            .zip(positional.iter().map(|a| a.as_ref())),
+    );
+    for (name, arg) in call_expr.args.named() {
+        let Some(name) = names.iter().find(|n| *n == name) else {
+            return context.eval_err(EvalErrorType::InvalidNamedArg {
+                name: (*name).into(),
+            });
+        };
+        if exprs.insert(*name, &*arg.expr).is_some() {
+            return context.eval_err(EvalErrorType::ArgSuppliedTwice {
+                name: (*name).into(),
+            });
+        }
+    }
+
+    Ok(exprs)
+}
+
 pub(crate) trait BuiltIn {
     fn arg_defs(&self) -> &'static [BuiltInArgDef];
 
+    /// Arguments without a default that can still be left out, so the
+    /// built-in can tell they weren't supplied.
+    fn optional_args(&self) -> &'static [&'static str] {
+        &[]
+    }
+
+    /// Whether positional arguments from the last one on are collected into a
+    /// list for the last argument, as in `Hull(a, b, c)`.
+    fn variadic(&self) -> bool {
+        false
+    }
+
+    /// `params` are the named parameters supplied by the host.
     fn eval<'src>(
         &self,
         solids: &mut SolidSet,
+        params: &HashMap<String, Value>,
         scope: &Scope,
         context: &EvalContext,
     ) -> EvalResult<'src, Value>;
@@ -25,6 +115,9 @@ pub(crate) trait BuiltIn {
     ) -> EvalResult<'src, ()> {
         for def in self.arg_defs() {
             if !args.contains_key(def.name) {
+                if def.default.is_none() && self.optional_args().contains(&def.name) {
+                    continue;
+                }
                 let Some(ref val) = def.default else {
                     return context.eval_err(EvalErrorType::NoSuppliedOrDefaultArg {
                         name: def.name.into(),
@@ -38,13 +131,20 @@ pub(crate) trait BuiltIn {
     }
 }
 
-pub(crate) struct BuiltInArgDef {
-    name: &'static str,
-    default: Option<Value>,
+/// An argument of a built-in function.
+pub struct BuiltInArgDef {
+    pub name: &'static str,
+    /// The value of the argument when it isn't supplied. Arguments without a
+    /// default must always be supplied.
+    pub default: Option<Value>,
 }
 
 trait BuiltInStatic {
     const ARGS: &[BuiltInArgDef];
+    /// See [`BuiltIn::optional_args`].
+    const OPTIONAL_ARGS: &[&str] = &[];
+    /// See [`BuiltIn::variadic`].
+    const VARIADIC: bool = false;
 
     fn eval_static<'src>(
         solids: &mut SolidSet,
@@ -71,6 +171,242 @@ trait BuiltInStatic {
 
         Ok(*num)
     }
+
+    /// Like [`BuiltInStatic::num_arg`] but the number must be an integer.
+    fn int_arg<'src>(
+        name: &str,
+        args: &BTreeMap<String, Value>,
+        context: &EvalContext,
+    ) -> EvalResult<'src, f64> {
+        let num = Self::num_arg(name, args, context)?;
+        if num.fract() != 0. {
+            return context.eval_err(EvalErrorType::ArgNotInteger { name: name.into() });
+        }
+
+        Ok(num)
+    }
+
+    /// Like [`BuiltInStatic::num_arg`] but the number must be greater than 0.
+    fn positive_arg<'src>(
+        name: &str,
+        args: &BTreeMap<String, Value>,
+        context: &EvalContext,
+    ) -> EvalResult<'src, f64> {
+        let num = Self::num_arg(name, args, context)?;
+        if num <= 0. {
+            return context.eval_err(EvalErrorType::InvalidDimension {
+                name: name.into(),
+                reason: "must be positive",
+            });
+        }
+
+        Ok(num)
+    }
+
+    /// A bool or number used as a condition, where any nonzero number is
+    /// truthy.
+    fn truthy_arg<'src>(
+        name: &str,
+        args: &BTreeMap<String, Value>,
+        context: &EvalContext,
+    ) -> EvalResult<'src, bool> {
+        if let Some(Value::Bool(val)) = args.get(name) {
+            return Ok(*val);
+        }
+
+        Ok(Self::num_arg(name, args, context)? != 0.)
+    }
+
+    fn vector_arg<'src>(
+        name: &str,
+        args: &BTreeMap<String, Value>,
+        context: &EvalContext,
+    ) -> EvalResult<'src, Vector3> {
+        let Some(val) = args.get(name) else {
+            return context.eval_err(EvalErrorType::ArgNotFound { name: name.into() });
+        };
+
+        let Value::Vector(x, y, z) = val else {
+            return context.eval_err(EvalErrorType::ArgWrongType {
+                name: name.into(),
+                expected: Value::VECTOR_TYPE_NAME,
+                got: val.type_name(),
+            });
+        };
+
+        Ok(Vector3::new(*x, *y, *z))
+    }
+
+    /// The faces of a 2D shape.
+    fn shape_2d_arg<'a, 'src>(
+        name: &str,
+        args: &'a BTreeMap<String, Value>,
+        context: &EvalContext,
+    ) -> EvalResult<'src, &'a [Face]> {
+        let Some(val) = args.get(name) else {
+            return context.eval_err(EvalErrorType::ArgNotFound { name: name.into() });
+        };
+
+        let Value::Shape2D(faces) = val else {
+            return context.eval_err(EvalErrorType::ArgWrongType {
+                name: name.into(),
+                expected: Value::SHAPE_2D_TYPE_NAME,
+                got: val.type_name(),
+            });
+        };
+
+        Ok(faces)
+    }
+
+    /// A vector given either as a vector in `vector` or as a number in each of
+    /// `components`, where components that aren't supplied are zero.
+    ///
+    /// The components should be optional arguments without a default, so that
+    /// supplying one along with `vector` is an
+    /// [`EvalErrorType::ConflictingArgs`] error rather than being ignored.
+    fn vector_or_components_arg<'src>(
+        vector: &str,
+        components: [&str; 3],
+        args: &BTreeMap<String, Value>,
+        context: &EvalContext,
+    ) -> EvalResult<'src, Vector3> {
+        if args.contains_key(vector) {
+            if let Some(name) = components.iter().find(|name| args.contains_key(**name)) {
+                return context.eval_err(EvalErrorType::ConflictingArgs {
+                    name: vector.into(),
+                    other: (*name).into(),
+                });
+            }
+            return Self::vector_arg(vector, args, context);
+        }
+
+        let mut vector = [0.; 3];
+        for (component, name) in vector.iter_mut().zip(components) {
+            if args.contains_key(name) {
+                *component = Self::num_arg(name, args, context)?;
+            }
+        }
+        Ok(Vector3::from(vector))
+    }
+
+    fn list_arg<'a, 'src>(
+        name: &str,
+        args: &'a BTreeMap<String, Value>,
+        context: &EvalContext,
+    ) -> EvalResult<'src, &'a [Value]> {
+        let Some(val) = args.get(name) else {
+            return context.eval_err(EvalErrorType::ArgNotFound { name: name.into() });
+        };
+
+        let Value::List(items) = val else {
+            return context.eval_err(EvalErrorType::ArgWrongType {
+                name: name.into(),
+                expected: Value::LIST_TYPE_NAME,
+                got: val.type_name(),
+            });
+        };
+
+        Ok(items)
+    }
+
+    /// Like [`BuiltInStatic::list_arg`] but every item must be a number.
+    fn num_list_arg<'src>(
+        name: &str,
+        args: &BTreeMap<String, Value>,
+        context: &EvalContext,
+    ) -> EvalResult<'src, Vec<f64>> {
+        Self::list_arg(name, args, context)?
+            .iter()
+            .map(|item| match item {
+                Value::Number(num) => Ok(*num),
+                _ => context.eval_err(EvalErrorType::ArgWrongType {
+                    name: name.into(),
+                    expected: Value::NUMBER_TYPE_NAME,
+                    got: item.type_name(),
+                }),
+            })
+            .collect()
+    }
+
+    /// Like [`BuiltInStatic::list_arg`] but every item must be a solid.
+    fn solid_list_arg<'src>(
+        name: &str,
+        args: &BTreeMap<String, Value>,
+        context: &EvalContext,
+    ) -> EvalResult<'src, Vec<SolidId>> {
+        Self::list_arg(name, args, context)?
+            .iter()
+            .map(|item| match item {
+                Value::Solid(solid) => Ok(*solid),
+                _ => context.eval_err(EvalErrorType::ArgWrongType {
+                    name: name.into(),
+                    expected: Value::SOLID_TYPE_NAME,
+                    got: item.type_name(),
+                }),
+            })
+            .collect()
+    }
+
+    /// A list of points, each given as a list of three coordinates.
+    fn point_list_arg<'src>(
+        name: &str,
+        args: &BTreeMap<String, Value>,
+        context: &EvalContext,
+    ) -> EvalResult<'src, Vec<Point3>> {
+        Self::list_arg(name, args, context)?
+            .iter()
+            .map(|item| {
+                let Value::List(coords) = item else {
+                    return context.eval_err(EvalErrorType::ArgWrongType {
+                        name: name.into(),
+                        expected: Value::LIST_TYPE_NAME,
+                        got: item.type_name(),
+                    });
+                };
+
+                let coords = coords
+                    .iter()
+                    .map(|coord| match coord {
+                        Value::Number(num) => Ok(*num),
+                        _ => context.eval_err(EvalErrorType::ArgWrongType {
+                            name: name.into(),
+                            expected: Value::NUMBER_TYPE_NAME,
+                            got: coord.type_name(),
+                        }),
+                    })
+                    .collect::<EvalResult<Vec<f64>>>()?;
+
+                let [x, y, z] = coords[..] else {
+                    return context.eval_err(EvalErrorType::PointWrongDimension {
+                        name: name.into(),
+                        got: coords.len(),
+                    });
+                };
+
+                Ok(Point3::new(x, y, z))
+            })
+            .collect()
+    }
+
+    fn solid_arg<'src>(
+        name: &str,
+        args: &BTreeMap<String, Value>,
+        context: &EvalContext,
+    ) -> EvalResult<'src, SolidId> {
+        let Some(val) = args.get(name) else {
+            return context.eval_err(EvalErrorType::ArgNotFound { name: name.into() });
+        };
+
+        let Value::Solid(solid) = val else {
+            return context.eval_err(EvalErrorType::ArgWrongType {
+                name: name.into(),
+                expected: Value::SOLID_TYPE_NAME,
+                got: val.type_name(),
+            });
+        };
+
+        Ok(*solid)
+    }
 }
 
 impl<T: BuiltInStatic> BuiltIn for T {
@@ -78,8 +414,17 @@ impl<T: BuiltInStatic> BuiltIn for T {
         T::ARGS
     }
 
+    fn optional_args(&self) -> &'static [&'static str] {
+        T::OPTIONAL_ARGS
+    }
+
+    fn variadic(&self) -> bool {
+        T::VARIADIC
+    }
+
     fn eval<'src>(&self, 
         solids: &mut SolidSet,
+        _params: &HashMap<String, Value>,
         scope: &Scope, context: &EvalContext) -> EvalResult<'src, Value> {
         let args = scope.args();
 
@@ -88,6 +433,35 @@ impl<T: BuiltInStatic> BuiltIn for T {
 }
 
 impl<'set, 'src> EvalCache<'set, 'src> {
+    pub(super) fn eval_special_form(
+        &mut self,
+        call_expr: &SpannedFuncCallExpr<'src>,
+        scope: &Scope,
+        context: &EvalContext,
+    ) -> EvalResult<'src, Value> {
+        match call_expr.name.name_part.text {
+            CACHE => self.eval_cache_call(call_expr, scope, context),
+            MAP => self.eval_map_call(call_expr, scope, context),
+            SWITCH => self.eval_switch_call(call_expr, scope, context),
+            name => context.eval_err(EvalErrorType::BuiltInNotFound { name: name.into() }),
+        }
+    }
+
+    /// For special forms that evaluate to one of their argument expressions,
+    /// picks that expression without evaluating it. Its value is the value of
+    /// the call, so a call in it is in tail position if the special form is.
+    pub(super) fn special_form_branch<'e>(
+        &mut self,
+        call_expr: &'e SpannedFuncCallExpr<'src>,
+        scope: &Scope,
+        context: &EvalContext,
+    ) -> EvalResult<'src, Option<&'e SpannedExpr<'src>>> {
+        match call_expr.name.name_part.text {
+            SWITCH => self.select_switch_case(call_expr, scope, context).map(Some),
+            _ => Ok(None),
+        }
+    }
+
     pub(super) fn eval_built_in_call_args(
         &mut self,
         call_expr: &SpannedFuncCallExpr<'src>,
@@ -95,64 +469,175 @@ impl<'set, 'src> EvalCache<'set, 'src> {
         scope: &Scope,
         context: &EvalContext,
     ) -> EvalResult<'src, BTreeMap<String, Value>> {
-        let mut args =
-            self.eval_supplied_built_in_call_args(call_expr, built_in, scope, context)?;
+        let mut args = self.eval_supplied_built_in_call_args(
+            call_expr,
+            built_in.arg_defs(),
+            built_in.variadic(),
+            scope,
+            context,
+        )?;
         built_in.add_default_args(&mut args, context)?;
         Ok(args)
     }
 
     /// Evaluates supplied arguments and returns an error if there are too many.
+    ///
+    /// If the built-in is `variadic`, positional arguments from the last one
+    /// on are collected into a list, see [`BuiltIn::variadic`].
     fn eval_supplied_built_in_call_args(
         &mut self,
         func_call: &SpannedFuncCallExpr<'src>,
-        built_in: &dyn BuiltIn,
+        arg_defs: &[BuiltInArgDef],
+        variadic: bool,
         scope: &Scope,
         context: &EvalContext,
     ) -> EvalResult<'src, BTreeMap<String, Value>> {
-        let arg_defs = built_in.arg_defs();
+        let mut arg_vals = BTreeMap::new();
 
-        match &func_call.args {
-            CallArgs::None => Ok(BTreeMap::new()),
-            CallArgs::Positional(args) => {
-                let mut arg_vals = BTreeMap::new();
+        let mut positional = func_call.args.positional();
+        let mut rest: &[_] = &[];
+        if variadic {
+            let fixed = arg_defs.len().saturating_sub(1).min(positional.len());
+            (positional, rest) = positional.split_at(fixed);
+        }
 
-                for (arg_index, arg_expr) in args.iter().enumerate() {
-                    let Some(arg_def) = arg_defs.get(arg_index) else {
-                        return context.eval_err(EvalErrorType::TooManyArgs);
-                    };
+        for (arg_index, arg_expr) in positional.iter().enumerate() {
+            let Some(arg_def) = arg_defs.get(arg_index) else {
+                return context.eval_err(EvalErrorType::TooManyArgs);
+            };
 
-                    let val = self.eval_expr(&arg_expr, scope, context)?;
-                    arg_vals.insert(arg_def.name.into(), val);
-                }
+            let val = self.eval_expr(arg_expr, scope, context)?;
+            arg_vals.insert(arg_def.name.into(), val);
+        }
 
-                Ok(arg_vals)
+        if !rest.is_empty() {
+            let Some(arg_def) = arg_defs.last() else {
+                return context.eval_err(EvalErrorType::TooManyArgs);
+            };
+
+            let mut vals = Vec::with_capacity(rest.len());
+            for arg_expr in rest {
+                vals.push(self.eval_expr(arg_expr, scope, context)?);
             }
-            CallArgs::Named(args) => {
-                let mut arg_vals = BTreeMap::new();
-
-                for (name, arg) in args {
-                    if arg_defs.iter().all(|f| &f.name != name) {
-                        return context.eval_err(EvalErrorType::InvalidNamedArg {
-                            name: (*name).into(),
-                        });
-                    }
-
-                    let val = self.eval_expr(&arg.expr, scope, context)?;
-                    arg_vals.insert((*name).into(), val);
-                }
+            arg_vals.insert(arg_def.name.into(), Value::List(vals));
+        }
 
-                Ok(arg_vals)
+        for (name, arg) in func_call.args.named() {
+            if arg_defs.iter().all(|f| &f.name != name) {
+                return context.eval_err(EvalErrorType::InvalidNamedArg {
+                    name: (*name).into(),
+                });
             }
+            if arg_vals.contains_key(*name) {
+                return context.eval_err(EvalErrorType::ArgSuppliedTwice {
+                    name: (*name).into(),
+                });
+            }
+
+            let val = self.eval_expr(&arg.expr, scope, context)?;
+            arg_vals.insert((*name).into(), val);
         }
+
+        Ok(arg_vals)
+    }
+
+    /// Finds a built-in by name, checking the host's registered built-ins
+    /// before funcad's own.
+    pub(crate) fn find_built_in(&self, name: &str) -> Option<&'set dyn BuiltIn> {
+        self.built_ins
+            .and_then(|registry| registry.get(name))
+            .or_else(|| Self::get_built_in_func(name))
     }
 
     pub(crate) fn get_built_in_func(name: &str) -> Option<&'static dyn BuiltIn> {
         match name {
+            "Empty" => Some(&shapes::Empty() as &dyn BuiltIn),
+            "Universal" => Some(&shapes::Universal() as &dyn BuiltIn),
             "Cube" => Some(&shapes::Cube() as &dyn BuiltIn),
+            "Box" => Some(&shapes::Cuboid() as &dyn BuiltIn),
+            "RectFrustum" => Some(&shapes::RectFrustum() as &dyn BuiltIn),
+            "Cylinder" => Some(&shapes::Cylinder() as &dyn BuiltIn),
+            "Cone" => Some(&shapes::Cone() as &dyn BuiltIn),
+            "Hull" => Some(&shapes::Hull() as &dyn BuiltIn),
+            "Mesh" => Some(&import_mesh::Mesh() as &dyn BuiltIn),
+
+            "Circle" => Some(&sketch::Circle() as &dyn BuiltIn),
+            "Rectangle" => Some(&sketch::Rectangle() as &dyn BuiltIn),
+            "Extrude" => Some(&sketch::Extrude() as &dyn BuiltIn),
+
+            "Translate" => Some(&transforms::Translate() as &dyn BuiltIn),
+            "Mirror" => Some(&transforms::Mirror() as &dyn BuiltIn),
+            "Symmetrize" => Some(&transforms::Symmetrize() as &dyn BuiltIn),
+            "PlaceAt" => Some(&transforms::PlaceAt() as &dyn BuiltIn),
+            "ClipBox" => Some(&transforms::ClipBox() as &dyn BuiltIn),
+            "Shell" => Some(&transforms::Shell() as &dyn BuiltIn),
+            "Chamfer" => Some(&transforms::Chamfer() as &dyn BuiltIn),
+            "DropToFloor" => Some(&transforms::DropToFloor() as &dyn BuiltIn),
+            "BlendPosition" => Some(&transforms::BlendPosition() as &dyn BuiltIn),
+            "HoleGrid" => Some(&features::HoleGrid() as &dyn BuiltIn),
+            "ChamferZ" => Some(&features::ChamferZ() as &dyn BuiltIn),
+            "LinearPattern" => Some(&features::LinearPattern() as &dyn BuiltIn),
+            "PolarPattern" => Some(&features::PolarPattern() as &dyn BuiltIn),
+
+            "Scale" => Some(&transforms::Scale() as &dyn BuiltIn),
+            "ScaleAbout" => Some(&transforms::ScaleAbout() as &dyn BuiltIn),
+            "Rotate" => Some(&transforms::Rotate() as &dyn BuiltIn),
+            "RotateAbout" => Some(&transforms::RotateAbout() as &dyn BuiltIn),
+
+            "CentroidX" => Some(&measure::Centroid::<0>() as &dyn BuiltIn),
+            "CentroidY" => Some(&measure::Centroid::<1>() as &dyn BuiltIn),
+            "CentroidZ" => Some(&measure::Centroid::<2>() as &dyn BuiltIn),
+            "EnclosingBox" => Some(&measure::EnclosingBox() as &dyn BuiltIn),
+            "Diagonal" => Some(&measure::Diagonal() as &dyn BuiltIn),
+            "Volume" => Some(&measure::Volume() as &dyn BuiltIn),
+            "BBoxSize" => Some(&measure::BBoxSize() as &dyn BuiltIn),
+            "TransformedBounds" => Some(&measure::TransformedBounds() as &dyn BuiltIn),
+            "ComponentCount" => Some(&measure::ComponentCount() as &dyn BuiltIn),
+            "LargestComponent" => Some(&measure::LargestComponent() as &dyn BuiltIn),
+            "TriangleCount" => Some(&measure::TriangleCount() as &dyn BuiltIn),
 
             "Sin" => Some(&math::Sin() as &dyn BuiltIn),
             "Cos" => Some(&math::Cos() as &dyn BuiltIn),
             "Tan" => Some(&math::Tan() as &dyn BuiltIn),
+            "Asin" => Some(&math::Asin() as &dyn BuiltIn),
+            "Acos" => Some(&math::Acos() as &dyn BuiltIn),
+            "Atan" => Some(&math::Atan() as &dyn BuiltIn),
+            "Atan2" => Some(&math::Atan2() as &dyn BuiltIn),
+            "Rand" => Some(&math::Rand() as &dyn BuiltIn),
+            "SafeDiv" => Some(&math::SafeDiv() as &dyn BuiltIn),
+            "Root" => Some(&math::Root() as &dyn BuiltIn),
+            "Sqrt" => Some(&math::Sqrt() as &dyn BuiltIn),
+            "Pow" => Some(&math::Pow() as &dyn BuiltIn),
+            "Ln" => Some(&math::Ln() as &dyn BuiltIn),
+            "Log" => Some(&math::Log() as &dyn BuiltIn),
+            "Exp" => Some(&math::Exp() as &dyn BuiltIn),
+            "Abs" => Some(&math::Abs() as &dyn BuiltIn),
+            "Floor" => Some(&math::Floor() as &dyn BuiltIn),
+            "Ceil" => Some(&math::Ceil() as &dyn BuiltIn),
+            "Round" => Some(&math::Round() as &dyn BuiltIn),
+            "ApproxEq" => Some(&math::ApproxEq() as &dyn BuiltIn),
+            "DegToRad" => Some(&math::DegToRad() as &dyn BuiltIn),
+            "RadToDeg" => Some(&math::RadToDeg() as &dyn BuiltIn),
+            "IsInt" => Some(&math::IsInt() as &dyn BuiltIn),
+            "Sort" => Some(&math::Sort() as &dyn BuiltIn),
+            "Factorial" => Some(&math::Factorial() as &dyn BuiltIn),
+            "Binomial" => Some(&math::Binomial() as &dyn BuiltIn),
+            "Pi" => Some(&math::Pi() as &dyn BuiltIn),
+            "E" => Some(&math::E() as &dyn BuiltIn),
+            "Tau" => Some(&math::Tau() as &dyn BuiltIn),
+
+            "Vec3" => Some(&vector::Vec3() as &dyn BuiltIn),
+            "X" => Some(&vector::X() as &dyn BuiltIn),
+            "Y" => Some(&vector::Y() as &dyn BuiltIn),
+            "Z" => Some(&vector::Z() as &dyn BuiltIn),
+
+            "Range" => Some(&list::Range() as &dyn BuiltIn),
+            "RangeStep" => Some(&list::RangeStep() as &dyn BuiltIn),
+
+            "Assert" => Some(&assert::Assert() as &dyn BuiltIn),
+            "AssertValid" => Some(&assert::AssertValid() as &dyn BuiltIn),
+
+            "Param" => Some(&param::Param() as &dyn BuiltIn),
             _ => None,
         }
     }
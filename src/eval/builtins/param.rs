@@ -0,0 +1,61 @@
+use std::collections::HashMap;
+
+use crate::{EvalErrorType, SolidSet, Value};
+
+use super::{BuiltIn, BuiltInArgDef, EvalContext, EvalResult, Scope};
+
+/// Looks up a parameter supplied by the host by `name`, evaluating to
+/// `default` if the host didn't supply it.
+///
+/// This lets a model be parameterized from outside without threading the
+/// parameter through every function that uses it as an argument.
+pub(super) struct Param();
+
+impl Param {
+    const ARGS: &[BuiltInArgDef] = &[
+        BuiltInArgDef {
+            name: "name",
+            default: None,
+        },
+        BuiltInArgDef {
+            name: "default",
+            default: None,
+        },
+    ];
+}
+
+impl BuiltIn for Param {
+    fn arg_defs(&self) -> &'static [BuiltInArgDef] {
+        Self::ARGS
+    }
+
+    fn eval<'src>(
+        &self,
+        _solids: &mut SolidSet,
+        params: &HashMap<String, Value>,
+        scope: &Scope,
+        context: &EvalContext,
+    ) -> EvalResult<'src, Value> {
+        let args = scope.args();
+        let Some(name) = args.get("name") else {
+            return context.eval_err(EvalErrorType::ArgNotFound {
+                name: "name".into(),
+            });
+        };
+        let Some(default) = args.get("default") else {
+            return context.eval_err(EvalErrorType::ArgNotFound {
+                name: "default".into(),
+            });
+        };
+
+        let Value::String(name) = name else {
+            return context.eval_err(EvalErrorType::ArgWrongType {
+                name: "name".into(),
+                expected: Value::STRING_TYPE_NAME,
+                got: name.type_name(),
+            });
+        };
+
+        Ok(params.get(name).unwrap_or(default).clone())
+    }
+}
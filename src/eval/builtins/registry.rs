@@ -0,0 +1,77 @@
+use std::collections::{BTreeMap, HashMap};
+
+use crate::{error::EvalErrorType, SolidSet, Value};
+
+use super::{BuiltIn, BuiltInArgDef, EvalContext, EvalResult, Scope};
+
+/// A built-in function supplied by the host rather than by funcad.
+///
+/// Arguments are bound to [`CustomBuiltIn::arg_defs`] the same way as for
+/// funcad's own built-ins, by position or by name, with defaults filled in
+/// before [`CustomBuiltIn::eval`] is called. Results are cached on the
+/// arguments like any other call, so a built-in should always give the same
+/// value for the same arguments.
+pub trait CustomBuiltIn {
+    /// The arguments in positional order.
+    fn arg_defs(&self) -> &'static [BuiltInArgDef];
+
+    /// Evaluates the built-in with every argument in `args` by name.
+    ///
+    /// Solids created by the built-in should be pushed to `solids`.
+    fn eval(
+        &self,
+        solids: &mut SolidSet,
+        args: &BTreeMap<String, Value>,
+    ) -> Result<Value, EvalErrorType<'static>>;
+}
+
+/// Built-in functions supplied by the host, by name.
+///
+/// Registered built-ins are found before funcad's own, so they can replace
+/// one, but not the special forms like `Map`. [`crate::validate_names`]
+/// doesn't know about registered built-ins and reports calls to them as
+/// functions that don't exist, so check documents that call them with
+/// [`crate::validate_names_with_built_ins`].
+#[derive(Default)]
+pub struct BuiltInRegistry {
+    built_ins: HashMap<String, Box<dyn CustomBuiltIn>>,
+}
+
+impl BuiltInRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers `built_in` under `name`, replacing any built-in already
+    /// registered under it.
+    pub fn register_builtin(&mut self, name: impl Into<String>, built_in: Box<dyn CustomBuiltIn>) {
+        self.built_ins.insert(name.into(), built_in);
+    }
+
+    pub(crate) fn contains(&self, name: &str) -> bool {
+        self.built_ins.contains_key(name)
+    }
+
+    pub(super) fn get(&self, name: &str) -> Option<&dyn BuiltIn> {
+        self.built_ins
+            .get(name)
+            .map(|built_in| built_in as &dyn BuiltIn)
+    }
+}
+
+impl BuiltIn for Box<dyn CustomBuiltIn> {
+    fn arg_defs(&self) -> &'static [BuiltInArgDef] {
+        CustomBuiltIn::arg_defs(&**self)
+    }
+
+    fn eval<'src>(
+        &self,
+        solids: &mut SolidSet,
+        _params: &HashMap<String, Value>,
+        scope: &Scope,
+        context: &EvalContext,
+    ) -> EvalResult<'src, Value> {
+        CustomBuiltIn::eval(&**self, solids, scope.args())
+            .or_else(|error_type| context.eval_err(error_type))
+    }
+}
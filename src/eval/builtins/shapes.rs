@@ -1,10 +1,80 @@
-use std::collections::BTreeMap;
+use std::{
+    collections::{BTreeMap, HashSet},
+    f64::consts::TAU,
+};
 
-use truck_modeling::{builder, Point3, Vector3};
+use truck_meshalgo::prelude::*;
+use truck_modeling::{
+    builder, BoundingBox, Curve, EuclideanSpace, InnerSpace, Point3, Rad, Shell, Solid, Surface,
+    Vector3, Wire,
+};
 
-use crate::{SolidSet, Value};
+use crate::{EvalErrorType, SolidId, SolidSet, Value};
 
-use super::{BuiltInArgDef, BuiltInStatic, EvalContext, EvalResult};
+use super::{
+    import_mesh::solid_from_polygons, BuiltInArgDef, BuiltInStatic, EvalContext, EvalResult,
+};
+
+/// A box with extents `size` centered on the origin.
+pub(super) fn centered_box(size: Vector3) -> Solid {
+    box_between(Point3::from_vec(-0.5 * size), Point3::from_vec(0.5 * size))
+}
+
+/// An axis-aligned box with opposite corners `min` and `max`.
+pub(super) fn box_between(min: Point3, max: Point3) -> Solid {
+    let size = max - min;
+    let vert = builder::vertex(min);
+    let edge = builder::tsweep(&vert, Vector3::unit_x() * size.x);
+    let face = builder::tsweep(&edge, Vector3::unit_y() * size.y);
+    builder::tsweep(&face, Vector3::unit_z() * size.z)
+}
+
+/// A cylinder with its base on the XY plane centered on the Z axis.
+pub(super) fn cylinder(radius: f64, height: f64) -> Solid {
+    let vert = builder::vertex(Point3::new(radius, 0., 0.));
+    let circle = builder::rsweep(&vert, Point3::origin(), Vector3::unit_z(), Rad(TAU));
+    let disk = builder::try_attach_plane(&[circle]).expect("A circle is always planar");
+    builder::tsweep(&disk, Vector3::unit_z() * height)
+}
+
+/// The empty solid, the identity of union.
+///
+/// This is the starting value when accumulating solids with `+`.
+pub(super) struct Empty();
+
+impl BuiltInStatic for Empty {
+    const ARGS: &[BuiltInArgDef] = &[];
+
+    fn eval_static<'src>(
+        _solids: &mut SolidSet,
+        _args: &BTreeMap<String, Value>,
+        _context: &EvalContext,
+    ) -> EvalResult<'src, Value> {
+        Ok(Value::Solid(SolidId::Empty))
+    }
+}
+
+/// The universal solid, which fills all of space and is the identity of
+/// intersection.
+///
+/// It's the complement of the empty solid, so a part can be carved by
+/// starting from `Universal` and subtracting. It has no boundary, so measuring
+/// or exporting it is an error. What's carved from it is inside out and just
+/// as unbounded until it's intersected with a bounded solid, so exporting
+/// that is an error too.
+pub(super) struct Universal();
+
+impl BuiltInStatic for Universal {
+    const ARGS: &[BuiltInArgDef] = &[];
+
+    fn eval_static<'src>(
+        _solids: &mut SolidSet,
+        _args: &BTreeMap<String, Value>,
+        _context: &EvalContext,
+    ) -> EvalResult<'src, Value> {
+        Ok(Value::Solid(SolidId::Universal))
+    }
+}
 
 pub(super) struct Cube();
 
@@ -21,13 +91,558 @@ impl BuiltInStatic for Cube {
     ) -> EvalResult<'src, Value> {
         let size = Self::num_arg("size", args, context)?;
 
-        let coord = -0.5 * size;
-        let vert = builder::vertex(Point3::new(coord, coord, coord));
-        let edge = builder::tsweep(&vert, Vector3::unit_x() * size);
-        let face = builder::tsweep(&edge, Vector3::unit_y() * size);
-        let cube = builder::tsweep(&face, Vector3::unit_z() * size);
-        let id = solids.push(cube);
+        let id = solids.push(centered_box(Vector3::new(size, size, size)));
 
         Ok(Value::Solid(id))
     }
 }
+
+/// A box centered on the origin with extents `x`, `y` and `z`, which must all
+/// be positive.
+pub(super) struct Cuboid();
+
+impl BuiltInStatic for Cuboid {
+    const ARGS: &[BuiltInArgDef] = &[
+        BuiltInArgDef {
+            name: "x",
+            default: Some(Value::Number(1.)),
+        },
+        BuiltInArgDef {
+            name: "y",
+            default: Some(Value::Number(1.)),
+        },
+        BuiltInArgDef {
+            name: "z",
+            default: Some(Value::Number(1.)),
+        },
+    ];
+
+    fn eval_static<'src>(
+        solids: &mut SolidSet,
+        args: &BTreeMap<String, Value>,
+        context: &EvalContext,
+    ) -> EvalResult<'src, Value> {
+        let size = Vector3::new(
+            Self::positive_arg("x", args, context)?,
+            Self::positive_arg("y", args, context)?,
+            Self::positive_arg("z", args, context)?,
+        );
+
+        let id = solids.push(centered_box(size));
+
+        Ok(Value::Solid(id))
+    }
+}
+
+/// A cylinder of `radius` and `height` with its base on the XY plane centered
+/// on the Z axis.
+///
+/// The curved face is exact, but it's approximated to within the solid
+/// tolerance when the cylinder is meshed.
+pub(super) struct Cylinder();
+
+impl BuiltInStatic for Cylinder {
+    const ARGS: &[BuiltInArgDef] = &[
+        BuiltInArgDef {
+            name: "radius",
+            default: None,
+        },
+        BuiltInArgDef {
+            name: "height",
+            default: None,
+        },
+    ];
+
+    fn eval_static<'src>(
+        solids: &mut SolidSet,
+        args: &BTreeMap<String, Value>,
+        context: &EvalContext,
+    ) -> EvalResult<'src, Value> {
+        let radius = Self::positive_arg("radius", args, context)?;
+        let height = Self::positive_arg("height", args, context)?;
+
+        Ok(Value::Solid(solids.push(cylinder(radius, height))))
+    }
+}
+
+/// A frustum of a rectangular pyramid tapering from a `bottom_x` by `bottom_y`
+/// rectangle on the XY plane to a `top_x` by `top_y` rectangle at `height`.
+///
+/// Both rectangles are centered on the Z axis. A top or bottom dimension of 0
+/// collapses that side of the rectangle, so a 0 by 0 top gives a pyramid.
+pub(super) struct RectFrustum();
+
+impl BuiltInStatic for RectFrustum {
+    const ARGS: &[BuiltInArgDef] = &[
+        BuiltInArgDef {
+            name: "bottom_x",
+            default: None,
+        },
+        BuiltInArgDef {
+            name: "bottom_y",
+            default: None,
+        },
+        BuiltInArgDef {
+            name: "top_x",
+            default: None,
+        },
+        BuiltInArgDef {
+            name: "top_y",
+            default: None,
+        },
+        BuiltInArgDef {
+            name: "height",
+            default: None,
+        },
+    ];
+
+    fn eval_static<'src>(
+        solids: &mut SolidSet,
+        args: &BTreeMap<String, Value>,
+        context: &EvalContext,
+    ) -> EvalResult<'src, Value> {
+        let mut dims = [0.; 5];
+        for (dim, def) in dims.iter_mut().zip(Self::ARGS) {
+            *dim = Self::num_arg(def.name, args, context)?;
+            if *dim < 0. {
+                return context.eval_err(EvalErrorType::InvalidDimension {
+                    name: def.name.into(),
+                    reason: "must not be negative",
+                });
+            }
+        }
+        let [bottom_x, bottom_y, top_x, top_y, height] = dims;
+
+        if height == 0. {
+            return context.eval_err(EvalErrorType::InvalidDimension {
+                name: "height".into(),
+                reason: "must be positive",
+            });
+        }
+        if bottom_x == 0. && top_x == 0. {
+            return context.eval_err(EvalErrorType::InvalidDimension {
+                name: "bottom_x".into(),
+                reason: "either bottom_x or top_x must be positive",
+            });
+        }
+        if bottom_y == 0. && top_y == 0. {
+            return context.eval_err(EvalErrorType::InvalidDimension {
+                name: "bottom_y".into(),
+                reason: "either bottom_y or top_y must be positive",
+            });
+        }
+
+        let rect = |x: f64, y: f64, z: f64| {
+            [
+                Point3::new(-0.5 * x, -0.5 * y, z),
+                Point3::new(0.5 * x, -0.5 * y, z),
+                Point3::new(0.5 * x, 0.5 * y, z),
+                Point3::new(-0.5 * x, 0.5 * y, z),
+            ]
+        };
+
+        // Collapsed sides leave coincident corners which are merged so faces
+        // don't have zero length edges.
+        let mut points: Vec<Point3> = Vec::new();
+        let indices: Vec<usize> = rect(bottom_x, bottom_y, 0.)
+            .into_iter()
+            .chain(rect(top_x, top_y, height))
+            .map(|pt| match points.iter().position(|p| *p == pt) {
+                Some(index) => index,
+                None => {
+                    points.push(pt);
+                    points.len() - 1
+                }
+            })
+            .collect();
+
+        // Faces are wound counter-clockwise when viewed from outside.
+        let faces: Vec<Vec<usize>> = [
+            [0, 3, 2, 1],
+            [4, 5, 6, 7],
+            [0, 1, 5, 4],
+            [1, 2, 6, 5],
+            [2, 3, 7, 6],
+            [3, 0, 4, 7],
+        ]
+        .iter()
+        .map(|face| {
+            let mut face: Vec<usize> = face.iter().map(|corner| indices[*corner]).collect();
+            face.dedup();
+            if face.len() > 1 && face.first() == face.last() {
+                face.pop();
+            }
+            face
+        })
+        .filter(|face| face.len() >= 3)
+        .collect();
+
+        match solid_from_polygons(&points, &faces) {
+            Ok(solid) => Ok(Value::Solid(solids.push(solid))),
+            Err(_) => {
+                context.eval_err(EvalErrorType::GeometryOperationFailed { op: "RectFrustum" })
+            }
+        }
+    }
+}
+
+/// A cone or frustum of a cone tapering from a circle of `bottom_radius` on
+/// the XY plane to a circle of `top_radius` at `height`, centered on the Z
+/// axis.
+///
+/// A radius within the solid tolerance of 0 collapses that end to a point, so
+/// the default `top_radius` of 0 gives a cone.
+pub(super) struct Cone();
+
+impl BuiltInStatic for Cone {
+    const ARGS: &[BuiltInArgDef] = &[
+        BuiltInArgDef {
+            name: "bottom_radius",
+            default: None,
+        },
+        BuiltInArgDef {
+            name: "top_radius",
+            default: Some(Value::Number(0.)),
+        },
+        BuiltInArgDef {
+            name: "height",
+            default: None,
+        },
+    ];
+
+    fn eval_static<'src>(
+        solids: &mut SolidSet,
+        args: &BTreeMap<String, Value>,
+        context: &EvalContext,
+    ) -> EvalResult<'src, Value> {
+        let mut dims = [0.; 3];
+        for (dim, def) in dims.iter_mut().zip(Self::ARGS) {
+            *dim = Self::num_arg(def.name, args, context)?;
+            if !dim.is_finite() {
+                return context.eval_err(EvalErrorType::NumExprNotFinite {
+                    location: context.location(),
+                });
+            }
+            if *dim < 0. {
+                return context.eval_err(EvalErrorType::InvalidDimension {
+                    name: def.name.into(),
+                    reason: "must not be negative",
+                });
+            }
+        }
+        let [bottom_radius, top_radius, height] = dims;
+
+        if height == 0. {
+            return context.eval_err(EvalErrorType::InvalidDimension {
+                name: "height".into(),
+                reason: "must be positive",
+            });
+        }
+        let (bottom_radius, top_radius) = (
+            collapse_within(bottom_radius, solids.tolerance),
+            collapse_within(top_radius, solids.tolerance),
+        );
+        if bottom_radius == 0. && top_radius == 0. {
+            return context.eval_err(EvalErrorType::NumExprNotFinite {
+                location: context.location(),
+            });
+        }
+
+        // The profile runs down from the top of the axis, out along the
+        // radii and back to the axis. Sweeping it around the axis closes the
+        // ends and leaves no degenerate edges where a radius is 0.
+        let mut profile = vec![Point3::new(0., 0., height)];
+        if top_radius > 0. {
+            profile.push(Point3::new(top_radius, 0., height));
+        }
+        if bottom_radius > 0. {
+            profile.push(Point3::new(bottom_radius, 0., 0.));
+        }
+        profile.push(Point3::origin());
+
+        let verts: Vec<_> = profile.into_iter().map(builder::vertex).collect();
+        let wire: Wire = verts
+            .windows(2)
+            .map(|pair| builder::line(&pair[0], &pair[1]))
+            .collect();
+        let shell = builder::cone(&wire, Vector3::unit_z(), Rad(TAU));
+
+        Ok(Value::Solid(solids.push(Solid::new(vec![shell]))))
+    }
+}
+
+/// The convex hull of any number of solids, the smallest convex solid that
+/// contains them all.
+///
+/// The solids are passed as separate arguments, as in `Hull(a, b, c)`, which
+/// are collected into the list `solids`. The hull is built from points sampled
+/// by [`hull_points`], so curved faces are approximated and the hull only has
+/// planar faces. Empty solids are skipped, and nothing but empty solids gives
+/// the empty solid. Solids that are flat together have no hull with volume,
+/// which is an error.
+pub(super) struct Hull();
+
+impl BuiltInStatic for Hull {
+    const ARGS: &[BuiltInArgDef] = &[BuiltInArgDef {
+        name: "solids",
+        default: None,
+    }];
+    const VARIADIC: bool = true;
+
+    fn eval_static<'src>(
+        solids: &mut SolidSet,
+        args: &BTreeMap<String, Value>,
+        context: &EvalContext,
+    ) -> EvalResult<'src, Value> {
+        let mut points: Vec<Point3> = Vec::new();
+        for solid in Self::solid_list_arg("solids", args, context)? {
+            match solid {
+                SolidId::Regular(_) => {
+                    points.extend(hull_points(solids.try_get(&solid)?, solids.tolerance))
+                }
+                SolidId::Empty => {}
+                SolidId::Universal => return context.eval_err(EvalErrorType::UniversalSolid),
+            }
+        }
+
+        if points.is_empty() {
+            return Ok(Value::Solid(SolidId::Empty));
+        }
+
+        let Some(faces) = convex_hull(&points, solids.tolerance) else {
+            return context.eval_err(EvalErrorType::InvalidSolid {
+                reason: "the solids are flat so their hull has no volume".into(),
+            });
+        };
+
+        let hull = solid_from_polygons(&points, &faces)
+            .or_else(|reason| context.eval_err(EvalErrorType::InvalidSolid { reason }))?;
+        Ok(Value::Solid(solids.push(hull)))
+    }
+}
+
+/// The number of points [`hull_points`] samples along a curved edge.
+const HULL_EDGE_SAMPLES: usize = 64;
+
+/// How closely [`hull_points`] approximates a curved face, relative to the
+/// size of its boundary.
+const HULL_RELATIVE_TOLERANCE: f64 = 0.001;
+
+/// Points on the boundary of `solid` with about the same convex hull.
+///
+/// The hull of a planar face is the hull of its edges, so only the edges of
+/// planar faces are sampled, at their ends if they're straight or at
+/// [`HULL_EDGE_SAMPLES`] points if they're curved. Curved faces are also
+/// triangulated to within [`HULL_RELATIVE_TOLERANCE`] of their size, or
+/// `tolerance` if that's coarser, so no face gives more points than its shape
+/// needs however fine `tolerance` is.
+fn hull_points(solid: &Solid, tolerance: f64) -> Vec<Point3> {
+    let mut points = Vec::new();
+    for face in solid.face_iter() {
+        let start = points.len();
+        for edge in face.boundaries().iter().flatten() {
+            match edge.curve() {
+                Curve::Line(_) => points.push(edge.front().point()),
+                curve => {
+                    let (t0, t1) = curve.range_tuple();
+                    points.extend(
+                        (0..HULL_EDGE_SAMPLES).map(|i| {
+                            curve.subs(t0 + (t1 - t0) * i as f64 / HULL_EDGE_SAMPLES as f64)
+                        }),
+                    );
+                }
+            }
+        }
+
+        if let Surface::Plane(_) = face.surface() {
+            continue;
+        }
+        let size = points[start..]
+            .iter()
+            .collect::<BoundingBox<Point3>>()
+            .diameter();
+        let shell: Shell = vec![face.clone()].into();
+        let mesh = shell.triangulation(tolerance.max(size * HULL_RELATIVE_TOLERANCE));
+        points.extend(mesh.to_polygon().positions());
+    }
+    points
+}
+
+/// A triangle of a hull under construction, wound counter-clockwise around
+/// its outward `normal`.
+struct HullTriangle {
+    verts: [usize; 3],
+    normal: Vector3,
+    offset: f64,
+}
+
+impl HullTriangle {
+    fn new(verts: [usize; 3], points: &[Point3]) -> Self {
+        let [a, b, c] = verts.map(|i| points[i]);
+        let normal = (b - a).cross(c - a).normalize();
+        Self {
+            verts,
+            normal,
+            offset: normal.dot(a.to_vec()),
+        }
+    }
+
+    /// How far `point` is in front of the triangle's plane.
+    fn height(&self, point: Point3) -> f64 {
+        self.normal.dot(point.to_vec()) - self.offset
+    }
+}
+
+/// The faces of the convex hull of `points`, as polygons of indices into
+/// `points` wound counter-clockwise when viewed from outside.
+///
+/// Points within `tolerance` of the hull are treated as inside it, and
+/// coplanar triangles are merged into a single face. Returns `None` if the
+/// points are all within `tolerance` of a plane.
+pub(super) fn convex_hull(points: &[Point3], tolerance: f64) -> Option<Vec<Vec<usize>>> {
+    let farthest = |dist: &dyn Fn(Point3) -> f64| {
+        (0..points.len())
+            .map(|i| (i, dist(points[i])))
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .filter(|(_, d)| *d > tolerance)
+            .map(|(i, _)| i)
+    };
+
+    // Start from a tetrahedron that's as large as possible so later points
+    // are less likely to be within tolerance of its faces.
+    let a = 0;
+    let b = farthest(&|p| p.distance(points[a]))?;
+    let line = (points[b] - points[a]).normalize();
+    let c = farthest(&|p| (p - points[a]).cross(line).magnitude())?;
+    let plane = line.cross(points[c] - points[a]).normalize();
+    let d = farthest(&|p| plane.dot(p - points[a]).abs())?;
+
+    let mut triangles: Vec<HullTriangle> = [[a, b, c], [a, c, d], [a, d, b], [b, d, c]]
+        .into_iter()
+        .map(|verts| HullTriangle::new(verts, points))
+        .collect();
+    // The winding above is outward only if `d` is below `a`, `b` and `c`.
+    if triangles[0].height(points[d]) > 0. {
+        for triangle in &mut triangles {
+            triangle.verts.swap(1, 2);
+            *triangle = HullTriangle::new(triangle.verts, points);
+        }
+    }
+
+    for (i, &point) in points.iter().enumerate() {
+        let visible: Vec<bool> = triangles
+            .iter()
+            .map(|triangle| triangle.height(point) > tolerance)
+            .collect();
+        if !visible.contains(&true) {
+            continue;
+        }
+
+        // The horizon is made of the edges of visible triangles that aren't
+        // shared with another visible triangle. Each is joined to the new
+        // point with the same winding as the triangle it came from.
+        let edges = |triangle: &HullTriangle| {
+            let [u, v, w] = triangle.verts;
+            [(u, v), (v, w), (w, u)]
+        };
+        let visible_edges: HashSet<_> = triangles
+            .iter()
+            .zip(&visible)
+            .filter(|(_, visible)| **visible)
+            .flat_map(|(triangle, _)| edges(triangle))
+            .collect();
+        let new_triangles: Vec<_> = triangles
+            .iter()
+            .zip(&visible)
+            .filter(|(_, visible)| **visible)
+            .flat_map(|(triangle, _)| edges(triangle))
+            .filter(|(u, v)| !visible_edges.contains(&(*v, *u)))
+            .map(|(u, v)| HullTriangle::new([u, v, i], points))
+            .collect();
+
+        let mut visible = visible.into_iter();
+        triangles.retain(|_| !visible.next().unwrap_or_default());
+        triangles.extend(new_triangles);
+    }
+
+    // Group the triangles by the plane they're in.
+    let mut planes: Vec<(Vector3, f64, Vec<usize>)> = Vec::new();
+    for triangle in &triangles {
+        let plane = planes.iter_mut().find(|(normal, offset, _)| {
+            (*normal - triangle.normal).magnitude() < tolerance
+                && (*offset - triangle.offset).abs() < tolerance
+        });
+        match plane {
+            Some((_, _, verts)) => verts.extend(triangle.verts),
+            None => planes.push((triangle.normal, triangle.offset, triangle.verts.into())),
+        }
+    }
+
+    let faces = planes
+        .into_iter()
+        .map(|(normal, _, mut verts)| {
+            verts.sort_unstable();
+            verts.dedup();
+            planar_hull(points, &verts, normal, tolerance)
+        })
+        .collect();
+    Some(faces)
+}
+
+/// The vertices of the 2D convex hull of the points at `verts`, which all lie
+/// in a plane with `normal`, wound counter-clockwise around `normal`.
+///
+/// Vertices within `tolerance` of the line between their neighbours are
+/// dropped so each face edge is a single hull edge.
+fn planar_hull(points: &[Point3], verts: &[usize], normal: Vector3, tolerance: f64) -> Vec<usize> {
+    // Any axis that isn't nearly parallel to the normal gives a basis for the
+    // plane.
+    let axis = if normal.x.abs() < 0.5 {
+        Vector3::unit_x()
+    } else {
+        Vector3::unit_y()
+    };
+    let u = normal.cross(axis).normalize();
+    let v = normal.cross(u);
+
+    let mut projected: Vec<_> = verts
+        .iter()
+        .map(|&i| (i, u.dot(points[i].to_vec()), v.dot(points[i].to_vec())))
+        .collect();
+    projected.sort_by(|a, b| a.1.total_cmp(&b.1).then(a.2.total_cmp(&b.2)));
+
+    // Andrew's monotone chain, building the lower then the upper half.
+    let turns_left = |o: &(usize, f64, f64), a: &(usize, f64, f64), b: &(usize, f64, f64)| {
+        let (ax, ay) = (a.1 - o.1, a.2 - o.2);
+        let (bx, by) = (b.1 - o.1, b.2 - o.2);
+        let cross = ax * by - ay * bx;
+        // The cross product over the distance from `o` to `b` is how far `a`
+        // is from the line through them.
+        cross > tolerance * (bx * bx + by * by).sqrt()
+    };
+    let mut hull: Vec<(usize, f64, f64)> = Vec::new();
+    for pass in [projected.clone(), projected.into_iter().rev().collect()] {
+        let start = hull.len();
+        for point in pass {
+            while hull.len() >= start + 2
+                && !turns_left(&hull[hull.len() - 2], &hull[hull.len() - 1], &point)
+            {
+                hull.pop();
+            }
+            hull.push(point);
+        }
+        // The last point of each half is the first of the other.
+        hull.pop();
+    }
+
+    hull.into_iter().map(|(i, _, _)| i).collect()
+}
+
+/// Rounds `num` to 0 if it's within `tolerance` of it.
+fn collapse_within(num: f64, tolerance: f64) -> f64 {
+    if num.abs() < tolerance {
+        0.
+    } else {
+        num
+    }
+}
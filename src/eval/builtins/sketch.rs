@@ -0,0 +1,255 @@
+use std::{
+    collections::BTreeMap,
+    f64::consts::TAU,
+    panic::{self, AssertUnwindSafe},
+};
+
+use truck_meshalgo::prelude::*;
+use truck_modeling::{
+    builder, BSplineCurve, Curve, EuclideanSpace, Face, InnerSpace, Line, Point3, Rad, Solid,
+    Surface, Vector3,
+};
+
+use crate::{EvalErrorType, SolidId, SolidSet, Value};
+
+use super::{shapes::box_between, BuiltInArgDef, BuiltInStatic, EvalContext, EvalResult};
+
+/// A circle of `radius` centered on the origin.
+///
+/// The circle is exact, but like [`super::shapes::Cylinder`] it's very slow
+/// to combine with other shapes.
+pub(super) struct Circle();
+
+impl BuiltInStatic for Circle {
+    const ARGS: &[BuiltInArgDef] = &[BuiltInArgDef {
+        name: "radius",
+        default: None,
+    }];
+
+    fn eval_static<'src>(
+        _solids: &mut SolidSet,
+        args: &BTreeMap<String, Value>,
+        context: &EvalContext,
+    ) -> EvalResult<'src, Value> {
+        let radius = Self::positive_arg("radius", args, context)?;
+
+        let vert = builder::vertex(Point3::new(radius, 0., 0.));
+        let circle = builder::rsweep(&vert, Point3::origin(), Vector3::unit_z(), Rad(TAU));
+        let disk = builder::try_attach_plane(&[circle]).expect("A circle is always planar");
+        Ok(Value::Shape2D(vec![disk]))
+    }
+}
+
+/// A rectangle with sides `x` and `y` centered on the origin.
+pub(super) struct Rectangle();
+
+impl BuiltInStatic for Rectangle {
+    const ARGS: &[BuiltInArgDef] = &[
+        BuiltInArgDef {
+            name: "x",
+            default: None,
+        },
+        BuiltInArgDef {
+            name: "y",
+            default: None,
+        },
+    ];
+
+    fn eval_static<'src>(
+        _solids: &mut SolidSet,
+        args: &BTreeMap<String, Value>,
+        context: &EvalContext,
+    ) -> EvalResult<'src, Value> {
+        let x = Self::positive_arg("x", args, context)?;
+        let y = Self::positive_arg("y", args, context)?;
+
+        let vert = builder::vertex(Point3::new(-0.5 * x, -0.5 * y, 0.));
+        let edge = builder::tsweep(&vert, Vector3::unit_x() * x);
+        Ok(Value::Shape2D(vec![builder::tsweep(
+            &edge,
+            Vector3::unit_y() * y,
+        )]))
+    }
+}
+
+/// Sweeps a 2D `profile` up from the XY plane by `height` into a solid.
+///
+/// The empty profile gives the empty solid. A `height` that isn't positive is
+/// an [`EvalErrorType::NumExprNotFinite`] error.
+pub(super) struct Extrude();
+
+impl BuiltInStatic for Extrude {
+    const ARGS: &[BuiltInArgDef] = &[
+        BuiltInArgDef {
+            name: "profile",
+            default: None,
+        },
+        BuiltInArgDef {
+            name: "height",
+            default: None,
+        },
+    ];
+
+    fn eval_static<'src>(
+        solids: &mut SolidSet,
+        args: &BTreeMap<String, Value>,
+        context: &EvalContext,
+    ) -> EvalResult<'src, Value> {
+        let profile = Self::shape_2d_arg("profile", args, context)?;
+        let height = Self::num_arg("height", args, context)?;
+        if !(height.is_finite() && height > 0.) {
+            return context.eval_err(EvalErrorType::NumExprNotFinite {
+                location: context.location(),
+            });
+        }
+        if profile.is_empty() {
+            return Ok(Value::Solid(SolidId::Empty));
+        }
+
+        Ok(Value::Solid(solids.push(extrude(profile, height))))
+    }
+}
+
+/// A boolean operation between 2D shapes.
+#[derive(Clone, Copy)]
+pub(crate) enum SketchOp {
+    Union,
+    Difference,
+    Intersection,
+}
+
+/// Combines the profiles `lhs` and `rhs` with `op`, or `None` if truck can't.
+///
+/// truck only has booleans between solids, so each profile is extruded into
+/// a prism, the prisms are combined and the result is cut back to a profile.
+/// `rhs` is extruded further than `lhs` so their tops and bottoms aren't
+/// coplanar, which truck's booleans can't handle. Profiles with overlapping
+/// edges still give prisms with coplanar sides, which truck either fails on
+/// or panics on, so both are caught.
+pub(crate) fn combine_sketches(
+    lhs: &[Face],
+    rhs: &[Face],
+    op: SketchOp,
+    tolerance: f64,
+) -> Option<Vec<Face>> {
+    match (lhs.is_empty(), rhs.is_empty(), op) {
+        (true, _, SketchOp::Union) => return Some(rhs.to_vec()),
+        (_, true, SketchOp::Union | SketchOp::Difference) => return Some(lhs.to_vec()),
+        (true, _, _) | (_, true, SketchOp::Intersection) => return Some(Vec::new()),
+        _ => {}
+    }
+
+    panic::catch_unwind(AssertUnwindSafe(|| combine_prisms(lhs, rhs, op, tolerance)))
+        .ok()
+        .flatten()
+}
+
+fn combine_prisms(lhs: &[Face], rhs: &[Face], op: SketchOp, tolerance: f64) -> Option<Vec<Face>> {
+    let (lhs_prism, mut rhs_prism) = (prism(lhs, 1.), prism(rhs, 2.));
+    let combined = match op {
+        SketchOp::Union => return union_sketches(&lhs_prism, &rhs_prism, tolerance),
+        SketchOp::Intersection => truck_shapeops::and(&lhs_prism, &rhs_prism, tolerance)?,
+        SketchOp::Difference => {
+            rhs_prism.not();
+            truck_shapeops::and(&lhs_prism, &rhs_prism, tolerance)?
+        }
+    };
+
+    // Where `rhs` is taller the bottom of the result is all from `lhs`.
+    Some(bottom_faces(&combined, -1., tolerance))
+}
+
+/// The union of two prisms cut back to a profile.
+///
+/// The bottom of the union is partly from each prism at different heights,
+/// so a slab through the middle of both, wider than either, cuts out the
+/// combined profile as its bottom faces. The union of two profiles is never
+/// empty, so an empty result means truck got it wrong.
+fn union_sketches(lhs_prism: &Solid, rhs_prism: &Solid, tolerance: f64) -> Option<Vec<Face>> {
+    let combined = truck_shapeops::or(lhs_prism, rhs_prism, tolerance)?;
+
+    let bbox = combined
+        .triangulation(tolerance)
+        .to_polygon()
+        .bounding_box();
+    let margin = Vector3::new(1., 1., 0.);
+    let slab = box_between(
+        Point3::new(bbox.min().x, bbox.min().y, -0.5) - margin,
+        Point3::new(bbox.max().x, bbox.max().y, 0.5) + margin,
+    );
+    let cut = truck_shapeops::and(&combined, &slab, tolerance)?;
+    let faces = bottom_faces(&cut, -0.5, tolerance);
+    (!faces.is_empty()).then_some(faces)
+}
+
+/// The faces of `solid` facing down at height `z`, flipped to face up and
+/// moved to the XY plane.
+///
+/// Edges the boolean made are intersection curves, which truck can't sweep,
+/// so they're replaced by approximations. A face with an edge that can't be
+/// approximated is left out.
+fn bottom_faces(solid: &Solid, z: f64, tolerance: f64) -> Vec<Face> {
+    let down = -Vector3::unit_z();
+    solid
+        .face_iter()
+        .filter(|face| match face.oriented_surface() {
+            Surface::Plane(plane) => {
+                (plane.normal() - down).magnitude() < tolerance
+                    && (plane.origin().z - z).abs() < tolerance
+            }
+            _ => false,
+        })
+        .filter_map(|face| {
+            face.try_mapped(
+                |point| Some(*point),
+                |curve| sweepable_curve(curve, tolerance),
+                |surface| Some(surface.clone()),
+            )
+        })
+        .map(|face| builder::translated(&face.inverse(), Vector3::unit_z() * -z))
+        .collect()
+}
+
+/// The number of intervals an intersection curve is checked over to see if
+/// it's straight.
+const STRAIGHTNESS_SAMPLES: usize = 8;
+
+/// `curve` if truck can sweep it, otherwise a line if it's straight or a
+/// cubic B-spline within `tolerance` of it.
+fn sweepable_curve(curve: &Curve, tolerance: f64) -> Option<Curve> {
+    let Curve::IntersectionCurve(intersection) = curve else {
+        return Some(curve.clone());
+    };
+
+    let (t0, t1) = intersection.range_tuple();
+    let (front, back) = (intersection.subs(t0), intersection.subs(t1));
+    let along = (back - front).normalize();
+    let straight = (1..STRAIGHTNESS_SAMPLES).all(|i| {
+        let t = t0 + (t1 - t0) * i as f64 / STRAIGHTNESS_SAMPLES as f64;
+        let offset = intersection.subs(t) - front;
+        (offset - along * offset.dot(along)).magnitude() < tolerance
+    });
+    if straight {
+        return Some(Curve::Line(Line(front, back)));
+    }
+
+    BSplineCurve::cubic_approximation(intersection, (t0, t1), tolerance, tolerance, 10)
+        .map(Curve::BSplineCurve)
+}
+
+/// The faces of a profile extruded up by `height`.
+///
+/// The faces of a profile don't overlap, so each prism is a separate shell of
+/// the same solid.
+fn extrude(faces: &[Face], height: f64) -> Solid {
+    let shells = faces
+        .iter()
+        .flat_map(|face| builder::tsweep(face, Vector3::unit_z() * height).into_boundaries());
+    Solid::new(shells.collect())
+}
+
+/// The faces of a profile extruded from `-half_height` to `half_height`.
+fn prism(faces: &[Face], half_height: f64) -> Solid {
+    let prism = extrude(faces, 2. * half_height);
+    builder::translated(&prism, Vector3::unit_z() * -half_height)
+}
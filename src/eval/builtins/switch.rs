@@ -0,0 +1,77 @@
+use crate::{ast::*, error::EvalResult, EvalErrorType, SpannedFuncCallExpr, Value};
+
+use super::{special_form_exprs, EvalCache, EvalContext, Scope};
+
+/// The name of the `Switch` special form.
+///
+/// `Switch(key, cases, default)` evaluates to the entry of the dictionary
+/// literal `cases` with the string `key`, or to `default` if there's no such
+/// entry. A bool `key` picks the `"true"` or `"false"` entry. Only the chosen
+/// expression is evaluated, so the geometry of cases that aren't picked is
+/// never built.
+///
+/// `cases` must be written as a dictionary literal like
+/// `{"small": Cube(1), "large": Cube(2)}`. `default` is optional and without it
+/// a key with no case is a [`EvalErrorType::NoMatchingCase`] error.
+pub(super) const SWITCH: &str = "Switch";
+
+const SWITCH_ARGS: &[&str] = &["key", "cases", "default"];
+
+impl<'set, 'src> EvalCache<'set, 'src> {
+    pub(super) fn eval_switch_call(
+        &mut self,
+        call_expr: &SpannedFuncCallExpr<'src>,
+        scope: &Scope,
+        context: &EvalContext,
+    ) -> EvalResult<'src, Value> {
+        let case = self.select_switch_case(call_expr, scope, context)?;
+        self.eval_expr(case, scope, context)
+    }
+
+    /// Evaluates the key of a `Switch` and returns the expression it picks
+    /// without evaluating it.
+    pub(super) fn select_switch_case<'e>(
+        &mut self,
+        call_expr: &'e SpannedFuncCallExpr<'src>,
+        scope: &Scope,
+        context: &EvalContext,
+    ) -> EvalResult<'src, &'e SpannedExpr<'src>> {
+        let exprs = special_form_exprs(call_expr, SWITCH_ARGS, context)?;
+        let arg = |name: &str| {
+            exprs.get(name).copied().ok_or_else(|| {
+                context.error(EvalErrorType::NoSuppliedOrDefaultArg { name: name.into() })
+            })
+        };
+
+        let key = match self.eval_expr(arg("key")?, scope, context)? {
+            Value::String(key) => key,
+            Value::Bool(key) => key.to_string(),
+            val => {
+                return context.eval_err(EvalErrorType::ArgWrongType {
+                    name: "key".into(),
+                    expected: Value::STRING_TYPE_NAME,
+                    got: val.type_name(),
+                })
+            }
+        };
+
+        let cases = arg("cases")?;
+        let Expr::Dict(dict) = &cases.inner else {
+            // Evaluated only to report what was passed instead.
+            let val = self.eval_expr(cases, scope, context)?;
+            return context.eval_err(EvalErrorType::ArgWrongType {
+                name: "cases".into(),
+                expected: "dictionary",
+                got: val.type_name(),
+            });
+        };
+
+        if let Some(entry) = dict.entries.get(key.as_str()) {
+            Ok(&entry.expr)
+        } else if let Some(default) = exprs.get("default") {
+            Ok(default)
+        } else {
+            context.eval_err(EvalErrorType::NoMatchingCase { key })
+        }
+    }
+}
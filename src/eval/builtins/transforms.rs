@@ -0,0 +1,897 @@
+use std::collections::{BTreeMap, HashMap};
+
+use truck_meshalgo::{
+    analyzers::WithPointCloud,
+    prelude::{PolygonMesh, TOLERANCE},
+};
+use truck_modeling::{
+    cgmath::{AbsDiffEq, Deg},
+    EuclideanSpace, InnerSpace, Matrix3, Matrix4, Point3, Solid, Surface, Transform, Vector3,
+};
+
+use crate::{EvalErrorType, SolidId, SolidSet, Value};
+
+use super::{
+    features::union_copies,
+    import_mesh::solid_from_polygons,
+    measure::{bounding_box, centroid, mesh},
+    shapes::{centered_box, convex_hull},
+    BuiltInArgDef, BuiltInStatic, EvalContext, EvalResult,
+};
+
+/// Returns the matrix reflecting across the plane through the origin with
+/// normal `normal`, or `None` if `normal` is too short to define a plane.
+fn mirror_matrix(normal: Vector3, tolerance: f64) -> Option<Matrix4> {
+    if normal.magnitude().abs_diff_eq(&0., tolerance) {
+        return None;
+    }
+
+    // Householder reflection: I - 2nn^T.
+    let n = normal.normalize();
+    let reflection = Matrix3::from_cols(
+        Vector3::unit_x() - 2. * n.x * n,
+        Vector3::unit_y() - 2. * n.y * n,
+        Vector3::unit_z() - 2. * n.z * n,
+    );
+
+    Some(reflection.into())
+}
+
+/// Returns the matrix scaling uniformly by `factor` about `pivot`.
+///
+/// This translates `pivot` to the origin, scales, then translates back, so
+/// `pivot` is the one point left where it was.
+pub(super) fn scale_about_matrix(factor: f64, pivot: Point3) -> Matrix4 {
+    let offset = pivot.to_vec();
+    Matrix4::from_translation(offset)
+        * Matrix4::from_scale(factor)
+        * Matrix4::from_translation(-offset)
+}
+
+/// Returns the matrix rotating by `angle` degrees about the axis through
+/// `pivot` with direction `axis`, or `None` if `axis` is too short to define
+/// a direction.
+///
+/// Like [`scale_about_matrix`] this translates `pivot` to the origin, rotates,
+/// then translates back. Rotation follows the right-hand rule about `axis`.
+pub(super) fn rotate_about_matrix(
+    axis: Vector3,
+    angle: f64,
+    pivot: Point3,
+    tolerance: f64,
+) -> Option<Matrix4> {
+    if axis.magnitude().abs_diff_eq(&0., tolerance) {
+        return None;
+    }
+
+    let offset = pivot.to_vec();
+    Some(
+        Matrix4::from_translation(offset)
+            * Matrix4::from_axis_angle(axis.normalize(), Deg(angle))
+            * Matrix4::from_translation(-offset),
+    )
+}
+
+/// Returns true if `mat` maps `mesh`, the triangulation of a solid, onto
+/// itself to within `tolerance`.
+///
+/// Every vertex of the mapped mesh has to be near the surface of the original.
+/// The mesh follows curved faces closely, so a face that bulges to one side
+/// of a mirror plane is caught even if its vertices are mirrored onto each
+/// other.
+fn maps_onto_itself(mesh: &PolygonMesh, mat: Matrix4, tolerance: f64) -> bool {
+    let mapped: Vec<_> = mesh
+        .positions()
+        .iter()
+        .map(|point| mat.transform_point(*point))
+        .collect();
+
+    // Both meshes are only within the tolerance of the faces they follow.
+    mesh.neighborhood_include(&mapped, 2. * tolerance.max(TOLERANCE))
+}
+
+/// Moves a solid by (`x`, `y`, `z`), where any left out are zero.
+///
+/// The offset can instead be a vector passed as `offset`, as in
+/// `Translate(solid, offset=Vec3(1, 2, 3))`, but not along with any of `x`, `y`
+/// or `z`.
+pub(super) struct Translate();
+
+impl BuiltInStatic for Translate {
+    const ARGS: &[BuiltInArgDef] = &[
+        BuiltInArgDef {
+            name: "solid",
+            default: None,
+        },
+        BuiltInArgDef {
+            name: "x",
+            default: None,
+        },
+        BuiltInArgDef {
+            name: "y",
+            default: None,
+        },
+        BuiltInArgDef {
+            name: "z",
+            default: None,
+        },
+        BuiltInArgDef {
+            name: "offset",
+            default: None,
+        },
+    ];
+    const OPTIONAL_ARGS: &[&str] = &["x", "y", "z", "offset"];
+
+    fn eval_static<'src>(
+        solids: &mut SolidSet,
+        args: &BTreeMap<String, Value>,
+        context: &EvalContext,
+    ) -> EvalResult<'src, Value> {
+        let solid = Self::solid_arg("solid", args, context)?;
+        let offset = Self::vector_or_components_arg("offset", ["x", "y", "z"], args, context)?;
+
+        let id = solids.transform(&solid, Matrix4::from_translation(offset))?;
+        Ok(Value::Solid(id))
+    }
+}
+
+/// Reflects a solid across the plane through the origin with normal
+/// (`normal_x`, `normal_y`, `normal_z`).
+pub(super) struct Mirror();
+
+impl BuiltInStatic for Mirror {
+    const ARGS: &[BuiltInArgDef] = &[
+        BuiltInArgDef {
+            name: "solid",
+            default: None,
+        },
+        BuiltInArgDef {
+            name: "normal_x",
+            default: None,
+        },
+        BuiltInArgDef {
+            name: "normal_y",
+            default: None,
+        },
+        BuiltInArgDef {
+            name: "normal_z",
+            default: None,
+        },
+    ];
+
+    fn eval_static<'src>(
+        solids: &mut SolidSet,
+        args: &BTreeMap<String, Value>,
+        context: &EvalContext,
+    ) -> EvalResult<'src, Value> {
+        let solid = Self::solid_arg("solid", args, context)?;
+        let normal = Vector3::new(
+            Self::num_arg("normal_x", args, context)?,
+            Self::num_arg("normal_y", args, context)?,
+            Self::num_arg("normal_z", args, context)?,
+        );
+
+        let Some(mat) = mirror_matrix(normal, solids.tolerance) else {
+            return context.eval_err(EvalErrorType::NumExprNotFinite {
+                location: context.location(),
+            });
+        };
+
+        // The transform turns the reflected copy right side out again.
+        Ok(Value::Solid(solids.transform(&solid, mat)?))
+    }
+}
+
+/// Unions a solid with its reflection across the plane through the origin
+/// with normal (`nx`, `ny`, `nz`).
+///
+/// A solid on one side of the plane, even one modelled up to a cut face on
+/// it, gives a solid with the boundaries of both halves. A solid that's
+/// already symmetric about the plane is returned as it is, and any other
+/// solid straddling the plane fails where truck can't union it with its
+/// mirror.
+pub(super) struct Symmetrize();
+
+impl BuiltInStatic for Symmetrize {
+    const ARGS: &[BuiltInArgDef] = &[
+        BuiltInArgDef {
+            name: "solid",
+            default: None,
+        },
+        BuiltInArgDef {
+            name: "nx",
+            default: None,
+        },
+        BuiltInArgDef {
+            name: "ny",
+            default: None,
+        },
+        BuiltInArgDef {
+            name: "nz",
+            default: None,
+        },
+    ];
+
+    fn eval_static<'src>(
+        solids: &mut SolidSet,
+        args: &BTreeMap<String, Value>,
+        context: &EvalContext,
+    ) -> EvalResult<'src, Value> {
+        let solid = Self::solid_arg("solid", args, context)?;
+        let normal = Vector3::new(
+            Self::num_arg("nx", args, context)?,
+            Self::num_arg("ny", args, context)?,
+            Self::num_arg("nz", args, context)?,
+        );
+
+        let Some(mat) = mirror_matrix(normal, solids.tolerance) else {
+            return context.eval_err(EvalErrorType::NumExprNotFinite {
+                location: context.location(),
+            });
+        };
+
+        // The empty and universal solids are their own mirrors.
+        let SolidId::Regular(_) = solid else {
+            return Ok(Value::Solid(solid));
+        };
+
+        let mesh = mesh(solids, &solid, context)?;
+        let tolerance = solids.tolerance;
+        let (nearest, farthest) = mesh
+            .positions()
+            .iter()
+            .map(|point| normal.normalize().dot(point.to_vec()))
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), offset| {
+                (min.min(offset), max.max(offset))
+            });
+
+        if nearest < -tolerance && farthest > tolerance && maps_onto_itself(&mesh, mat, tolerance) {
+            return Ok(Value::Solid(solid));
+        }
+
+        let mirrored = solids.transform(&solid, mat)?;
+        if nearest >= -tolerance || farthest <= tolerance {
+            // The halves can only meet on the plane, so their boundaries don't
+            // cross and together they bound the union. truck can't union
+            // solids that touch on a face.
+            let mut shells = solids.try_get(&solid)?.boundaries().clone();
+            shells.extend(solids.try_get(&mirrored)?.boundaries().iter().cloned());
+            return Ok(Value::Solid(solids.push(Solid::new(shells))));
+        }
+
+        match solids.union(&solid, &mirrored)? {
+            SolidId::Empty => {
+                context.eval_err(EvalErrorType::GeometryOperationFailed { op: "Symmetrize" })
+            }
+            id => Ok(Value::Solid(id)),
+        }
+    }
+}
+
+/// The union of copies of a solid translated to each of a list of points.
+///
+/// Copies are unioned as the copies of a pattern are, so copies that truck
+/// can't union fail rather than giving the empty solid.
+pub(super) struct PlaceAt();
+
+impl BuiltInStatic for PlaceAt {
+    const ARGS: &[BuiltInArgDef] = &[
+        BuiltInArgDef {
+            name: "solid",
+            default: None,
+        },
+        BuiltInArgDef {
+            name: "points",
+            default: None,
+        },
+    ];
+
+    fn eval_static<'src>(
+        solids: &mut SolidSet,
+        args: &BTreeMap<String, Value>,
+        context: &EvalContext,
+    ) -> EvalResult<'src, Value> {
+        let solid = Self::solid_arg("solid", args, context)?;
+        let points = Self::point_list_arg("points", args, context)?;
+
+        let mut copies = Vec::with_capacity(points.len());
+        for point in points {
+            copies.push(solids.transform(&solid, Matrix4::from_translation(point.to_vec()))?);
+        }
+
+        let placed = union_copies(solids, copies, "PlaceAt", context)?;
+        Ok(Value::Solid(placed))
+    }
+}
+
+/// Crops a solid to a box centered on the origin with extents `x`, `y` and
+/// `z`, which must all be positive.
+pub(super) struct ClipBox();
+
+impl BuiltInStatic for ClipBox {
+    const ARGS: &[BuiltInArgDef] = &[
+        BuiltInArgDef {
+            name: "solid",
+            default: None,
+        },
+        BuiltInArgDef {
+            name: "x",
+            default: None,
+        },
+        BuiltInArgDef {
+            name: "y",
+            default: None,
+        },
+        BuiltInArgDef {
+            name: "z",
+            default: None,
+        },
+    ];
+
+    fn eval_static<'src>(
+        solids: &mut SolidSet,
+        args: &BTreeMap<String, Value>,
+        context: &EvalContext,
+    ) -> EvalResult<'src, Value> {
+        let solid = Self::solid_arg("solid", args, context)?;
+        let size = Vector3::new(
+            Self::positive_arg("x", args, context)?,
+            Self::positive_arg("y", args, context)?,
+            Self::positive_arg("z", args, context)?,
+        );
+
+        // The box is never pushed to the set so it's dropped right after.
+        let id = solids.intersection_with(&solid, &centered_box(size))?;
+        Ok(Value::Solid(id))
+    }
+}
+
+/// Translates a solid vertically so its lowest point sits on the XY plane.
+pub(super) struct DropToFloor();
+
+impl BuiltInStatic for DropToFloor {
+    const ARGS: &[BuiltInArgDef] = &[BuiltInArgDef {
+        name: "solid",
+        default: None,
+    }];
+
+    fn eval_static<'src>(
+        solids: &mut SolidSet,
+        args: &BTreeMap<String, Value>,
+        context: &EvalContext,
+    ) -> EvalResult<'src, Value> {
+        let solid = Self::solid_arg("solid", args, context)?;
+        let min_z = bounding_box(solids, &solid, context)?.min().z;
+
+        let offset = Vector3::new(0., 0., -min_z);
+        let id = solids.transform(&solid, Matrix4::from_translation(offset))?;
+        Ok(Value::Solid(id))
+    }
+}
+
+/// Hollows out a solid, leaving walls `thickness` thick.
+///
+/// The cavity is the solid with each of its faces moved inward by
+/// `thickness`, and it's subtracted from the solid. A `thickness` that isn't
+/// positive or that leaves no room for a cavity is reported as
+/// [`EvalErrorType::NumExprNotFinite`].
+///
+/// truck can't offset general solids, so for now only convex solids with
+/// planar faces can be shelled. Others are an [`EvalErrorType::InvalidSolid`]
+/// error.
+pub(super) struct Shell();
+
+impl BuiltInStatic for Shell {
+    const ARGS: &[BuiltInArgDef] = &[
+        BuiltInArgDef {
+            name: "solid",
+            default: None,
+        },
+        BuiltInArgDef {
+            name: "thickness",
+            default: None,
+        },
+    ];
+
+    fn eval_static<'src>(
+        solids: &mut SolidSet,
+        args: &BTreeMap<String, Value>,
+        context: &EvalContext,
+    ) -> EvalResult<'src, Value> {
+        let solid = Self::solid_arg("solid", args, context)?;
+        let thickness = Self::num_arg("thickness", args, context)?;
+        if !(thickness.is_finite() && thickness > 0.) {
+            return context.eval_err(EvalErrorType::NumExprNotFinite {
+                location: context.location(),
+            });
+        }
+        if solid == SolidId::Empty {
+            return Ok(Value::Solid(SolidId::Empty));
+        }
+
+        let cavity = match shell_cavity(solids.try_get(&solid)?, thickness, solids.tolerance) {
+            Ok(Some(cavity)) => cavity,
+            Ok(None) => {
+                return context.eval_err(EvalErrorType::NumExprNotFinite {
+                    location: context.location(),
+                })
+            }
+            Err(reason) => return context.eval_err(EvalErrorType::InvalidSolid { reason }),
+        };
+
+        let cavity = solids.push(cavity);
+        Ok(Value::Solid(solids.difference(&solid, &cavity)?))
+    }
+}
+
+/// The outward normal and offset from the origin of the plane of each face of
+/// `solid`, or an error if it has curved faces or isn't convex.
+///
+/// `op` describes what was being done with the solid for the error message.
+pub(super) fn convex_face_planes(
+    solid: &Solid,
+    tolerance: f64,
+    op: &str,
+) -> Result<Vec<(Vector3, f64)>, String> {
+    let mut planes = Vec::new();
+    for face in solid.face_iter() {
+        let Surface::Plane(plane) = face.oriented_surface() else {
+            return Err(format!("only solids with planar faces can be {op}"));
+        };
+        let normal = plane.normal();
+        planes.push((normal, normal.dot(plane.origin().to_vec())));
+    }
+
+    if solid
+        .vertex_iter()
+        .any(|vertex| in_front_of_any(&planes, vertex.point(), tolerance))
+    {
+        return Err(format!("only convex solids can be {op}"));
+    }
+
+    Ok(planes)
+}
+
+/// Returns true if `point` is more than `tolerance` in front of any of
+/// `planes`.
+fn in_front_of_any(planes: &[(Vector3, f64)], point: Point3, tolerance: f64) -> bool {
+    planes
+        .iter()
+        .any(|(normal, offset)| normal.dot(point.to_vec()) - offset > tolerance)
+}
+
+/// The intersection of the half-spaces behind `planes`, or `None` if it's
+/// empty or flat.
+///
+/// This is the hull of the points where three of the planes meet without being
+/// in front of any other.
+pub(super) fn half_space_intersection(
+    planes: &[(Vector3, f64)],
+    tolerance: f64,
+) -> Result<Option<Solid>, String> {
+    let mut corners = Vec::new();
+    for (i, (n0, d0)) in planes.iter().enumerate() {
+        for (j, (n1, d1)) in planes.iter().enumerate().skip(i + 1) {
+            for (n2, d2) in planes.iter().skip(j + 1) {
+                let det = n0.dot(n1.cross(*n2));
+                if det.abs() < tolerance {
+                    continue;
+                }
+
+                let corner =
+                    (n1.cross(*n2) * *d0 + n2.cross(*n0) * *d1 + n0.cross(*n1) * *d2) / det;
+                let corner = Point3::from_vec(corner);
+                if !in_front_of_any(planes, corner, tolerance) {
+                    corners.push(corner);
+                }
+            }
+        }
+    }
+
+    if corners.is_empty() {
+        return Ok(None);
+    }
+    let Some(faces) = convex_hull(&corners, tolerance) else {
+        return Ok(None);
+    };
+
+    solid_from_polygons(&corners, &faces).map(Some)
+}
+
+/// The cavity left by shelling `solid` with walls `thickness` thick, or
+/// `None` if the walls would fill the solid.
+///
+/// The cavity is the intersection of the half-spaces behind each face moved
+/// inward by `thickness`.
+fn shell_cavity(solid: &Solid, thickness: f64, tolerance: f64) -> Result<Option<Solid>, String> {
+    let planes: Vec<_> = convex_face_planes(solid, tolerance, "shelled")?
+        .into_iter()
+        .map(|(normal, offset)| (normal, offset - thickness))
+        .collect();
+
+    half_space_intersection(&planes, tolerance)
+}
+
+/// Bevels every edge of a solid, cutting `distance` back along both of the
+/// faces that meet at it.
+///
+/// Like `Shell`, only convex solids with planar faces can be chamfered. The
+/// chamfered solid is the solid cut by a plane across each edge, so a
+/// `distance` that's large next to the solid's faces cuts them away entirely.
+pub(super) struct Chamfer();
+
+impl BuiltInStatic for Chamfer {
+    const ARGS: &[BuiltInArgDef] = &[
+        BuiltInArgDef {
+            name: "solid",
+            default: None,
+        },
+        BuiltInArgDef {
+            name: "distance",
+            default: None,
+        },
+    ];
+
+    fn eval_static<'src>(
+        solids: &mut SolidSet,
+        args: &BTreeMap<String, Value>,
+        context: &EvalContext,
+    ) -> EvalResult<'src, Value> {
+        let solid = Self::solid_arg("solid", args, context)?;
+        let distance = Self::positive_arg("distance", args, context)?;
+        if solid == SolidId::Empty {
+            return Ok(Value::Solid(SolidId::Empty));
+        }
+
+        let solid = solids.try_get(&solid)?;
+        let mut planes = convex_face_planes(solid, solids.tolerance, "chamfered")
+            .or_else(|reason| context.eval_err(EvalErrorType::InvalidSolid { reason }))?;
+        planes.extend(chamfer_planes(solid, distance, |_, _| true));
+
+        match half_space_intersection(&planes, solids.tolerance) {
+            Ok(Some(chamfered)) => Ok(Value::Solid(solids.push(chamfered))),
+            Ok(None) | Err(_) => {
+                context.eval_err(EvalErrorType::GeometryOperationFailed { op: "Chamfer" })
+            }
+        }
+    }
+}
+
+/// The plane cutting each edge of a convex solid with planar faces, as an
+/// outward normal and offset from the origin, `distance` back along both
+/// faces.
+///
+/// Only edges whose ends are accepted by `include` are cut.
+pub(super) fn chamfer_planes(
+    solid: &Solid,
+    distance: f64,
+    include: impl Fn(Point3, Point3) -> bool,
+) -> Vec<(Vector3, f64)> {
+    // Edges in the order they're found, so planes are always in the same
+    // order.
+    let mut order = Vec::new();
+    let mut sides = HashMap::new();
+    for face in solid.face_iter() {
+        let Surface::Plane(plane) = face.oriented_surface() else {
+            continue;
+        };
+        let normal = plane.normal();
+
+        for boundary in face.boundary_iters() {
+            for edge in boundary {
+                let (start, end) = (edge.front().point(), edge.back().point());
+                if !include(start, end) {
+                    continue;
+                }
+
+                // Boundaries run counter-clockwise around the outward normal,
+                // so the face lies to the left of each edge.
+                let inward = normal.cross(end - start).normalize();
+                sides
+                    .entry(edge.id())
+                    .or_insert_with(|| {
+                        order.push(edge.id());
+                        Vec::new()
+                    })
+                    .push((start, normal, inward));
+            }
+        }
+    }
+
+    order
+        .into_iter()
+        .filter_map(|id| match &sides[&id][..] {
+            // The chamfer is symmetric about the plane bisecting the faces, so
+            // its normal is halfway between theirs.
+            [(start, n0, t0), (_, n1, _)] => {
+                let normal = (n0 + n1).normalize();
+                Some((normal, normal.dot((start + t0 * distance).to_vec())))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// Translates `a` part way from its own position towards the position of `b`.
+///
+/// Positions are centroids and `t` is the fraction of the way to move, so 0
+/// leaves `a` where it is and 1 centers it on `b`. Only the position is
+/// blended; the geometry of `a` is unchanged.
+pub(super) struct BlendPosition();
+
+impl BuiltInStatic for BlendPosition {
+    const ARGS: &[BuiltInArgDef] = &[
+        BuiltInArgDef {
+            name: "a",
+            default: None,
+        },
+        BuiltInArgDef {
+            name: "b",
+            default: None,
+        },
+        BuiltInArgDef {
+            name: "t",
+            default: None,
+        },
+    ];
+
+    fn eval_static<'src>(
+        solids: &mut SolidSet,
+        args: &BTreeMap<String, Value>,
+        context: &EvalContext,
+    ) -> EvalResult<'src, Value> {
+        let a = Self::solid_arg("a", args, context)?;
+        let b = Self::solid_arg("b", args, context)?;
+        let t = Self::num_arg("t", args, context)?;
+
+        let offset = t * (centroid(solids, &b, context)? - centroid(solids, &a, context)?);
+        let id = solids.transform(&a, Matrix4::from_translation(offset))?;
+        Ok(Value::Solid(id))
+    }
+}
+
+/// Scales a solid about the origin, uniformly by `factor` and along each axis
+/// by `x`, `y` and `z`.
+///
+/// All the factors must be positive. A zero factor would collapse the solid and
+/// a negative one would mirror it, so both are reported as
+/// [`EvalErrorType::NumExprNotFinite`]. Use [`Mirror`] to flip a solid.
+pub(super) struct Scale();
+
+impl BuiltInStatic for Scale {
+    const ARGS: &[BuiltInArgDef] = &[
+        BuiltInArgDef {
+            name: "solid",
+            default: None,
+        },
+        BuiltInArgDef {
+            name: "factor",
+            default: Some(Value::Number(1.)),
+        },
+        BuiltInArgDef {
+            name: "x",
+            default: Some(Value::Number(1.)),
+        },
+        BuiltInArgDef {
+            name: "y",
+            default: Some(Value::Number(1.)),
+        },
+        BuiltInArgDef {
+            name: "z",
+            default: Some(Value::Number(1.)),
+        },
+    ];
+
+    fn eval_static<'src>(
+        solids: &mut SolidSet,
+        args: &BTreeMap<String, Value>,
+        context: &EvalContext,
+    ) -> EvalResult<'src, Value> {
+        let solid = Self::solid_arg("solid", args, context)?;
+        let mut factors = [0.; 4];
+        for (factor, name) in factors.iter_mut().zip(["factor", "x", "y", "z"]) {
+            *factor = scale_factor_arg(name, args, context)?;
+        }
+        let [factor, x, y, z] = factors;
+        let axes = Vector3::new(x, y, z) * factor;
+
+        let mat = Matrix4::from_nonuniform_scale(axes.x, axes.y, axes.z);
+        Ok(Value::Solid(solids.transform(&solid, mat)?))
+    }
+}
+
+/// Scales a solid uniformly about the point (`px`, `py`, `pz`).
+///
+/// The solid is translated so the pivot is at the origin, scaled, then
+/// translated back, so the pivot stays fixed while everything else moves
+/// towards or away from it.
+///
+/// Like those of [`Scale`], `factor` must be positive.
+pub(super) struct ScaleAbout();
+
+impl BuiltInStatic for ScaleAbout {
+    const ARGS: &[BuiltInArgDef] = &[
+        BuiltInArgDef {
+            name: "solid",
+            default: None,
+        },
+        BuiltInArgDef {
+            name: "factor",
+            default: None,
+        },
+        BuiltInArgDef {
+            name: "px",
+            default: None,
+        },
+        BuiltInArgDef {
+            name: "py",
+            default: None,
+        },
+        BuiltInArgDef {
+            name: "pz",
+            default: None,
+        },
+    ];
+
+    fn eval_static<'src>(
+        solids: &mut SolidSet,
+        args: &BTreeMap<String, Value>,
+        context: &EvalContext,
+    ) -> EvalResult<'src, Value> {
+        let solid = Self::solid_arg("solid", args, context)?;
+        let factor = scale_factor_arg("factor", args, context)?;
+        let pivot = Point3::new(
+            Self::num_arg("px", args, context)?,
+            Self::num_arg("py", args, context)?,
+            Self::num_arg("pz", args, context)?,
+        );
+
+        let id = solids.transform(&solid, scale_about_matrix(factor, pivot))?;
+        Ok(Value::Solid(id))
+    }
+}
+
+/// A scale factor of [`Scale`], [`ScaleAbout`] or `TransformedBounds`, which
+/// must be positive and finite.
+pub(super) fn scale_factor_arg<'src>(
+    name: &str,
+    args: &BTreeMap<String, Value>,
+    context: &EvalContext,
+) -> EvalResult<'src, f64> {
+    let factor = Scale::num_arg(name, args, context)?;
+    if !(factor.is_finite() && factor > 0.) {
+        return context.eval_err(EvalErrorType::NumExprNotFinite {
+            location: context.location(),
+        });
+    }
+
+    Ok(factor)
+}
+
+/// Rotates a solid by `angle` degrees about the axis through the origin with
+/// direction (`axis_x`, `axis_y`, `axis_z`), where any left out are zero.
+///
+/// The axis can instead be a vector passed as `axis`, as in
+/// `Rotate(s, angle=90, axis=Vec3(0, 0, 1))`, but not along with any of
+/// `axis_x`, `axis_y` or `axis_z`.
+pub(super) struct Rotate();
+
+impl BuiltInStatic for Rotate {
+    const ARGS: &[BuiltInArgDef] = &[
+        BuiltInArgDef {
+            name: "solid",
+            default: None,
+        },
+        BuiltInArgDef {
+            name: "axis_x",
+            default: None,
+        },
+        BuiltInArgDef {
+            name: "axis_y",
+            default: None,
+        },
+        BuiltInArgDef {
+            name: "axis_z",
+            default: None,
+        },
+        BuiltInArgDef {
+            name: "angle",
+            default: None,
+        },
+        BuiltInArgDef {
+            name: "axis",
+            default: None,
+        },
+    ];
+    const OPTIONAL_ARGS: &[&str] = &["axis_x", "axis_y", "axis_z", "axis"];
+
+    fn eval_static<'src>(
+        solids: &mut SolidSet,
+        args: &BTreeMap<String, Value>,
+        context: &EvalContext,
+    ) -> EvalResult<'src, Value> {
+        let solid = Self::solid_arg("solid", args, context)?;
+        let axis =
+            Self::vector_or_components_arg("axis", ["axis_x", "axis_y", "axis_z"], args, context)?;
+        let angle = Self::num_arg("angle", args, context)?;
+
+        let Some(mat) = rotate_about_matrix(axis, angle, Point3::origin(), solids.tolerance) else {
+            return context.eval_err(EvalErrorType::NumExprNotFinite {
+                location: context.location(),
+            });
+        };
+
+        Ok(Value::Solid(solids.transform(&solid, mat)?))
+    }
+}
+
+/// Rotates a solid by `angle` degrees about the axis through the point
+/// (`px`, `py`, `pz`) with direction (`ax`, `ay`, `az`).
+///
+/// Points on the axis, including the pivot, stay fixed. This is how a part
+/// swings about a hinge that isn't at the origin.
+pub(super) struct RotateAbout();
+
+impl BuiltInStatic for RotateAbout {
+    const ARGS: &[BuiltInArgDef] = &[
+        BuiltInArgDef {
+            name: "solid",
+            default: None,
+        },
+        BuiltInArgDef {
+            name: "px",
+            default: None,
+        },
+        BuiltInArgDef {
+            name: "py",
+            default: None,
+        },
+        BuiltInArgDef {
+            name: "pz",
+            default: None,
+        },
+        BuiltInArgDef {
+            name: "ax",
+            default: None,
+        },
+        BuiltInArgDef {
+            name: "ay",
+            default: None,
+        },
+        BuiltInArgDef {
+            name: "az",
+            default: None,
+        },
+        BuiltInArgDef {
+            name: "angle",
+            default: None,
+        },
+    ];
+
+    fn eval_static<'src>(
+        solids: &mut SolidSet,
+        args: &BTreeMap<String, Value>,
+        context: &EvalContext,
+    ) -> EvalResult<'src, Value> {
+        let solid = Self::solid_arg("solid", args, context)?;
+        let pivot = Point3::new(
+            Self::num_arg("px", args, context)?,
+            Self::num_arg("py", args, context)?,
+            Self::num_arg("pz", args, context)?,
+        );
+        let axis = Vector3::new(
+            Self::num_arg("ax", args, context)?,
+            Self::num_arg("ay", args, context)?,
+            Self::num_arg("az", args, context)?,
+        );
+        let angle = Self::num_arg("angle", args, context)?;
+
+        let Some(mat) = rotate_about_matrix(axis, angle, pivot, solids.tolerance) else {
+            return context.eval_err(EvalErrorType::NumExprNotFinite {
+                location: context.location(),
+            });
+        };
+
+        Ok(Value::Solid(solids.transform(&solid, mat)?))
+    }
+}
@@ -0,0 +1,91 @@
+use std::collections::BTreeMap;
+
+use crate::{SolidSet, Value};
+
+use super::{BuiltInArgDef, BuiltInStatic, EvalContext, EvalResult};
+
+/// The vector (`x`, `y`, `z`).
+pub(super) struct Vec3();
+
+impl BuiltInStatic for Vec3 {
+    const ARGS: &[BuiltInArgDef] = &[
+        BuiltInArgDef {
+            name: "x",
+            default: Some(Value::Number(0.)),
+        },
+        BuiltInArgDef {
+            name: "y",
+            default: Some(Value::Number(0.)),
+        },
+        BuiltInArgDef {
+            name: "z",
+            default: Some(Value::Number(0.)),
+        },
+    ];
+
+    fn eval_static<'src>(
+        _solids: &mut SolidSet,
+        args: &BTreeMap<String, Value>,
+        context: &EvalContext,
+    ) -> EvalResult<'src, Value> {
+        let x = Self::num_arg("x", args, context)?;
+        let y = Self::num_arg("y", args, context)?;
+        let z = Self::num_arg("z", args, context)?;
+
+        Ok(Value::Vector(x, y, z))
+    }
+}
+
+/// The x component of `vector`.
+pub(super) struct X();
+
+impl BuiltInStatic for X {
+    const ARGS: &[BuiltInArgDef] = &[BuiltInArgDef {
+        name: "vector",
+        default: None,
+    }];
+
+    fn eval_static<'src>(
+        _solids: &mut SolidSet,
+        args: &BTreeMap<String, Value>,
+        context: &EvalContext,
+    ) -> EvalResult<'src, Value> {
+        Ok(Value::Number(Self::vector_arg("vector", args, context)?.x))
+    }
+}
+
+/// The y component of `vector`.
+pub(super) struct Y();
+
+impl BuiltInStatic for Y {
+    const ARGS: &[BuiltInArgDef] = &[BuiltInArgDef {
+        name: "vector",
+        default: None,
+    }];
+
+    fn eval_static<'src>(
+        _solids: &mut SolidSet,
+        args: &BTreeMap<String, Value>,
+        context: &EvalContext,
+    ) -> EvalResult<'src, Value> {
+        Ok(Value::Number(Self::vector_arg("vector", args, context)?.y))
+    }
+}
+
+/// The z component of `vector`.
+pub(super) struct Z();
+
+impl BuiltInStatic for Z {
+    const ARGS: &[BuiltInArgDef] = &[BuiltInArgDef {
+        name: "vector",
+        default: None,
+    }];
+
+    fn eval_static<'src>(
+        _solids: &mut SolidSet,
+        args: &BTreeMap<String, Value>,
+        context: &EvalContext,
+    ) -> EvalResult<'src, Value> {
+        Ok(Value::Number(Self::vector_arg("vector", args, context)?.z))
+    }
+}
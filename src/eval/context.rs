@@ -1,17 +1,15 @@
-use std::fmt::Display;
+use std::{collections::BTreeMap, fmt::Display};
 
-use pest::Span;
+use crate::{error::EvalErrorType, EvalError, FQPath, SourceLocation};
 
-use crate::{error::EvalErrorType, EvalError, FQPath};
-
-use super::{EvalResult, SpannedArgDef, SpannedFuncCallExpr, SpannedFuncDef};
+use super::{EvalResult, SpannedArgDef, SpannedFuncCallExpr, SpannedFuncDef, Value};
 
 #[derive(Clone, Debug)]
 enum ContextEntryType {
     FuncCall { text: String },
     FuncDef { name: String },
     ArgDefault { func: String, arg: String },
-    BuiltIn { name: String },
+    BuiltIn { name: String, args: String },
 }
 
 impl Display for ContextEntryType {
@@ -23,35 +21,29 @@ impl Display for ContextEntryType {
                 f,
                 "evaluation of argument \"{arg}\" default of function \"{func}\""
             ),
-            ContextEntryType::BuiltIn { name } => write!(f, "built-in function \"{name}\""),
+            ContextEntryType::BuiltIn { name, args } => {
+                write!(f, "built-in function \"{name}\"")?;
+                if !args.is_empty() {
+                    write!(f, " with {args}")?;
+                }
+                Ok(())
+            }
         }
     }
 }
 
 #[derive(Clone, Debug)]
-struct ContextPos {
-    line: usize,
-    col: usize,
-    doc: FQPath,
+pub(crate) struct ContextEntry {
+    entry_type: ContextEntryType,
+    pos: Option<SourceLocation>,
 }
 
-impl ContextPos {
-    fn new(span: Span, doc: &FQPath) -> Self {
-        let (line, col) = span.start_pos().line_col();
-        ContextPos {
-            line,
-            col,
-            doc: doc.clone(),
-        }
+impl ContextEntry {
+    pub(crate) fn pos(&self) -> Option<&SourceLocation> {
+        self.pos.as_ref()
     }
 }
 
-#[derive(Clone, Debug)]
-pub(crate) struct ContextEntry {
-    entry_type: ContextEntryType,
-    pos: Option<ContextPos>,
-}
-
 impl Display for ContextEntry {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "\t in {}", self.entry_type)?;
@@ -74,6 +66,12 @@ pub(crate) enum EvalContext<'c> {
         entry: ContextEntry,
         outer: &'c EvalContext<'c>,
     },
+    // Built-in arguments are only rendered if an error is actually raised.
+    BuiltIn {
+        entry: ContextEntry,
+        args: &'c BTreeMap<String, Value>,
+        outer: &'c EvalContext<'c>,
+    },
 }
 
 impl<'c> EvalContext<'c> {
@@ -87,7 +85,7 @@ impl<'c> EvalContext<'c> {
 
         let entry = ContextEntry {
             entry_type: ContextEntryType::FuncCall { text: text.into() },
-            pos: Some(ContextPos::new(expr.span, doc)),
+            pos: Some(SourceLocation::new(expr.span, doc)),
         };
 
         Self::Node { entry, outer: self }
@@ -98,7 +96,7 @@ impl<'c> EvalContext<'c> {
             entry_type: ContextEntryType::FuncDef {
                 name: expr.name.text.into(),
             },
-            pos: Some(ContextPos::new(expr.span, doc)),
+            pos: Some(SourceLocation::new(expr.span, doc)),
         };
 
         Self::Node { entry, outer: self }
@@ -115,7 +113,7 @@ impl<'c> EvalContext<'c> {
                 func: func.name.text.into(),
                 arg: arg.name.text.into(),
             },
-            pos: Some(ContextPos::new(arg.span, doc)),
+            pos: Some(SourceLocation::new(arg.span, doc)),
         };
 
         Self::Node {
@@ -124,23 +122,44 @@ impl<'c> EvalContext<'c> {
         }
     }
 
-    pub(crate) fn push_built_in(&'c self, name: &str) -> Self {
+    pub(crate) fn push_built_in(&'c self, name: &str, args: &'c BTreeMap<String, Value>) -> Self {
         let entry = ContextEntry {
-            entry_type: ContextEntryType::BuiltIn { name: name.into() },
+            entry_type: ContextEntryType::BuiltIn {
+                name: name.into(),
+                args: String::new(),
+            },
             pos: None,
         };
 
-        Self::Node { entry, outer: self }
+        Self::BuiltIn {
+            entry,
+            args,
+            outer: self,
+        }
     }
 
     pub(crate) fn eval_err<'src, T>(&self, error_type: EvalErrorType<'src>) -> EvalResult<'src, T> {
+        Err(self.error(error_type))
+    }
+
+    pub(crate) fn error<'src>(&self, error_type: EvalErrorType<'src>) -> EvalError<'src> {
         let mut context_entries = self.to_vec_rev();
         context_entries.reverse();
 
-        Err(EvalError {
+        EvalError {
             error_type,
             context_entries,
-        })
+        }
+    }
+
+    /// The location of the innermost entry that has one.
+    pub(crate) fn location(&self) -> Option<SourceLocation> {
+        match self {
+            EvalContext::None => None,
+            EvalContext::Node { entry, outer } | EvalContext::BuiltIn { entry, outer, .. } => {
+                entry.pos.clone().or_else(|| outer.location())
+            }
+        }
     }
 
     fn to_vec_rev(&self) -> Vec<ContextEntry> {
@@ -151,6 +170,20 @@ impl<'c> EvalContext<'c> {
                 entries.push(entry.clone());
                 entries
             }
+            EvalContext::BuiltIn { entry, args, outer } => {
+                let mut entry = entry.clone();
+                if let ContextEntryType::BuiltIn { args: text, .. } = &mut entry.entry_type {
+                    *text = args
+                        .iter()
+                        .map(|(name, val)| format!("{name} = {}", val.rounded()))
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                }
+
+                let mut entries = outer.to_vec_rev();
+                entries.push(entry);
+                entries
+            }
         }
     }
 }
@@ -0,0 +1,51 @@
+use std::collections::HashMap;
+
+use typed_arena::Arena;
+
+use crate::{ast::Document, error::ParseResult, parse_document, FQPath};
+
+/// Parses documents the first time evaluation needs them.
+pub(crate) struct DocLoader<'set, 'src> {
+    load_src: &'set dyn Fn(&FQPath) -> ParseResult<'src, &'src str>,
+    arena: &'set Arena<Document<'src>>,
+
+    loaded: HashMap<FQPath, &'set Document<'src>>,
+    // Paths in the order their documents were allocated in `arena`.
+    order: Vec<FQPath>,
+}
+
+impl<'set, 'src> DocLoader<'set, 'src> {
+    /// `load_src` should return the source of a document given its path.
+    /// Parsed documents are allocated in `arena`.
+    pub(crate) fn new(
+        load_src: &'set dyn Fn(&FQPath) -> ParseResult<'src, &'src str>,
+        arena: &'set Arena<Document<'src>>,
+    ) -> Self {
+        Self {
+            load_src,
+            arena,
+            loaded: HashMap::new(),
+            order: Vec::new(),
+        }
+    }
+
+    pub(crate) fn load(&mut self, path: &FQPath) -> ParseResult<'src, &'set Document<'src>> {
+        if let Some(doc) = self.loaded.get(path) {
+            return Ok(doc);
+        }
+
+        let doc = parse_document((self.load_src)(path)?)?;
+        let arena = self.arena;
+        let doc = &*arena.alloc(doc);
+
+        self.loaded.insert(path.clone(), doc);
+        self.order.push(path.clone());
+
+        Ok(doc)
+    }
+
+    /// Returns the paths of loaded documents in the order they were allocated.
+    pub(crate) fn into_loaded_paths(self) -> Vec<FQPath> {
+        self.order
+    }
+}
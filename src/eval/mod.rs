@@ -1,4 +1,6 @@
 mod builtins;
+use builtins::{combine_sketches, SketchOp};
+pub use builtins::{BuiltInArgDef, BuiltInRegistry, CustomBuiltIn};
 
 mod context;
 pub(crate) use context::{ContextEntry, EvalContext};
@@ -9,30 +11,200 @@ pub use value::Value;
 mod scope;
 pub(crate) use scope::Scope;
 
+mod validate;
+pub(crate) use validate::validate_names;
+
+mod loader;
+pub(crate) use loader::DocLoader;
+
 use std::collections::{BTreeMap, HashMap, HashSet};
 
+use pest::Span;
+
 use crate::{
     ast::*,
     error::{EvalErrorType, EvalResult},
-    DocSet, FQPath, SolidSet,
+    DocSet, FQPath, OpDescriptor, SolidOp, SolidSet, SourceLocation,
 };
 
+/// Counts of how often evaluation reused a cached value.
+///
+/// A hit is a function call, argument default or built-in call whose value
+/// was already cached from an identical earlier call. A miss had to be
+/// evaluated. Many misses for calls expected to repeat suggest an argument
+/// that differs between the calls, such as a computed number.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct CacheStats {
+    pub hits: usize,
+    pub misses: usize,
+    /// The number of solids created, including intermediate ones.
+    pub solids_created: usize,
+}
+
+/// The values an [`EvalCache`] cached and the solids they refer to, kept so a
+/// later evaluation can reuse the values of documents that haven't changed.
+#[cfg(feature = "watch")]
+#[derive(Default)]
+pub(crate) struct CachedValues {
+    cache: HashMap<Scope, Value>,
+    solids: SolidSet,
+}
+
+#[cfg(feature = "watch")]
+impl CachedValues {
+    /// Keeps only the values from documents that are `fresh`, along with those
+    /// of built-in calls, which only depend on their arguments.
+    pub(crate) fn retain_docs(&mut self, fresh: impl Fn(&FQPath) -> bool) {
+        self.cache.retain(|scope, _| scope.doc().is_none_or(&fresh));
+    }
+}
+
 pub(crate) struct EvalCache<'set, 'src> {
     docs: &'set DocSet<'src>,
+    loader: Option<DocLoader<'set, 'src>>,
     evaluating: HashSet<Scope>,
 
     cache: HashMap<Scope, Value>,
     solids: SolidSet,
+    stats: CacheStats,
+
+    // Named parameters supplied by the host, read with the `Param` built-in.
+    params: HashMap<String, Value>,
+    // Built-ins supplied by the host.
+    built_ins: Option<&'set BuiltInRegistry>,
 }
 
 impl<'set, 'src> EvalCache<'set, 'src> {
     pub(crate) fn new(docs: &'set DocSet<'src>) -> Self {
         Self {
             docs,
+            loader: None,
             evaluating: HashSet::new(),
             cache: HashMap::new(),
             solids: SolidSet::default(),
+            stats: CacheStats::default(),
+            params: HashMap::new(),
+            built_ins: None,
+        }
+    }
+
+    /// Sets the parameters that `Param` looks names up in.
+    pub(crate) fn with_params(self, params: HashMap<String, Value>) -> Self {
+        Self { params, ..self }
+    }
+
+    /// Sets the host's built-ins, which are found before funcad's own.
+    pub(crate) fn with_built_ins(self, built_ins: &'set BuiltInRegistry) -> Self {
+        Self {
+            built_ins: Some(built_ins),
+            ..self
+        }
+    }
+
+    /// Sets the tolerance geometry operations are performed to.
+    pub(crate) fn with_tolerance(mut self, tolerance: f64) -> Self {
+        self.solids.tolerance = tolerance;
+        self
+    }
+
+    /// Starts from the values and solids of an earlier evaluation.
+    ///
+    /// Warnings recorded by the earlier evaluation are dropped.
+    #[cfg(feature = "watch")]
+    pub(crate) fn with_cached(self, cached: CachedValues) -> Self {
+        let mut solids = cached.solids;
+        solids.clear_warnings();
+        Self {
+            cache: cached.cache,
+            solids,
+            ..self
+        }
+    }
+
+    /// Like [`EvalCache::new`] but documents not in `docs` are parsed by
+    /// `loader` when first needed.
+    pub(crate) fn with_loader(docs: &'set DocSet<'src>, loader: DocLoader<'set, 'src>) -> Self {
+        Self {
+            loader: Some(loader),
+            ..Self::new(docs)
+        }
+    }
+
+    /// The solids created so far by evaluation.
+    #[cfg(any(feature = "json", feature = "watch"))]
+    pub(crate) fn solids(&self) -> &SolidSet {
+        &self.solids
+    }
+
+    /// Cache hits and misses so far.
+    pub(crate) fn stats(&self) -> CacheStats {
+        CacheStats {
+            solids_created: self.solids.len(),
+            ..self.stats
+        }
+    }
+
+    /// Consumes the cache, keeping only the solids created by evaluation.
+    pub(crate) fn into_solids(self) -> SolidSet {
+        self.solids
+    }
+
+    /// Consumes the cache, keeping its values and solids for a later
+    /// evaluation.
+    #[cfg(feature = "watch")]
+    pub(crate) fn into_cached(self) -> CachedValues {
+        CachedValues {
+            cache: self.cache,
+            solids: self.solids,
+        }
+    }
+
+    /// Returns the paths of documents parsed during evaluation in the order
+    /// they were loaded.
+    pub(crate) fn into_loaded_paths(self) -> Vec<FQPath> {
+        self.loader
+            .map(DocLoader::into_loaded_paths)
+            .unwrap_or_default()
+    }
+
+    /// Returns the document at `path`, parsing it first if there's a loader.
+    fn doc(
+        &mut self,
+        path: &FQPath,
+        context: &EvalContext,
+    ) -> EvalResult<'src, &'set Document<'src>> {
+        let docs = self.docs;
+        if let Some(doc) = docs.get(path) {
+            return Ok(doc);
         }
+
+        let Some(loader) = &mut self.loader else {
+            return context.eval_err(EvalErrorType::DocNotFound { path: path.clone() });
+        };
+
+        loader
+            .load(path)
+            .map_err(|e| context.error(EvalErrorType::Parse(Box::new(e))))
+    }
+
+    /// Evaluates an expression that isn't part of any function in the global
+    /// scope of the document at `doc_path`.
+    pub(crate) fn eval_expr_in_doc(
+        &mut self,
+        expr: &SpannedExpr<'src>,
+        doc_path: &FQPath,
+        context: &EvalContext,
+    ) -> EvalResult<'src, Value> {
+        // The scope isn't cached, it only provides the document to resolve
+        // names in and no arguments.
+        let scope = Scope::Inline {
+            doc_path: doc_path.clone(),
+            start: expr.span.start(),
+            end: expr.span.end(),
+            args: BTreeMap::new(),
+        };
+
+        self.eval_expr(expr, &scope, context)
     }
 
     fn eval_expr(
@@ -43,6 +215,7 @@ impl<'set, 'src> EvalCache<'set, 'src> {
     ) -> EvalResult<'src, Value> {
         match &expr.inner {
             Expr::Number(Number { val }) => Ok(Value::Number(*val)),
+            Expr::Str(StringLit { val }) => Ok(Value::String((*val).into())),
             Expr::Unary(unary) => self.eval_unary_expr(&unary.spanned(&expr.span), scope, context),
             Expr::Binary(binary) => {
                 self.eval_binary_expr(&binary.spanned(&expr.span), scope, context)
@@ -50,7 +223,59 @@ impl<'set, 'src> EvalCache<'set, 'src> {
             Expr::FuncCall(call) => {
                 self.eval_func_call_expr(&call.spanned(&expr.span), scope, context)
             }
+            Expr::List(list) => {
+                let items: EvalResult<Vec<_>> = list
+                    .items
+                    .iter()
+                    .map(|item| self.eval_expr(item, scope, context))
+                    .collect();
+                Ok(Value::List(items?))
+            }
+            Expr::Dict(_) => context.eval_err(EvalErrorType::UnexpectedDict),
+            Expr::Let(let_expr) => {
+                self.eval_let_expr(&let_expr.spanned(&expr.span), scope, context)
+            }
+        }
+    }
+
+    /// Evaluates the body of a let in the scope given by
+    /// [`EvalCache::eval_let_bindings`].
+    fn eval_let_expr(
+        &mut self,
+        expr: &SpannedLetExpr<'src>,
+        scope: &Scope,
+        context: &EvalContext,
+    ) -> EvalResult<'src, Value> {
+        let inner = self.eval_let_bindings(expr, scope, context)?;
+        self.eval_expr(&expr.body, &inner, context)
+    }
+
+    /// Evaluates the bindings of a let and returns the scope to evaluate its
+    /// body in: a [`Scope::Inline`] whose arguments are those of `scope` plus
+    /// the bindings, so anything cached within it is keyed on the values of
+    /// the bindings too.
+    fn eval_let_bindings(
+        &mut self,
+        expr: &SpannedLetExpr<'src>,
+        scope: &Scope,
+        context: &EvalContext,
+    ) -> EvalResult<'src, Scope> {
+        let doc_path = scope.doc().expect("Wrong kind of scope for let evaluation");
+
+        let mut args = scope.args().clone();
+        let mut inner = scope.clone();
+        for binding in &expr.bindings {
+            let val = self.eval_expr(&binding.expr, &inner, context)?;
+            args.insert(binding.name.text.into(), val);
+            inner = Scope::Inline {
+                doc_path: doc_path.clone(),
+                start: expr.span.start(),
+                end: expr.span.end(),
+                args: args.clone(),
+            };
         }
+
+        Ok(inner)
     }
 
     fn eval_unary_expr(
@@ -62,7 +287,17 @@ impl<'set, 'src> EvalCache<'set, 'src> {
         match expr.op {
             UnaryOp::Neg => match self.eval_expr(&expr.unit, scope, context)? {
                 Value::Number(number) => Ok(Value::Number(-number)),
-                Value::Solid(ref solid) => Ok(Value::Solid(self.solids.negate(solid)?)),
+                Value::Solid(ref solid) => {
+                    let start = self.solids.len();
+                    let negated = self.solids.negate(solid)?;
+                    self.record_solid_op(start, SolidOp::Negate, expr.span, scope);
+                    Ok(Value::Solid(negated))
+                }
+                val => context.eval_err(EvalErrorType::UnaryOpWrongType {
+                    op: expr.op.op_name(),
+                    operand_type: val.type_name(),
+                    location: scope.doc().map(|doc| SourceLocation::new(expr.span, doc)),
+                }),
             },
         }
     }
@@ -75,36 +310,142 @@ impl<'set, 'src> EvalCache<'set, 'src> {
     ) -> EvalResult<'src, Value> {
         let lhsv = self.eval_expr(&expr.lhs, scope, context)?;
         let rhsv = self.eval_expr(&expr.rhs, scope, context)?;
+        let start = self.solids.len();
+        let location = || {
+            scope
+                .doc()
+                .map(|doc| SourceLocation::new(expr.op.span, doc))
+        };
 
         use {BinaryOp::*, Value::*};
-        let val = match (lhsv, expr.op, rhsv) {
+        let val = match (lhsv, *expr.op, rhsv) {
             (Number(lhs), Add, Number(rhs)) => Number(lhs + rhs),
             (Number(lhs), Sub, Number(rhs)) => Number(lhs - rhs),
             (Number(lhs), Mul, Number(rhs)) => Number(lhs * rhs),
+            (Number(_), Div, Number(0.)) => {
+                return context.eval_err(EvalErrorType::DivisionByZero {
+                    location: location(),
+                })
+            }
             (Number(lhs), Div, Number(rhs)) => Number(lhs / rhs),
+            (Number(lhs), Pow, Number(rhs)) => Number(lhs.powf(rhs)),
+
+            (Number(lhs), Lt, Number(rhs)) => Bool(lhs < rhs),
+            (Number(lhs), Le, Number(rhs)) => Bool(lhs <= rhs),
+            (Number(lhs), Gt, Number(rhs)) => Bool(lhs > rhs),
+            (Number(lhs), Ge, Number(rhs)) => Bool(lhs >= rhs),
+            // Solids are equal only if they're the same solid, not if they
+            // have the same shape.
+            (ref lhs, Eq, ref rhs) if lhs.type_name() == rhs.type_name() => Bool(lhs == rhs),
+            (ref lhs, Ne, ref rhs) if lhs.type_name() == rhs.type_name() => Bool(lhs != rhs),
 
             (Solid(ref lhs), Add, Solid(ref rhs)) => Solid(self.solids.union(lhs, rhs)?),
             (Solid(ref lhs), Sub, Solid(ref rhs)) => Solid(self.solids.difference(lhs, rhs)?),
             (Solid(ref lhs), Mul, Solid(ref rhs)) => Solid(self.solids.intersection(lhs, rhs)?),
 
+            (Shape2D(ref lhs), Add | Sub | Mul, Shape2D(ref rhs)) => {
+                let op = match *expr.op {
+                    Add => SketchOp::Union,
+                    Sub => SketchOp::Difference,
+                    _ => SketchOp::Intersection,
+                };
+                match combine_sketches(lhs, rhs, op, self.solids.tolerance) {
+                    Some(faces) => Shape2D(faces),
+                    None => {
+                        return context.eval_err(EvalErrorType::GeometryOperationFailed {
+                            op: expr.op.op_name(),
+                        })
+                    }
+                }
+            }
+
             (lhs, op, rhs) => {
                 return context.eval_err(EvalErrorType::BinaryOpWrongTypes {
                     op: op.op_name(),
                     lhs_type: lhs.type_name(),
                     rhs_type: rhs.type_name(),
+                    location: location(),
                 })
             }
         };
 
         if let Number(fval) = val {
             if !fval.is_finite() {
-                return context.eval_err(EvalErrorType::NumExprNotFinite);
+                return context.eval_err(EvalErrorType::NumExprNotFinite {
+                    location: location(),
+                });
             }
         }
 
+        let op = match *expr.op {
+            Add => SolidOp::Union,
+            Sub => SolidOp::Difference,
+            Mul => SolidOp::Intersection,
+            Div | Pow | Lt | Le | Gt | Ge | Eq | Ne => return Ok(val),
+        };
+        self.record_solid_op(start, op, expr.span, scope);
+
         Ok(val)
     }
 
+    /// Attributes solids pushed since the set had `start` solids to the
+    /// operator at `span`.
+    fn record_solid_op(&mut self, start: usize, op: SolidOp, span: Span<'src>, scope: &Scope) {
+        let location = scope.doc().map(|doc| SourceLocation::new(span, doc));
+        self.solids
+            .record_since(start, OpDescriptor { op, location });
+    }
+
+    /// Follows a chain of imports starting from the document at `doc_path`,
+    /// returning the path of the document the last one imports.
+    fn resolve_imports(
+        &mut self,
+        doc_path: &FQPath,
+        import_parts: &[SpannedIdentifier<'src>],
+        context: &EvalContext,
+    ) -> EvalResult<'src, FQPath> {
+        let mut path = doc_path.clone();
+        for part in import_parts {
+            let doc = self.doc(&path, context)?;
+            let Some(import) = doc.imports.get(part.text) else {
+                return context.eval_err(EvalErrorType::ImportNotFound {
+                    name: part.text.into(),
+                });
+            };
+            path = path.import_path(import)?;
+        }
+
+        Ok(path)
+    }
+
+    /// Finds the function `name` in the documents imported with a wildcard by
+    /// `this_doc`, the document at `doc_path`, along with the path of the
+    /// document it's defined in.
+    ///
+    /// A function defined by more than one of those documents is an error.
+    fn find_wildcard_func(
+        &mut self,
+        doc_path: &FQPath,
+        this_doc: &'set Document<'src>,
+        name: &str,
+        context: &EvalContext,
+    ) -> EvalResult<'src, Option<(FQPath, &'set SpannedFuncDef<'src>)>> {
+        let mut found = None;
+        for import in this_doc.imports.values().filter(|import| import.wildcard) {
+            let import_path = doc_path.import_path(import)?;
+            let Some(func) = self.doc(&import_path, context)?.funcs.get(name) else {
+                continue;
+            };
+
+            if found.is_some() {
+                return context.eval_err(EvalErrorType::AmbiguousFunction { name: name.into() });
+            }
+            found = Some((import_path, func));
+        }
+
+        Ok(found)
+    }
+
     fn eval_func_call_expr(
         &mut self,
         expr: &SpannedFuncCallExpr<'src>,
@@ -114,23 +455,13 @@ impl<'set, 'src> EvalCache<'set, 'src> {
         let doc_path = scope
             .doc()
             .expect("Wrong kind of scope for func call evaluation");
-        let this_doc = &self.docs[doc_path];
         let context = context.push_func_call(expr, doc_path);
+        let this_doc = self.doc(doc_path, &context)?;
 
-        if let Some(import_part) = expr.name.import_part {
+        if !expr.name.import_parts.is_empty() {
             // Function call with import.
-            let Some(import) = this_doc.imports.get(import_part.text) else {
-                return context.eval_err(EvalErrorType::ImportNotFound {
-                    name: import_part.text.into(),
-                });
-            };
-
-            let import_path = doc_path.import_path(import)?;
-            let Some(import_doc) = self.docs.get(&import_path) else {
-                return context.eval_err(EvalErrorType::DocNotFound {
-                    path: import_path.clone(),
-                });
-            };
+            let import_path = self.resolve_imports(doc_path, &expr.name.import_parts, &context)?;
+            let import_doc = self.doc(&import_path, &context)?;
 
             let Some(func_def) = import_doc.funcs.get(expr.name.name_part.text) else {
                 return context.eval_err(EvalErrorType::FuncNotFound {
@@ -148,7 +479,19 @@ impl<'set, 'src> EvalCache<'set, 'src> {
         } else if let Some(arg) = scope.args().get(expr.name.name_part.text) {
             // Argument.
             Ok(arg.clone())
-        } else if let Some(built_in) = Self::get_built_in_func(&expr.name.name_part.text) {
+        } else if let Some(func) = this_doc.funcs.get(expr.name.name_part.text) {
+            // Regular function call. These shadow built-ins of the same name.
+            let args = self.eval_func_call_args(expr, func, &doc_path, scope, &context)?;
+            let scope = Scope::FuncCall {
+                name: expr.name.name_part.text.into(),
+                args,
+                doc_path: doc_path.clone(),
+            };
+            self.eval_scope(&scope, &context)
+        } else if builtins::is_special_form(expr.name.name_part.text) {
+            // Special forms need more than the values of their arguments.
+            self.eval_special_form(expr, scope, &context)
+        } else if let Some(built_in) = self.find_built_in(expr.name.name_part.text) {
             // Built-in function.
             let args = self.eval_built_in_call_args(expr, built_in, scope, &context)?;
             let scope = Scope::BuiltIn {
@@ -156,13 +499,17 @@ impl<'set, 'src> EvalCache<'set, 'src> {
                 args,
             };
             self.eval_scope(&scope, &context)
-        } else if let Some(func) = this_doc.funcs.get(expr.name.name_part.text) {
-            // Regular function call.
-            let args = self.eval_func_call_args(expr, func, &doc_path, scope, &context)?;
+        } else if let Some((import_path, func)) =
+            self.find_wildcard_func(doc_path, this_doc, expr.name.name_part.text, &context)?
+        {
+            // Function from a wildcard import. Unlike functions in the same
+            // document these don't shadow built-ins, so adding a function to
+            // an imported document can't change what a call in this one means.
+            let args = self.eval_func_call_args(expr, func, &import_path, scope, &context)?;
             let scope = Scope::FuncCall {
                 name: expr.name.name_part.text.into(),
                 args,
-                doc_path: doc_path.clone(),
+                doc_path: import_path,
             };
             self.eval_scope(&scope, &context)
         } else {
@@ -194,40 +541,41 @@ impl<'set, 'src> EvalCache<'set, 'src> {
         scope: &Scope,
         context: &EvalContext,
     ) -> EvalResult<'src, BTreeMap<String, Value>> {
-        match (&func_call.args, &func_def.args) {
-            (CallArgs::None, _) => Ok(BTreeMap::default()),
-            (CallArgs::Positional(args), Some(arg_defs)) => {
-                let mut arg_vals = BTreeMap::new();
-
-                for (arg_index, arg_expr) in args.iter().enumerate() {
-                    let Some(arg_def) = arg_defs.args.get(arg_index) else {
-                        return context.eval_err(EvalErrorType::TooManyArgs);
-                    };
+        let Some(arg_defs) = &func_def.args else {
+            return match func_call.args {
+                CallArgs::None => Ok(BTreeMap::default()),
+                _ => context.eval_err(EvalErrorType::TooManyArgs),
+            };
+        };
 
-                    let val = self.eval_expr(&arg_expr, scope, context)?;
-                    arg_vals.insert(arg_def.name.text.into(), val);
-                }
+        let mut arg_vals = BTreeMap::new();
 
-                Ok(arg_vals)
-            }
-            (CallArgs::Named(args), Some(arg_defs)) => {
-                let mut arg_vals = BTreeMap::new();
-
-                for (name, arg) in args {
-                    if arg_defs.args.iter().all(|f| &f.name.text != name) {
-                        return context.eval_err(EvalErrorType::InvalidNamedArg {
-                            name: (*name).into(),
-                        });
-                    }
+        for (arg_index, arg_expr) in func_call.args.positional().iter().enumerate() {
+            let Some(arg_def) = arg_defs.args.get(arg_index) else {
+                return context.eval_err(EvalErrorType::TooManyArgs);
+            };
 
-                    let val = self.eval_expr(&arg.expr, scope, context)?;
-                    arg_vals.insert((*name).into(), val);
-                }
+            let val = self.eval_expr(arg_expr, scope, context)?;
+            arg_vals.insert(arg_def.name.text.into(), val);
+        }
 
-                Ok(arg_vals)
+        for (name, arg) in func_call.args.named() {
+            if arg_defs.args.iter().all(|f| &f.name.text != name) {
+                return context.eval_err(EvalErrorType::InvalidNamedArg {
+                    name: (*name).into(),
+                });
             }
-            (_, None) => context.eval_err(EvalErrorType::TooManyArgs),
+            if arg_vals.contains_key(*name) {
+                return context.eval_err(EvalErrorType::ArgSuppliedTwice {
+                    name: (*name).into(),
+                });
+            }
+
+            let val = self.eval_expr(&arg.expr, scope, context)?;
+            arg_vals.insert((*name).into(), val);
         }
+
+        Ok(arg_vals)
     }
 
     /// Evaluates and adds argument defaults not already in args and throws an
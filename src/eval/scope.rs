@@ -1,11 +1,12 @@
 use std::collections::BTreeMap;
 
 use crate::{
+    ast::{Expr, SpannedExpr, SpannedFuncCallExpr, SpannedFuncDef, ToSpanned},
     error::{EvalErrorType, EvalResult},
-    FQPath,
+    FQPath, OpDescriptor, SolidOp,
 };
 
-use super::{EvalCache, EvalContext, Value};
+use super::{builtins::is_special_form, EvalCache, EvalContext, Value};
 
 /// A Scope is an identifier of a single cacheable unit of evaluation.
 /// i.e. A call to a specific function call with a specific set of arguments
@@ -27,12 +28,23 @@ pub(crate) enum Scope {
         name: String,
         args: BTreeMap<String, Value>,
     },
+    /// An inline expression wrapped in `Cache`, identified by its span in a
+    /// document and the arguments it refers to, or the body of a let with its
+    /// bindings added to the arguments.
+    Inline {
+        doc_path: FQPath,
+        start: usize,
+        end: usize,
+        args: BTreeMap<String, Value>,
+    },
 }
 
 impl Scope {
     pub(super) fn doc(&self) -> Option<&FQPath> {
         match self {
-            Scope::FuncCall { doc_path, .. } | Scope::ArgDefault { doc_path, .. } => Some(doc_path),
+            Scope::FuncCall { doc_path, .. }
+            | Scope::ArgDefault { doc_path, .. }
+            | Scope::Inline { doc_path, .. } => Some(doc_path),
             Scope::BuiltIn { .. } => None,
         }
     }
@@ -40,7 +52,9 @@ impl Scope {
     const EMPTY_ARGS: &'static BTreeMap<String, Value> = &BTreeMap::new();
     pub(super) fn args(&self) -> &BTreeMap<String, Value> {
         match self {
-            Scope::FuncCall { args, .. } | Scope::BuiltIn { args, .. } => args,
+            Scope::FuncCall { args, .. }
+            | Scope::BuiltIn { args, .. }
+            | Scope::Inline { args, .. } => args,
             Scope::ArgDefault { .. } => Self::EMPTY_ARGS,
         }
     }
@@ -58,8 +72,10 @@ impl<'set, 'src> EvalCache<'set, 'src> {
         self.evaluating.insert(scope.clone());
 
         let res = if let Some(cached) = self.cache.get(scope) {
+            self.stats.hits += 1;
             Ok(cached.clone())
         } else {
+            self.stats.misses += 1;
             self.eval_scope_unchecked(scope, context)
         };
 
@@ -79,29 +95,21 @@ impl<'set, 'src> EvalCache<'set, 'src> {
     ) -> EvalResult<'src, Value> {
         match scope {
             Scope::FuncCall { name, doc_path, .. } => {
-                let Some(doc) = self.docs.get(doc_path) else {
-                    return context.eval_err(EvalErrorType::DocNotFound {
-                        path: doc_path.clone(),
-                    });
-                };
+                let doc = self.doc(doc_path, context)?;
                 let Some(func) = doc.funcs.get(name.as_str()) else {
                     return context.eval_err(EvalErrorType::FuncNotFound { name: name.clone() });
                 };
 
                 let context = context.push_func_def(func, doc_path);
 
-                self.eval_expr(&func.body, scope, &context)
+                self.eval_func_body(func, doc_path, scope, &context)
             }
             Scope::ArgDefault {
                 doc_path,
                 func,
                 arg,
             } => {
-                let Some(doc) = self.docs.get(doc_path) else {
-                    return context.eval_err(EvalErrorType::DocNotFound {
-                        path: doc_path.clone(),
-                    });
-                };
+                let doc = self.doc(doc_path, context)?;
                 let Some(func) = doc.funcs.get(func.as_str()) else {
                     return context.eval_err(EvalErrorType::FuncNotFound { name: func.clone() });
                 };
@@ -120,15 +128,143 @@ impl<'set, 'src> EvalCache<'set, 'src> {
 
                 self.eval_expr(expr, scope, &context)
             }
-            Scope::BuiltIn { name, .. } => {
-                let Some(built_in) = Self::get_built_in_func(name) else {
+            Scope::BuiltIn { name, args } => {
+                let Some(built_in) = self.find_built_in(name) else {
                     return context.eval_err(EvalErrorType::BuiltInNotFound { name: name.clone() });
                 };
 
-                let context = context.push_built_in(name);
+                let context = context.push_built_in(name, args);
+
+                let start = self.solids.len();
+                let res = built_in.eval(&mut self.solids, &self.params, scope, &context);
+                self.solids.record_since(
+                    start,
+                    OpDescriptor {
+                        op: SolidOp::BuiltIn(name.clone()),
+                        location: context.location(),
+                    },
+                );
+
+                res
+            }
+            Scope::Inline { .. } => {
+                // Inline scopes are only ever evaluated with their expression
+                // in hand and are looked up in the cache directly.
+                unreachable!("Inline scopes can't be evaluated without their expression")
+            }
+        }
+    }
+}
+
+/// The outcome of evaluating an expression in tail position of a function's
+/// body.
+enum TailEval<'src> {
+    Value(Value),
+    /// A call to the function itself, with `next` the scope to evaluate the
+    /// body in again.
+    SelfCall {
+        call: SpannedFuncCallExpr<'src>,
+        next: Scope,
+    },
+}
+
+impl<'set, 'src> EvalCache<'set, 'src> {
+    /// Evaluates the body of `func` in `scope`.
+    ///
+    /// A call to `func` itself in tail position is a tail call. The body is in
+    /// tail position, and so are the body of a let and the cases of a `Switch`
+    /// that are. Rather than recursing, the call's arguments are evaluated and
+    /// the body is evaluated again in the new scope, so deep self-recursion
+    /// doesn't grow the stack. Every scope passed through is cached with the
+    /// final value.
+    fn eval_func_body(
+        &mut self,
+        func: &SpannedFuncDef<'src>,
+        doc_path: &FQPath,
+        scope: &Scope,
+        context: &EvalContext,
+    ) -> EvalResult<'src, Value> {
+        let mut scope = scope.clone();
+        let mut tail_scopes = Vec::new();
+
+        let res = loop {
+            let (call, next) = match self.eval_tail(&func.body, func, doc_path, &scope, context) {
+                Ok(TailEval::Value(val)) => break Ok(val),
+                Ok(TailEval::SelfCall { call, next }) => (call, next),
+                Err(err) => break Err(err),
+            };
+
+            if let Some(cached) = self.cache.get(&next) {
+                self.stats.hits += 1;
+                break Ok(cached.clone());
+            }
+            if self.evaluating.contains(&next) {
+                let call_context = context.push_func_call(&call, doc_path);
+                break call_context.eval_err(EvalErrorType::InfiniteRecursion);
+            }
+
+            self.stats.misses += 1;
+            self.evaluating.insert(next.clone());
+            tail_scopes.push(next.clone());
+            scope = next;
+        };
+
+        for tail_scope in tail_scopes {
+            self.evaluating.remove(&tail_scope);
+            if let Ok(val) = &res {
+                self.cache.insert(tail_scope, val.clone());
+            }
+        }
+
+        res
+    }
+
+    /// Evaluates `expr`, which is in tail position of the body of `func`,
+    /// stopping at a call to `func` itself once its arguments are evaluated.
+    fn eval_tail(
+        &mut self,
+        expr: &SpannedExpr<'src>,
+        func: &SpannedFuncDef<'src>,
+        doc_path: &FQPath,
+        scope: &Scope,
+        context: &EvalContext,
+    ) -> EvalResult<'src, TailEval<'src>> {
+        match &expr.inner {
+            Expr::Let(let_expr) => {
+                let inner =
+                    self.eval_let_bindings(&let_expr.spanned(&expr.span), scope, context)?;
+                self.eval_tail(&let_expr.body, func, doc_path, &inner, context)
+            }
+            // Names are resolved as in `eval_func_call_expr`: arguments first,
+            // then functions in the same document, then special forms.
+            Expr::FuncCall(call)
+                if call.name.import_parts.is_empty()
+                    && !scope.args().contains_key(call.name.name_part.text) =>
+            {
+                let call = call.spanned(&expr.span);
+                let name = call.name.name_part.text;
+                let call_context = context.push_func_call(&call, doc_path);
+
+                if name == func.name.text {
+                    let args =
+                        self.eval_func_call_args(&call, func, doc_path, scope, &call_context)?;
+                    let next = Scope::FuncCall {
+                        name: name.into(),
+                        args,
+                        doc_path: doc_path.clone(),
+                    };
+                    return Ok(TailEval::SelfCall { call, next });
+                }
+
+                if is_special_form(name) && !self.doc(doc_path, context)?.funcs.contains_key(name) {
+                    if let Some(branch) = self.special_form_branch(&call, scope, &call_context)? {
+                        return self.eval_tail(branch, func, doc_path, scope, &call_context);
+                    }
+                }
 
-                built_in.eval(&mut self.solids, scope, &context)
+                Ok(TailEval::Value(self.eval_expr(expr, scope, context)?))
             }
+            _ => Ok(TailEval::Value(self.eval_expr(expr, scope, context)?)),
         }
     }
 }
@@ -0,0 +1,195 @@
+use crate::{ast::*, error::EvalErrorType, DocSet, EvalError, FQPath};
+
+use super::{builtins::is_special_form, BuiltInRegistry, EvalCache, EvalContext};
+
+/// Resolves names in every function body and argument default in `docs`.
+///
+/// Names are resolved in the same order as [`EvalCache`] resolves them at
+/// evaluation time: imports, then arguments, then functions in the same
+/// document, then the host's built-ins in `built_ins` and funcad's own,
+/// then functions from wildcard imports.
+pub(crate) fn validate_names<'src>(
+    docs: &DocSet<'src>,
+    built_ins: Option<&BuiltInRegistry>,
+) -> Vec<EvalError<'src>> {
+    let mut errors = Vec::new();
+
+    let mut paths: Vec<_> = docs.keys().collect();
+    paths.sort();
+
+    for doc_path in paths {
+        let mut validator = NameValidator {
+            docs,
+            doc_path,
+            doc: &docs[doc_path],
+            built_ins,
+            errors: &mut errors,
+        };
+        validator.validate_doc();
+    }
+
+    errors
+}
+
+struct NameValidator<'v, 'src> {
+    docs: &'v DocSet<'src>,
+    doc_path: &'v FQPath,
+    doc: &'v Document<'src>,
+    built_ins: Option<&'v BuiltInRegistry>,
+    errors: &'v mut Vec<EvalError<'src>>,
+}
+
+impl<'v, 'src> NameValidator<'v, 'src> {
+    fn validate_doc(&mut self) {
+        let mut funcs: Vec<_> = self.doc.funcs.values().collect();
+        funcs.sort_by_key(|func| func.span.start());
+
+        for func in funcs {
+            let context = EvalContext::default();
+            let arg_names: Vec<&str> = func
+                .args
+                .iter()
+                .flat_map(|args| args.args.iter())
+                .map(|arg| arg.name.text)
+                .collect();
+
+            let body_context = context.push_func_def(func, self.doc_path);
+            self.validate_expr(&func.body, &arg_names, &body_context);
+
+            // Argument defaults are evaluated without access to other
+            // arguments.
+            for arg in func.args.iter().flat_map(|args| args.args.iter()) {
+                if let Some(default) = &arg.default {
+                    let default_context = context.push_arg_default(arg, func, self.doc_path);
+                    self.validate_expr(default, &[], &default_context);
+                }
+            }
+        }
+    }
+
+    fn validate_expr(
+        &mut self,
+        expr: &SpannedExpr<'src>,
+        arg_names: &[&str],
+        context: &EvalContext,
+    ) {
+        match &expr.inner {
+            Expr::Number(_) | Expr::Str(_) => {}
+            Expr::Unary(unary) => self.validate_expr(&unary.unit, arg_names, context),
+            Expr::Binary(binary) => {
+                self.validate_expr(&binary.lhs, arg_names, context);
+                self.validate_expr(&binary.rhs, arg_names, context);
+            }
+            Expr::FuncCall(call) => {
+                self.validate_func_call(&call.spanned(&expr.span), arg_names, context)
+            }
+            Expr::List(list) => {
+                for item in &list.items {
+                    self.validate_expr(item, arg_names, context);
+                }
+            }
+            Expr::Dict(dict) => {
+                for entry in dict.entries.values() {
+                    self.validate_expr(&entry.expr, arg_names, context);
+                }
+            }
+            Expr::Let(let_expr) => {
+                let mut names = arg_names.to_vec();
+                for binding in &let_expr.bindings {
+                    self.validate_expr(&binding.expr, &names, context);
+                    names.push(binding.name.text);
+                }
+                self.validate_expr(&let_expr.body, &names, context);
+            }
+        }
+    }
+
+    fn validate_func_call(
+        &mut self,
+        expr: &SpannedFuncCallExpr<'src>,
+        arg_names: &[&str],
+        context: &EvalContext,
+    ) {
+        let context = context.push_func_call(expr, self.doc_path);
+        let name = expr.name.name_part.text;
+
+        if !expr.name.import_parts.is_empty() {
+            if let Err(error_type) = self.resolve_import(&expr.name.import_parts, name) {
+                self.errors.push(context.error(error_type));
+            }
+        } else if !arg_names.contains(&name)
+            && !self.doc.funcs.contains_key(name)
+            && EvalCache::get_built_in_func(name).is_none()
+            && !self
+                .built_ins
+                .is_some_and(|built_ins| built_ins.contains(name))
+            && !is_special_form(name)
+        {
+            match self.wildcard_matches(name) {
+                0 => {
+                    self.errors
+                        .push(context.error(EvalErrorType::FuncNotFound { name: name.into() }));
+                }
+                1 => {}
+                _ => self
+                    .errors
+                    .push(context.error(EvalErrorType::AmbiguousFunction { name: name.into() })),
+            }
+        }
+
+        for arg in expr.args.positional() {
+            self.validate_expr(arg, arg_names, &context);
+        }
+        for (_, arg) in expr.args.named() {
+            self.validate_expr(&arg.expr, arg_names, &context);
+        }
+    }
+
+    /// The number of documents imported with a wildcard by this document that
+    /// define the function `name`.
+    fn wildcard_matches(&self, name: &str) -> usize {
+        self.doc
+            .imports
+            .values()
+            .filter(|import| import.wildcard)
+            .filter_map(|import| self.doc_path.import_path(import).ok())
+            .filter(|path| {
+                self.docs
+                    .get(path)
+                    .is_some_and(|doc| doc.funcs.contains_key(name))
+            })
+            .count()
+    }
+
+    /// Checks that `name` is a function in the document reached by following
+    /// the chain of `import_parts` from this document.
+    fn resolve_import(
+        &self,
+        import_parts: &[SpannedIdentifier<'src>],
+        name: &str,
+    ) -> Result<(), EvalErrorType<'src>> {
+        let mut import_path = self.doc_path.clone();
+        let mut import_doc = self.doc;
+        for part in import_parts {
+            let Some(import) = import_doc.imports.get(part.text) else {
+                return Err(EvalErrorType::ImportNotFound {
+                    name: part.text.into(),
+                });
+            };
+
+            import_path = import_path
+                .import_path(import)
+                .map_err(|e| EvalErrorType::Parse(Box::new(e)))?;
+            let Some(doc) = self.docs.get(&import_path) else {
+                return Err(EvalErrorType::DocNotFound { path: import_path });
+            };
+            import_doc = doc;
+        }
+
+        if !import_doc.funcs.contains_key(name) {
+            return Err(EvalErrorType::FuncNotFound { name: name.into() });
+        }
+
+        Ok(())
+    }
+}
@@ -1,11 +1,23 @@
-use crate::SolidId;
+use crate::{
+    error::{format_number, EvalResult},
+    EvalError, EvalErrorType, SolidId,
+};
 
-use std::hash::Hash;
+use std::{fmt::Display, hash::Hash};
+
+use truck_modeling::Face;
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum Value {
     Number(f64),
     Solid(SolidId),
+    List(Vec<Value>),
+    String(String),
+    Bool(bool),
+    Vector(f64, f64, f64),
+    /// A 2D profile on the XY plane, made of faces facing +Z. No faces is the
+    /// empty profile.
+    Shape2D(Vec<Face>),
 }
 
 // This is dangerous since float NaNs are never equal.
@@ -20,18 +32,164 @@ impl Hash for Value {
             // should never be equal, but we should error on NaN anyway.
             Value::Number(val) => val.to_bits().hash(state),
             Value::Solid(id) => id.hash(state),
+            Value::List(items) => items.hash(state),
+            Value::String(val) => val.hash(state),
+            Value::Bool(val) => val.hash(state),
+            Value::Vector(x, y, z) => [x, y, z].map(|val| val.to_bits()).hash(state),
+            Value::Shape2D(faces) => faces.hash(state),
+        }
+    }
+}
+
+impl Value {
+    /// Writes the value with each number written by `number`.
+    fn fmt_with(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        number: fn(f64) -> String,
+    ) -> std::fmt::Result {
+        match self {
+            Value::Number(val) => write!(f, "{}", number(*val)),
+            Value::Solid(id) => write!(f, "solid#{id}"),
+            Value::List(items) => {
+                write!(f, "[")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    item.fmt_with(f, number)?;
+                }
+                write!(f, "]")
+            }
+            Value::String(val) => write!(f, "\"{val}\""),
+            Value::Bool(val) => write!(f, "{val}"),
+            Value::Vector(x, y, z) => {
+                write!(f, "Vec3({}, {}, {})", number(*x), number(*y), number(*z))
+            }
+            Value::Shape2D(faces) => write!(f, "shape2d({} faces)", faces.len()),
+        }
+    }
+
+    /// Displays the value as it's shown in error messages, with numbers
+    /// rounded by [`format_number`].
+    pub(crate) fn rounded(&self) -> impl Display + '_ {
+        struct Rounded<'v>(&'v Value);
+
+        impl Display for Rounded<'_> {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                self.0.fmt_with(f, format_number)
+            }
+        }
+
+        Rounded(self)
+    }
+}
+
+/// Numbers are displayed exactly. [`Value::rounded`] displays them rounded.
+impl Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.fmt_with(f, |num| num.to_string())
+    }
+}
+
+/// Values serialize as plain data: numbers, strings, bools and lists as
+/// themselves, vectors as `[x, y, z]` and solids as `{"solid": id}` where the
+/// id is the solid's index in its set or `"empty"` or `"universal"`.
+///
+/// Evaluation never produces NaN or infinite numbers, so serializing one is an
+/// error rather than writing something that isn't valid JSON.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Value {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::{Error, SerializeMap};
+
+        let finite = |val: &f64| {
+            if val.is_finite() {
+                Ok(*val)
+            } else {
+                Err(S::Error::custom(format!("{val} isn't a finite number")))
+            }
+        };
+
+        match self {
+            Value::Number(val) => serializer.serialize_f64(finite(val)?),
+            Value::Solid(id) => {
+                let mut map = serializer.serialize_map(Some(1))?;
+                match id {
+                    SolidId::Regular(index) => map.serialize_entry("solid", index)?,
+                    SolidId::Empty => map.serialize_entry("solid", "empty")?,
+                    SolidId::Universal => map.serialize_entry("solid", "universal")?,
+                }
+                map.end()
+            }
+            Value::List(items) => items.serialize(serializer),
+            Value::String(val) => serializer.serialize_str(val),
+            Value::Bool(val) => serializer.serialize_bool(*val),
+            Value::Vector(x, y, z) => [finite(x)?, finite(y)?, finite(z)?].serialize(serializer),
+            Value::Shape2D(_) => Err(S::Error::custom("2D shapes can't be serialized")),
         }
     }
 }
 
 impl Value {
     pub(crate) const NUMBER_TYPE_NAME: &str = "number";
-    pub(crate) const SOLID_TYPE_NAME: &str = "number";
+    pub(crate) const SOLID_TYPE_NAME: &str = "solid";
+    pub(crate) const LIST_TYPE_NAME: &str = "list";
+    pub(crate) const STRING_TYPE_NAME: &str = "string";
+    pub(crate) const BOOL_TYPE_NAME: &str = "bool";
+    pub(crate) const VECTOR_TYPE_NAME: &str = "vector";
+    pub(crate) const SHAPE_2D_TYPE_NAME: &str = "shape2d";
 
     pub(crate) fn type_name(&self) -> &'static str {
         match self {
             Value::Number(_) => Self::NUMBER_TYPE_NAME,
             Value::Solid(_) => Self::SOLID_TYPE_NAME,
+            Value::List(_) => Self::LIST_TYPE_NAME,
+            Value::String(_) => Self::STRING_TYPE_NAME,
+            Value::Bool(_) => Self::BOOL_TYPE_NAME,
+            Value::Vector(..) => Self::VECTOR_TYPE_NAME,
+            Value::Shape2D(_) => Self::SHAPE_2D_TYPE_NAME,
+        }
+    }
+
+    /// The number in this value, if it's a number.
+    pub fn as_number(&self) -> Option<f64> {
+        match self {
+            Value::Number(num) => Some(*num),
+            _ => None,
+        }
+    }
+
+    /// The solid in this value, if it's a solid.
+    pub fn as_solid(&self) -> Option<SolidId> {
+        match self {
+            Value::Solid(id) => Some(*id),
+            _ => None,
+        }
+    }
+
+    /// Like [`Value::as_number`] but a value of another type is an
+    /// [`EvalErrorType::ArgWrongType`] error naming its type.
+    pub fn try_into_number<'src>(self) -> EvalResult<'src, f64> {
+        self.as_number()
+            .ok_or_else(|| self.wrong_type(Self::NUMBER_TYPE_NAME))
+    }
+
+    /// Like [`Value::as_solid`] but a value of another type is an
+    /// [`EvalErrorType::ArgWrongType`] error naming its type.
+    pub fn try_into_solid<'src>(self) -> EvalResult<'src, SolidId> {
+        self.as_solid()
+            .ok_or_else(|| self.wrong_type(Self::SOLID_TYPE_NAME))
+    }
+
+    fn wrong_type<'src>(&self, expected: &'static str) -> EvalError<'src> {
+        EvalError {
+            error_type: EvalErrorType::ArgWrongType {
+                name: "value".into(),
+                expected,
+                got: self.type_name(),
+            },
+            context_entries: Vec::default(),
         }
     }
 }
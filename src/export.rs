@@ -0,0 +1,219 @@
+use std::io::{self, Write};
+
+use truck_meshalgo::prelude::*;
+use truck_modeling::Solid;
+use truck_stepio::out::{CompleteStepDisplay, StepHeaderDescriptor, StepModel};
+
+use crate::{error::EvalResult, EvalError, EvalErrorType, ExportError, SolidId, SolidSet, Value};
+
+/// Triangulates a solid with curved faces approximated to within `tolerance`.
+///
+/// Faces don't share vertices, so each vertex has the normal of its face.
+pub fn tessellate(solid: &Solid, tolerance: f64) -> PolygonMesh {
+    let mut mesh = solid.triangulation(tolerance).to_polygon();
+    mesh.triangulate();
+    mesh
+}
+
+/// Like [`tessellate`] but the solid is looked up in `solids` and
+/// triangulated to within the set's tolerance.
+///
+/// The empty solid gives an empty mesh. The universal solid has no boundary
+/// to triangulate so it's an error, as is a solid carved from it, which is
+/// inside out with everything outside its boundary.
+pub fn tessellate_solid(solids: &SolidSet, solid: &SolidId) -> io::Result<PolygonMesh> {
+    match solid {
+        SolidId::Regular(_) => {
+            let solid = solids
+                .try_get(solid)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e.to_string()))?;
+            bounded_mesh(tessellate(solid, solids.tolerance))
+        }
+        SolidId::Empty => Ok(PolygonMesh::default()),
+        SolidId::Universal => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "the universal solid can't be exported",
+        )),
+    }
+}
+
+/// Returns `mesh` unless it's of a solid that's inside out, which has a
+/// negative volume.
+fn bounded_mesh(mesh: PolygonMesh) -> io::Result<PolygonMesh> {
+    if mesh.volume() < 0. {
+        return Err(unbounded_error());
+    }
+    Ok(mesh)
+}
+
+fn unbounded_error() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        "the solid is inside out and unbounded, so it can't be exported",
+    )
+}
+
+/// Whether `solid` is inside out, like a solid carved from the universal
+/// solid, without triangulating it finely.
+///
+/// A solid is inside out when a mesh of it has a negative volume, but the mesh
+/// only has to be fine enough to get the sign right. Every point of a mesh is
+/// within its tolerance of the boundary, so its volume is off by at most
+/// about its area times the tolerance. The mesh starts coarse relative to the
+/// size of the solid and is only refined while its volume is within that
+/// error.
+fn is_inverted(solid: &Solid) -> bool {
+    // truck caps how coarse a mesh gets, so this is cheap whatever the size.
+    let size = tessellate(solid, f64::MAX).bounding_box().diameter();
+    if !(size.is_finite() && size > 0.) {
+        return false;
+    }
+
+    let mut tolerance = size / 10.;
+    loop {
+        let mesh = tessellate(solid, tolerance);
+        let volume = mesh.volume();
+        if volume.abs() > area(&mesh) * tolerance || tolerance < size * 1e-4 {
+            return volume < 0.;
+        }
+        tolerance /= 10.;
+    }
+}
+
+/// The total area of the triangles of `mesh`.
+fn area(mesh: &PolygonMesh) -> f64 {
+    let positions = mesh.positions();
+    mesh.tri_faces()
+        .iter()
+        .map(|tri| {
+            let [a, b, c] = tri.map(|vertex| positions[vertex.pos]);
+            (b - a).cross(c - a).magnitude() / 2.
+        })
+        .sum()
+}
+
+/// The triangles of a solid value, triangulated by [`tessellate`].
+///
+/// This is for hosts that draw results themselves rather than writing files.
+/// The empty solid has no triangles, while the universal solid, solids carved
+/// from it and values that aren't solids are errors.
+pub fn mesh_of<'src>(
+    value: &Value,
+    solids: &SolidSet,
+    tolerance: f64,
+) -> EvalResult<'src, Vec<[Point3; 3]>> {
+    let error = |error_type| {
+        Err(EvalError {
+            error_type,
+            context_entries: Vec::default(),
+        })
+    };
+
+    let solid = match value {
+        Value::Solid(SolidId::Empty) => return Ok(Vec::new()),
+        Value::Solid(SolidId::Universal) => return error(EvalErrorType::UniversalSolid),
+        Value::Solid(solid) => solids.try_get(solid)?,
+        _ => {
+            return error(EvalErrorType::ArgWrongType {
+                name: "value".into(),
+                expected: Value::SOLID_TYPE_NAME,
+                got: value.type_name(),
+            })
+        }
+    };
+
+    let mesh = tessellate(solid, tolerance);
+    if mesh.volume() < 0. {
+        return error(EvalErrorType::InvertedSolid);
+    }
+    let positions = mesh.positions();
+    Ok(mesh
+        .tri_faces()
+        .iter()
+        .map(|tri| tri.map(|vertex| positions[vertex.pos]))
+        .collect())
+}
+
+/// Writes a solid as a binary STL file.
+///
+/// The solid is triangulated by [`tessellate`]. A solid that's inside out
+/// can't be written.
+pub fn export_stl<W: Write>(solid: &Solid, tolerance: f64, writer: W) -> io::Result<()> {
+    write_stl(&bounded_mesh(tessellate(solid, tolerance))?, writer)
+}
+
+/// Like [`export_stl`] but the solid is looked up in `solids` and
+/// triangulated by [`tessellate_solid`].
+///
+/// The empty solid is written as an STL file without any triangles.
+pub fn export_solid_stl<W: Write>(solids: &SolidSet, solid: &SolidId, writer: W) -> io::Result<()> {
+    write_stl(&tessellate_solid(solids, solid)?, writer)
+}
+
+/// Writes a solid as a Wavefront OBJ file.
+///
+/// The solid is triangulated by [`tessellate`]. A solid that's inside out
+/// can't be written.
+pub fn export_obj<W: Write>(solid: &Solid, tolerance: f64, writer: W) -> io::Result<()> {
+    write_obj(&bounded_mesh(tessellate(solid, tolerance))?, writer)
+}
+
+/// Like [`export_obj`] but the solid is looked up in `solids` and
+/// triangulated by [`tessellate_solid`].
+///
+/// The empty solid is written as an OBJ file without any faces.
+pub fn export_solid_obj<W: Write>(solids: &SolidSet, solid: &SolidId, writer: W) -> io::Result<()> {
+    write_obj(&tessellate_solid(solids, solid)?, writer)
+}
+
+fn write_stl<W: Write>(mesh: &PolygonMesh, mut writer: W) -> io::Result<()> {
+    stl::write(mesh, &mut writer, stl::StlType::Binary).map_err(mesh_io_error)
+}
+
+fn write_obj<W: Write>(mesh: &PolygonMesh, writer: W) -> io::Result<()> {
+    obj::write(mesh, writer).map_err(mesh_io_error)
+}
+
+fn mesh_io_error(error: errors::Error) -> io::Error {
+    match error {
+        errors::Error::FromIO(e) => e,
+        e => io::Error::new(io::ErrorKind::InvalidData, e.to_string()),
+    }
+}
+
+/// Writes a solid's boundary representation as a STEP file.
+///
+/// Unlike [`export_stl`] the solid isn't triangulated to be written, curved
+/// faces are written exactly. A solid that's inside out can't be written,
+/// which only takes a coarse mesh to tell.
+pub fn export_step<W: Write>(solid: &Solid, mut writer: W) -> io::Result<()> {
+    if is_inverted(solid) {
+        return Err(unbounded_error());
+    }
+
+    let compressed = solid.compress();
+    let step = CompleteStepDisplay::new(
+        StepModel::from(&compressed),
+        StepHeaderDescriptor {
+            organization_system: "funcad".into(),
+            ..Default::default()
+        },
+    );
+
+    write!(writer, "{step}")
+}
+
+/// Like [`export_step`] but the solid is looked up in `solids`.
+///
+/// The empty and universal solids have no boundary to write, so like any
+/// other failed lookup they give an [`EvalErrorType::InvalidSolidId`] error
+/// before anything is written. Like [`tessellate_solid`], a solid carved from
+/// the universal solid is an error too.
+pub fn export_solid_step<'src, W: Write>(
+    solids: &SolidSet,
+    solid: &SolidId,
+    writer: W,
+) -> Result<(), ExportError<'src>> {
+    let solid = solids.try_get(solid)?;
+    Ok(export_step(solid, writer)?)
+}
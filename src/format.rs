@@ -0,0 +1,288 @@
+use std::{collections::BTreeMap, fmt::Write};
+
+use pest::Span;
+
+use crate::ast::*;
+
+/// Re-emits a document as canonical source.
+///
+/// Imports come first, sorted by file, then a blank line and one function per
+/// line in the order they were defined. Binary operators are surrounded by
+/// single spaces and parentheses are only kept where precedence needs them.
+///
+/// Named arguments are written in name order and calls without arguments,
+/// like `f()`, without parentheses. Formatting the result again gives the same
+/// source.
+///
+/// Comments are kept, each on a line of its own before the import or function
+/// it was in or in front of. Comments after the last of them are written at
+/// the end. A document with nothing but comments has no source to find them
+/// in, so it formats as empty.
+pub fn format_document(doc: &Document) -> String {
+    let mut out = String::new();
+
+    let mut imports: Vec<_> = doc.imports.values().collect();
+    imports.sort_by_key(|import| (import.file, import.alias));
+    let mut funcs: Vec<_> = doc.funcs.values().collect();
+    funcs.sort_by_key(|func| (func.span.start(), func.name.text));
+
+    let spans: Vec<_> = imports
+        .iter()
+        .map(|import| import.span)
+        .chain(funcs.iter().map(|func| func.span))
+        .collect();
+    let mut comments = owned_comments(&spans);
+
+    for import in &imports {
+        write_comments(&mut out, &mut comments, import.span.end());
+        let wildcard = if import.wildcard { "* from " } else { "" };
+        let _ = writeln!(out, "import {wildcard}{}", import.file);
+    }
+
+    if !imports.is_empty() && !funcs.is_empty() {
+        out.push('\n');
+    }
+    for func in funcs {
+        write_comments(&mut out, &mut comments, func.span.end());
+        out.push_str(func.name.text);
+        if let Some(args) = &func.args {
+            out.push('(');
+            for (i, arg) in args.args.iter().enumerate() {
+                if i > 0 {
+                    out.push_str(", ");
+                }
+                out.push_str(arg.name.text);
+                if let Some(default) = &arg.default {
+                    out.push_str(" = ");
+                    write_expr(&mut out, default);
+                }
+            }
+            out.push(')');
+        }
+        out.push_str(" = ");
+        write_expr(&mut out, &func.body);
+        out.push('\n');
+    }
+    write_comments(&mut out, &mut comments, usize::MAX);
+
+    out
+}
+
+/// The comments in the source of `spans`, keyed by the end of the first
+/// statement span whose content ends after them, or by `usize::MAX` if none
+/// does.
+fn owned_comments<'src>(spans: &[Span<'src>]) -> BTreeMap<usize, Vec<&'src str>> {
+    let src = spans.first().map_or("", |span| span.get_input());
+    let comments = comments(src);
+
+    // A statement's span runs on over the whitespace and comments after it,
+    // which belong with whatever follows.
+    let mut ends: Vec<_> = spans
+        .iter()
+        .map(|span| {
+            let mut end = span.end();
+            loop {
+                end = span.start() + src[span.start()..end].trim_end().len();
+                match comments
+                    .iter()
+                    .find(|(start, comment)| start + comment.len() == end)
+                {
+                    Some((start, _)) => end = *start,
+                    None => break (end, span.end()),
+                }
+            }
+        })
+        .collect();
+    ends.sort();
+
+    let mut owned: BTreeMap<_, Vec<_>> = BTreeMap::new();
+    for (start, comment) in comments {
+        let owner = ends.get(ends.partition_point(|&(end, _)| end <= start));
+        owned
+            .entry(owner.map_or(usize::MAX, |&(_, span_end)| span_end))
+            .or_default()
+            .push(comment);
+    }
+
+    owned
+}
+
+/// The block comments in `src` and where they start, skipping string literals.
+fn comments(src: &str) -> Vec<(usize, &str)> {
+    let mut comments = Vec::new();
+    let mut pos = 0;
+    while let Some(offset) = src[pos..].find(['"', '/']) {
+        let start = pos + offset;
+        let rest = &src[start..];
+        pos = if let Some(string) = rest.strip_prefix('"') {
+            // Strings can't contain quotes, so the next one closes it.
+            start + string.find('"').map_or(rest.len(), |end| end + 2)
+        } else if let Some(comment) = rest.strip_prefix("/*") {
+            let len = comment.find("*/").map_or(rest.len(), |end| end + 4);
+            comments.push((start, &rest[..len]));
+            start + len
+        } else {
+            start + 1
+        };
+    }
+
+    comments
+}
+
+fn write_comments(out: &mut String, comments: &mut BTreeMap<usize, Vec<&str>>, owner: usize) {
+    for comment in comments.remove(&owner).into_iter().flatten() {
+        out.push_str(comment);
+        out.push('\n');
+    }
+}
+
+// Binding strength, from loosest to tightest. A let's body extends as far as
+// possible so a let can only be an operand inside parentheses.
+const LET: u8 = 0;
+const EQUALITY: u8 = 1;
+const COMPARISON: u8 = 2;
+const ADDITIVE: u8 = 3;
+const MULTIPLICATIVE: u8 = 4;
+const NEGATION: u8 = 5;
+const POWER: u8 = 6;
+const ATOM: u8 = 7;
+
+fn binary_precedence(op: BinaryOp) -> u8 {
+    match op {
+        BinaryOp::Eq | BinaryOp::Ne => EQUALITY,
+        BinaryOp::Lt | BinaryOp::Le | BinaryOp::Gt | BinaryOp::Ge => COMPARISON,
+        BinaryOp::Add | BinaryOp::Sub => ADDITIVE,
+        BinaryOp::Mul | BinaryOp::Div => MULTIPLICATIVE,
+        BinaryOp::Pow => POWER,
+    }
+}
+
+fn binary_symbol(op: BinaryOp) -> &'static str {
+    match op {
+        BinaryOp::Add => "+",
+        BinaryOp::Sub => "-",
+        BinaryOp::Mul => "*",
+        BinaryOp::Div => "/",
+        BinaryOp::Pow => "^",
+        BinaryOp::Lt => "<",
+        BinaryOp::Le => "<=",
+        BinaryOp::Gt => ">",
+        BinaryOp::Ge => ">=",
+        BinaryOp::Eq => "==",
+        BinaryOp::Ne => "!=",
+    }
+}
+
+fn precedence(expr: &Expr) -> u8 {
+    match expr {
+        Expr::Binary(binary) => binary_precedence(binary.op.inner),
+        Expr::Unary(_) => NEGATION,
+        // Written with a leading minus, which reads back as a negation.
+        Expr::Number(number) if number.val.is_sign_negative() => NEGATION,
+        Expr::Let(_) => LET,
+        _ => ATOM,
+    }
+}
+
+/// Writes `expr`, in parentheses if it binds looser than `min`.
+fn write_operand(out: &mut String, expr: &SpannedExpr, min: u8) {
+    if precedence(expr) < min {
+        out.push('(');
+        write_expr(out, expr);
+        out.push(')');
+    } else {
+        write_expr(out, expr);
+    }
+}
+
+fn write_expr(out: &mut String, expr: &SpannedExpr) {
+    match &expr.inner {
+        Expr::Number(number) => {
+            let _ = write!(out, "{}", number.val);
+        }
+        Expr::Str(string) => {
+            let _ = write!(out, "\"{}\"", string.val);
+        }
+        Expr::Unary(unary) => match unary.op {
+            UnaryOp::Neg => {
+                out.push('-');
+                write_operand(out, &unary.unit, NEGATION);
+            }
+        },
+        Expr::Binary(binary) => {
+            let op = binary.op.inner;
+            let prec = binary_precedence(op);
+            // Exponentiation groups to the right and everything else to the
+            // left, so only the other side needs parentheses at equal
+            // precedence. Negation binds looser than exponentiation, so a
+            // negated exponent is parenthesized too.
+            let (lmin, rmin) = match op {
+                BinaryOp::Pow => (prec + 1, prec),
+                _ => (prec, prec + 1),
+            };
+            write_operand(out, &binary.lhs, lmin);
+            let _ = write!(out, " {} ", binary_symbol(op));
+            write_operand(out, &binary.rhs, rmin);
+        }
+        Expr::FuncCall(call) => {
+            for part in &call.name.import_parts {
+                out.push_str(part.text);
+                out.push('.');
+            }
+            out.push_str(call.name.name_part.text);
+            write_call_args(out, &call.args);
+        }
+        Expr::List(list) => {
+            out.push('[');
+            write_separated(out, &list.items, |out, item| write_expr(out, item));
+            out.push(']');
+        }
+        Expr::Dict(dict) => {
+            out.push('{');
+            let entries: Vec<_> = dict.entries.values().collect();
+            write_separated(out, &entries, |out, entry| {
+                let _ = write!(out, "\"{}\": ", entry.key.val);
+                write_expr(out, &entry.expr);
+            });
+            out.push('}');
+        }
+        Expr::Let(let_expr) => {
+            out.push_str("let ");
+            // A let bound by another let would take the following bindings
+            // as its own.
+            write_separated(out, &let_expr.bindings, |out, binding| {
+                let _ = write!(out, "{} = ", binding.name.text);
+                write_operand(out, &binding.expr, EQUALITY);
+            });
+            out.push_str(" in ");
+            write_expr(out, &let_expr.body);
+        }
+    }
+}
+
+fn write_call_args(out: &mut String, args: &CallArgs) {
+    if let CallArgs::None = args {
+        return;
+    }
+
+    out.push('(');
+    write_separated(out, args.positional(), |out, arg| write_expr(out, arg));
+    let named: Vec<_> = args.named().map(|(_, arg)| arg).collect();
+    if !args.positional().is_empty() && !named.is_empty() {
+        out.push_str(", ");
+    }
+    write_separated(out, &named, |out, arg| {
+        let _ = write!(out, "{} = ", arg.name.text);
+        write_expr(out, &arg.expr);
+    });
+    out.push(')');
+}
+
+fn write_separated<T>(out: &mut String, items: &[T], mut write_item: impl FnMut(&mut String, &T)) {
+    for (i, item) in items.iter().enumerate() {
+        if i > 0 {
+            out.push_str(", ");
+        }
+        write_item(out, item);
+    }
+}
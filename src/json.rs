@@ -0,0 +1,108 @@
+use std::collections::BTreeMap;
+
+use serde_json::{json, Value as JsonValue};
+use truck_meshalgo::prelude::*;
+
+use crate::{
+    error::EvalResult, tessellate, DocSet, EvalCache, EvalContext, EvalError, EvalErrorType,
+    FQPath, Scope, SolidId, SolidSet, Value,
+};
+
+/// Evaluate a single function in `doc_path` by name and describe the result
+/// as JSON.
+///
+/// On success the object has a `"result"` field and on failure an `"error"`
+/// field with the error `"message"` and its `"context"`, innermost first.
+///
+/// Results are tagged with a `"type"`:
+/// - `"number"` and `"string"` have a `"value"`.
+/// - `"list"` has `"items"`, each described the same way.
+/// - `"solid"` has a mesh from [`crate::tessellate`] as `"vertices"`, a list
+///   of `[x, y, z]`, and `"triangles"`, a list of vertex indices, along with
+///   its `"bbox"` with `"min"` and `"max"` corners. The mesh approximates
+///   curved faces to within the solid tolerance. A solid carved from the
+///   universal solid is inside out and unbounded, so it's an error.
+/// - `"empty_solid"` and `"universal_solid"` have no other fields.
+/// - `"shape2d"` has the number of `"faces"` in the profile.
+pub fn evaluate_to_json(docs: &DocSet, doc_path: &FQPath, func_name: &str) -> JsonValue {
+    let mut cache = EvalCache::new(docs);
+    let scope = Scope::FuncCall {
+        name: func_name.into(),
+        args: BTreeMap::new(),
+        doc_path: doc_path.clone(),
+    };
+
+    let context = EvalContext::default();
+    let result = cache
+        .eval_scope(&scope, &context)
+        .and_then(|val| value_to_json(&val, cache.solids()));
+
+    match result {
+        Ok(result) => json!({ "result": result }),
+        Err(error) => json!({ "error": error_to_json(&error) }),
+    }
+}
+
+fn value_to_json<'src>(val: &Value, solids: &SolidSet) -> EvalResult<'src, JsonValue> {
+    Ok(match val {
+        Value::Number(num) => json!({ "type": "number", "value": num }),
+        Value::String(string) => json!({ "type": "string", "value": string }),
+        Value::Bool(val) => json!({ "type": "bool", "value": val }),
+        Value::Vector(x, y, z) => json!({ "type": "vector", "value": [x, y, z] }),
+        Value::Shape2D(faces) => json!({ "type": "shape2d", "faces": faces.len() }),
+        Value::List(items) => {
+            let items: EvalResult<Vec<_>> = items
+                .iter()
+                .map(|item| value_to_json(item, solids))
+                .collect();
+            json!({ "type": "list", "items": items? })
+        }
+        Value::Solid(SolidId::Empty) => json!({ "type": "empty_solid" }),
+        Value::Solid(SolidId::Universal) => json!({ "type": "universal_solid" }),
+        Value::Solid(id) => {
+            let mesh = tessellate(solids.try_get(id)?, solids.tolerance);
+            if mesh.volume() < 0. {
+                return Err(EvalError {
+                    error_type: EvalErrorType::InvertedSolid,
+                    context_entries: Vec::default(),
+                });
+            }
+
+            let vertices: Vec<[f64; 3]> = mesh
+                .positions()
+                .iter()
+                .map(|pt| [pt.x, pt.y, pt.z])
+                .collect();
+            let triangles: Vec<[usize; 3]> = mesh
+                .tri_faces()
+                .iter()
+                .map(|tri| tri.map(|vertex| vertex.pos))
+                .collect();
+            let bbox = mesh.bounding_box();
+            let (min, max) = (bbox.min(), bbox.max());
+
+            json!({
+                "type": "solid",
+                "vertices": vertices,
+                "triangles": triangles,
+                "bbox": {
+                    "min": [min.x, min.y, min.z],
+                    "max": [max.x, max.y, max.z],
+                },
+            })
+        }
+    })
+}
+
+fn error_to_json(error: &EvalError) -> JsonValue {
+    let context: Vec<String> = error
+        .context_entries
+        .iter()
+        .map(|entry| entry.to_string())
+        .collect();
+
+    json!({
+        "message": error.error_type.to_string(),
+        "context": context,
+    })
+}
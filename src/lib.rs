@@ -4,15 +4,40 @@ pub mod ast;
 use ast::*;
 
 mod error;
-pub use error::{EvalError, ParseError, EvalErrorType};
+pub use error::{EvalError, ParseError, EvalErrorType, ExportError};
 use error::{EvalResult, ParseResult};
 
 mod eval;
-pub use eval::Value;
-use eval::{EvalCache, EvalContext, Scope};
+pub use eval::{BuiltInArgDef, BuiltInRegistry, CacheStats, CustomBuiltIn, Value};
+use eval::{DocLoader, EvalCache, EvalContext, Scope};
 
 mod solids;
-pub use solids::{SolidId, SolidSet};
+pub use solids::{OpDescriptor, SolidId, SolidOp, SolidSet, SourceLocation};
+
+#[cfg(feature = "json")]
+mod json;
+#[cfg(feature = "json")]
+pub use json::evaluate_to_json;
+
+mod export;
+pub use export::{
+    export_obj, export_solid_obj, export_solid_step, export_solid_stl, export_step, export_stl,
+    mesh_of, tessellate, tessellate_solid,
+};
+
+mod format;
+pub use format::format_document;
+
+mod opt;
+pub use opt::{fold_constants, fold_document_constants};
+
+mod repl;
+pub use repl::repl;
+
+#[cfg(feature = "watch")]
+mod watch;
+#[cfg(feature = "watch")]
+pub use watch::watch;
 
 use std::{
     collections::{BTreeMap, BTreeSet, HashMap},
@@ -22,7 +47,7 @@ use std::{
     path::{Path, PathBuf},
 };
 
-use pest::Parser;
+use pest::{iterators::Pairs, Parser};
 
 use typed_arena::Arena;
 
@@ -37,16 +62,67 @@ pub(crate) use lang::{FCParser, Rule};
 
 /// Parses a single document.
 ///
-/// `src` should contain the source to parse.
+/// `src` should contain the source to parse. It may start with a byte order
+/// mark and use CRLF or lone CR line breaks, as sources written on Windows
+/// and old Macs do.
 ///
 /// Imported files won't be parsed and only parsing validation is performed so
 /// the code cannot be guaranteed to be correct.
 pub fn parse_document<'src>(src: &'src str) -> Result<Document, ParseError> {
-    let pair = FCParser::parse(Rule::document, src)?.try_next()?;
+    let src = src.strip_prefix('\u{feff}').unwrap_or(src);
+    let pair = parse_rule(Rule::document, src)?.try_next()?;
 
     Document::try_from(pair)
 }
 
+/// Parses a single expression such as `1 + Sin(30)`.
+///
+/// `src` must contain nothing but the expression.
+pub fn parse_expr<'src>(src: &'src str) -> ParseResult<'src, SpannedExpr<'src>> {
+    let pair = parse_rule(Rule::expr_only, src)?.try_next()?;
+
+    SpannedExpr::try_from(pair)
+}
+
+/// Parses `src` as `rule`, with errors on the lines [`line_col`] counts.
+///
+/// pest doesn't count a lone CR as a line break. The grammar treats CR and LF
+/// alike, and swapping one for the other keeps every offset the same, so
+/// parsing a copy with lone CRs swapped for LFs fails with the same error but
+/// on the right line.
+fn parse_rule(rule: Rule, src: &str) -> ParseResult<'_, Pairs<'_, Rule>> {
+    FCParser::parse(rule, src).map_err(|error| {
+        let lf_src: String = src
+            .char_indices()
+            .map(|(i, c)| match c {
+                '\r' if !src[i + 1..].starts_with('\n') => '\n',
+                c => c,
+            })
+            .collect();
+        if lf_src == src {
+            return error.into();
+        }
+        FCParser::parse(rule, &lf_src).err().unwrap_or(error).into()
+    })
+}
+
+/// The line and column, both from 1, of the byte at `pos` in `src`.
+///
+/// Unlike pest, CRLF, a lone CR and LF each count as a line break.
+pub(crate) fn line_col(src: &str, pos: usize) -> (usize, usize) {
+    let before = &src[..pos];
+    let lone_crs = before
+        .match_indices('\r')
+        .filter(|(i, _)| !src[i + 1..].starts_with('\n'))
+        .count();
+    let line_start = before.rfind(['\r', '\n']).map_or(0, |i| i + 1);
+
+    (
+        before.matches('\n').count() + lone_crs + 1,
+        before[line_start..].chars().count() + 1,
+    )
+}
+
 /// A collection of documents by path.
 type DocSet<'src> = HashMap<FQPath, Document<'src>>;
 
@@ -84,6 +160,58 @@ where
     Ok(parsed)
 }
 
+/// Like [`parse_all`] but documents are read and parsed in parallel.
+///
+/// Imports are followed a level at a time, with every newly imported document
+/// of a level read and parsed concurrently. The arena isn't thread safe, so
+/// sources are copied into it between being read and being parsed. If several
+/// documents fail, which error is returned doesn't depend on how threads are
+/// scheduled.
+#[cfg(feature = "parallel")]
+pub fn parse_all_parallel<'src, R, F>(
+    source_arena: &'src Arena<u8>,
+    main: &FQPath,
+    get_source: F,
+) -> ParseResult<'src, DocSet<'src>>
+where
+    R: Read,
+    F: Fn(&FQPath) -> ParseResult<'src, R> + Sync,
+{
+    use rayon::prelude::*;
+
+    let mut to_parse = vec![main.clone()];
+    let mut parsed = HashMap::new();
+
+    while !to_parse.is_empty() {
+        let read: Vec<_> = to_parse
+            .par_iter()
+            .map(|path| read_src(get_source(path)?))
+            .collect();
+        let srcs = read
+            .into_iter()
+            .map(|src| Ok(&*source_arena.alloc_str(&src?)))
+            .collect::<ParseResult<Vec<_>>>()?;
+        let docs: Vec<_> = srcs.into_par_iter().map(parse_document).collect();
+
+        let mut imported = BTreeSet::new();
+        for (current, doc) in to_parse.into_iter().zip(docs) {
+            let doc = doc?;
+            for import in doc.imports.values() {
+                imported.insert(current.import_path(import)?);
+            }
+
+            parsed.insert(current, doc);
+        }
+
+        to_parse = imported
+            .into_iter()
+            .filter(|path| !parsed.contains_key(path))
+            .collect();
+    }
+
+    Ok(parsed)
+}
+
 /// Read and parse the file `main` and any imports recursively.
 pub fn parse_all_files<'src>(
     source_arena: &'src Arena<u8>,
@@ -104,12 +232,160 @@ pub fn parse_all_files<'src>(
 }
 
 /// Evaluate a single function in `doc_path` by name.
+///
+/// [`EvalOptions`] evaluates with more settings and returns more than the
+/// value.
 pub fn eval_function<'src>(
     docs: &DocSet<'src>,
     doc_path: &FQPath,
     func_name: &str,
 ) -> EvalResult<'src, Value> {
+    EvalOptions::new()
+        .eval_function(docs, doc_path, func_name)
+        .result
+}
+
+/// Settings for evaluating a function, for hosts that need more than
+/// [`eval_function`].
+///
+/// Each setting is chosen with a builder method and they combine freely, so
+/// for example a host can evaluate with its own built-ins at a custom
+/// tolerance. Unset settings are the same as for [`eval_function`].
+#[derive(Default)]
+pub struct EvalOptions<'set> {
+    params: HashMap<String, Value>,
+    tolerance: Option<f64>,
+    built_ins: Option<&'set BuiltInRegistry>,
+}
+
+impl<'set> EvalOptions<'set> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// `Param` calls look names up in `params`.
+    ///
+    /// This lets a host such as a build script parameterize a model without
+    /// the model taking the parameters as arguments.
+    pub fn with_params(self, params: HashMap<String, Value>) -> Self {
+        Self { params, ..self }
+    }
+
+    /// Geometry operations are performed to within `tolerance` rather than
+    /// the default.
+    ///
+    /// A tolerance that isn't finite and positive makes evaluation fail.
+    pub fn with_tolerance(self, tolerance: f64) -> Self {
+        Self {
+            tolerance: Some(tolerance),
+            ..self
+        }
+    }
+
+    /// Calls can also be to the host's built-ins in `built_ins`.
+    pub fn with_built_ins(self, built_ins: &'set BuiltInRegistry) -> Self {
+        Self {
+            built_ins: Some(built_ins),
+            ..self
+        }
+    }
+
+    /// Evaluate a single function in `doc_path` by name with these settings.
+    pub fn eval_function<'src>(
+        self,
+        docs: &DocSet<'src>,
+        doc_path: &FQPath,
+        func_name: &str,
+    ) -> Evaluation<'src> {
+        let context = EvalContext::default();
+        if let Some(tolerance) = self.tolerance {
+            if !tolerance.is_finite() || tolerance <= 0. {
+                return Evaluation {
+                    result: context.eval_err(EvalErrorType::InvalidDimension {
+                        name: "tolerance".into(),
+                        reason: "must be finite and positive",
+                    }),
+                    solids: SolidSet::default(),
+                    stats: CacheStats::default(),
+                };
+            }
+        }
+
+        let mut cache = EvalCache::new(docs).with_params(self.params);
+        if let Some(tolerance) = self.tolerance {
+            cache = cache.with_tolerance(tolerance);
+        }
+        if let Some(built_ins) = self.built_ins {
+            cache = cache.with_built_ins(built_ins);
+        }
+
+        let scope = Scope::FuncCall {
+            name: func_name.into(),
+            args: BTreeMap::new(),
+            doc_path: doc_path.clone(),
+        };
+        let result = cache.eval_scope(&scope, &context);
+        let stats = cache.stats();
+        Evaluation {
+            result,
+            solids: cache.into_solids(),
+            stats,
+        }
+    }
+}
+
+/// The outcome of [`EvalOptions::eval_function`].
+///
+/// The solids and stats are returned even if evaluation fails.
+pub struct Evaluation<'src> {
+    pub result: EvalResult<'src, Value>,
+    /// Every solid created during evaluation, including those pushed by the
+    /// host's built-ins. [`SolidSet::provenance`] describes how each solid
+    /// was produced.
+    pub solids: SolidSet,
+    /// How often cached values were reused during evaluation.
+    pub stats: CacheStats,
+}
+
+/// Evaluate a bare expression as if it were the body of a function without
+/// arguments in `doc_path`.
+///
+/// The expression can call the document's functions, imports and built-ins.
+pub fn eval_expr_str<'src>(
+    docs: &DocSet<'src>,
+    doc_path: &FQPath,
+    expr_src: &'src str,
+) -> EvalResult<'src, Value> {
+    let expr = parse_expr(expr_src)?;
+
     let mut cache = EvalCache::new(docs);
+    let context = EvalContext::default();
+    cache.eval_expr_in_doc(&expr, doc_path, &context)
+}
+
+/// Evaluate a single function in `doc_path` by name, parsing documents only
+/// when evaluation first needs them.
+///
+/// Unlike [`parse_all`], documents that are imported but never used aren't
+/// parsed. Documents already in `docs` are used as is and any documents parsed
+/// during evaluation are added to `docs` so later calls can reuse them.
+///
+/// `get_source` should return a reader to a source file given an FQPath.
+pub fn eval_function_lazy<'src, R, F>(
+    source_arena: &'src Arena<u8>,
+    docs: &mut DocSet<'src>,
+    doc_path: &FQPath,
+    func_name: &str,
+    get_source: F,
+) -> EvalResult<'src, Value>
+where
+    R: Read,
+    F: Fn(&FQPath) -> ParseResult<'src, R>,
+{
+    let load_src = |path: &FQPath| alloc_src(source_arena, get_source(path)?);
+    let doc_arena = Arena::new();
+
+    let mut cache = EvalCache::with_loader(docs, DocLoader::new(&load_src, &doc_arena));
     let scope = Scope::FuncCall {
         name: func_name.into(),
         args: BTreeMap::new(),
@@ -117,7 +393,40 @@ pub fn eval_function<'src>(
     };
 
     let context = EvalContext::default();
-    cache.eval_scope(&scope, &context)
+    let res = cache.eval_scope(&scope, &context);
+
+    let loaded = cache.into_loaded_paths();
+    docs.extend(loaded.into_iter().zip(doc_arena.into_vec()));
+
+    res
+}
+
+/// The names of the functions defined in the document at `doc_path`, sorted,
+/// or `None` if `docs` doesn't contain it.
+///
+/// Imports and built-ins aren't included.
+pub fn list_functions<'a>(docs: &'a DocSet, doc_path: &FQPath) -> Option<Vec<&'a str>> {
+    let mut names: Vec<_> = docs.get(doc_path)?.function_names().collect();
+    names.sort_unstable();
+    Some(names)
+}
+
+/// Checks that every function call in `docs` resolves to a function, argument,
+/// built-in or imported function without evaluating anything.
+///
+/// Every unresolved name is reported, so an empty `Vec` means all names
+/// resolve. Calls may still fail during evaluation for other reasons.
+pub fn validate_names<'src>(docs: &DocSet<'src>) -> Vec<EvalError<'src>> {
+    eval::validate_names(docs, None)
+}
+
+/// Like [`validate_names`] but calls can also be to the host's built-ins in
+/// `built_ins`.
+pub fn validate_names_with_built_ins<'src>(
+    docs: &DocSet<'src>,
+    built_ins: &BuiltInRegistry,
+) -> Vec<EvalError<'src>> {
+    eval::validate_names(docs, Some(built_ins))
 }
 
 /// A "fully qualified" path to a document or function.
@@ -136,6 +445,9 @@ impl Display for FQPath {
 
 impl FQPath {
     /// Returns the `FQPath` of an import in a doc with path this path.
+    ///
+    /// An import that resolves back to this path is an error, since a
+    /// document importing itself is always a mistake.
     pub(crate) fn import_path<'src>(
         &self,
         import: &SpannedImport<'src>,
@@ -146,7 +458,7 @@ impl FQPath {
         for part in import.file.split("/") {
             match part {
                 ".." => match new_parts.pop() {
-                    None => return Err(ParseError::ImportNotInDir(import.clone())),
+                    None => return Err(ParseError::ImportNotInDir(Box::new(*import))),
                     _ => {}
                 },
                 ident => {
@@ -155,6 +467,10 @@ impl FQPath {
             }
         }
 
+        if new_parts == self.0 {
+            return Err(ParseError::SelfImport(Box::new(*import)));
+        }
+
         Ok(Self(new_parts))
     }
 
@@ -166,9 +482,14 @@ impl FQPath {
 
 fn alloc_src<'src, R: Read>(
     source_arena: &'src Arena<u8>,
-    mut reader: R,
+    reader: R,
 ) -> Result<&'src str, ParseError<'src>> {
+    Ok(source_arena.alloc_str(&read_src(reader)?))
+}
+
+fn read_src<'src, R: Read>(mut reader: R) -> Result<String, ParseError<'src>> {
     let mut src_string = String::new();
     reader.read_to_string(&mut src_string)?;
-    Ok(source_arena.alloc_str(&src_string))
+
+    Ok(src_string)
 }
@@ -0,0 +1,162 @@
+//! Exports a function of a model to a file, or evaluates expressions against
+//! a model interactively.
+//!
+//! ```sh
+//! funcad model.fc Part part.stl
+//! funcad --watch model.fc Part part.stl
+//! funcad repl model.fc
+//! ```
+//!
+//! The file is written as STEP if its extension is `step` or `stp`, as OBJ if
+//! it's `obj` and as STL otherwise. With `--watch` the function is exported
+//! again every time the model's sources change. Errors are printed and the
+//! last good export is kept until the sources are fixed.
+//!
+//! `repl` reads expressions and function definitions from stdin a line at a
+//! time, as [`funcad::repl`] describes, printing results to stdout.
+
+use std::{
+    env,
+    fs::File,
+    io::{self, BufWriter},
+    path::Path,
+    process::ExitCode,
+};
+
+use funcad::{
+    export_solid_obj, export_solid_step, export_solid_stl, parse_all_files, EvalOptions, FQPath,
+    SolidSet, Value,
+};
+use typed_arena::Arena;
+
+const USAGE: &str =
+    "usage: funcad [--watch] <main.fc> <function> <out>\n       funcad repl <main.fc>";
+
+fn main() -> ExitCode {
+    let mut args: Vec<_> = env::args().skip(1).collect();
+    if let [command, main] = args.as_slice() {
+        if command == "repl" {
+            return repl(Path::new(main));
+        }
+    }
+
+    let watching = args.iter().any(|arg| arg == "--watch");
+    args.retain(|arg| arg != "--watch");
+    let [main, func_name, out] = args.as_slice() else {
+        eprintln!("{USAGE}");
+        return ExitCode::FAILURE;
+    };
+    let (main, out) = (Path::new(main), Path::new(out));
+
+    if watching {
+        return watch(main, func_name, out);
+    }
+
+    let arena = Arena::new();
+    let docs = match parse_all_files(&arena, main) {
+        Ok(docs) => docs,
+        Err(e) => {
+            eprintln!("{e}");
+            return ExitCode::FAILURE;
+        }
+    };
+    let main_name = main.file_stem().unwrap_or_default().to_string_lossy();
+    let doc_path = FQPath(vec![main_name.into()]);
+
+    let evaluation = EvalOptions::new().eval_function(&docs, &doc_path, func_name);
+    match evaluation.result {
+        Ok(val) => report(export(val, &evaluation.solids, func_name, out)),
+        Err(e) => {
+            eprintln!("{e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn repl(main: &Path) -> ExitCode {
+    let arena = Arena::new();
+    let mut docs = match parse_all_files(&arena, main) {
+        Ok(docs) => docs,
+        Err(e) => {
+            eprintln!("{e}");
+            return ExitCode::FAILURE;
+        }
+    };
+    let main_name = main.file_stem().unwrap_or_default().to_string_lossy();
+    let doc_path = FQPath(vec![main_name.into()]);
+
+    match funcad::repl(
+        &arena,
+        &mut docs,
+        &doc_path,
+        io::stdin().lock(),
+        io::stdout(),
+    ) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(feature = "watch")]
+fn watch(main: &Path, func_name: &str, out: &Path) -> ExitCode {
+    use std::ops::ControlFlow;
+
+    let res = funcad::watch(main, func_name, |res| {
+        match res {
+            Ok((val, solids)) => {
+                report(export(val, solids, func_name, out));
+            }
+            Err(e) => eprintln!("{e}"),
+        }
+        ControlFlow::Continue(())
+    });
+
+    match res {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(not(feature = "watch"))]
+fn watch(_: &Path, _: &str, _: &Path) -> ExitCode {
+    eprintln!("funcad was built without the watch feature, so --watch isn't supported");
+    ExitCode::FAILURE
+}
+
+/// Writes `val`, the value of `func_name`, to `out` in the format its
+/// extension names, returning what was written.
+fn export(val: Value, solids: &SolidSet, func_name: &str, out: &Path) -> Result<String, String> {
+    let Value::Solid(id) = val else {
+        return Err(format!("{func_name} is {val}, not a solid"));
+    };
+
+    let file = File::create(out).map_err(|e| e.to_string())?;
+    let writer = BufWriter::new(file);
+    let extension = out.extension().unwrap_or_default().to_ascii_lowercase();
+    let written = match extension.to_str() {
+        Some("step" | "stp") => export_solid_step(solids, &id, writer).map_err(|e| e.to_string()),
+        Some("obj") => export_solid_obj(solids, &id, writer).map_err(|e| e.to_string()),
+        _ => export_solid_stl(solids, &id, writer).map_err(|e| e.to_string()),
+    };
+
+    written.map(|()| format!("Exported {func_name} to {}", out.display()))
+}
+
+fn report(res: Result<String, String>) -> ExitCode {
+    match res {
+        Ok(msg) => {
+            println!("{msg}");
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("{e}");
+            ExitCode::FAILURE
+        }
+    }
+}
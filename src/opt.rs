@@ -0,0 +1,68 @@
+use crate::ast::*;
+
+/// Collapses arithmetic on number literals, like `2 * 3 + 1`, into a single
+/// [`Expr::Number`] spanning the folded source.
+///
+/// Only negation, `+`, `-`, `*`, `/` and `^` are folded. Function calls are
+/// never folded, even calls to built-ins, though their arguments are.
+///
+/// An operation that would fail when evaluated, dividing by zero or giving a
+/// result that isn't finite, is left as it is so evaluating it still reports
+/// the error.
+///
+/// Parsing and evaluation don't fold anything themselves, since evaluation
+/// caches function results anyway. Folding is an opt-in pass for hosts that
+/// evaluate the same bodies many times or inspect the tree, and
+/// [`fold_document_constants`] folds a whole document.
+pub fn fold_constants(mut expr: SpannedExpr) -> SpannedExpr {
+    ConstantFolder.visit_expr_mut(&mut expr);
+    expr
+}
+
+/// Like [`fold_constants`] but folds every argument default and function body
+/// in `doc` in place.
+pub fn fold_document_constants(doc: &mut Document) {
+    walk_document_mut(&mut ConstantFolder, doc);
+}
+
+struct ConstantFolder;
+
+impl<'src> VisitorMut<'src> for ConstantFolder {
+    fn visit_expr_mut(&mut self, expr: &mut SpannedExpr<'src>) {
+        walk_expr_mut(self, expr);
+
+        let folded = match &expr.inner {
+            Expr::Unary(unary) => match (unary.op, &unary.unit.inner) {
+                (UnaryOp::Neg, Expr::Number(number)) => Some(-number.val),
+                _ => None,
+            },
+            Expr::Binary(binary) => match (&binary.lhs.inner, &binary.rhs.inner) {
+                (Expr::Number(lhs), Expr::Number(rhs)) => {
+                    fold_binary(lhs.val, binary.op.inner, rhs.val)
+                }
+                _ => None,
+            },
+            _ => None,
+        };
+
+        if let Some(val) = folded {
+            expr.inner = Expr::Number(Number { val });
+        }
+    }
+}
+
+/// The result of a numeric operation, if it can be folded.
+///
+/// Comparisons give booleans, which have no literal to fold into.
+fn fold_binary(lhs: f64, op: BinaryOp, rhs: f64) -> Option<f64> {
+    let val = match op {
+        BinaryOp::Add => lhs + rhs,
+        BinaryOp::Sub => lhs - rhs,
+        BinaryOp::Mul => lhs * rhs,
+        BinaryOp::Div if rhs != 0. => lhs / rhs,
+        BinaryOp::Pow => lhs.powf(rhs),
+        _ => return None,
+    };
+
+    val.is_finite().then_some(val)
+}
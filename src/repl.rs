@@ -0,0 +1,48 @@
+use std::io::{self, BufRead, Write};
+
+use typed_arena::Arena;
+
+use crate::{eval_expr_str, parse_document, DocSet, FQPath};
+
+/// Reads lines from `input` and evaluates each in `doc_path`, writing the
+/// results to `output`.
+///
+/// A line of function definitions, such as `f(x) = x * 2`, adds them to the
+/// document for the rest of the session, replacing any function with the same
+/// name. If `docs` has no document at `doc_path` an empty one is added. Any
+/// other line is evaluated as an expression and its value written on a line of
+/// its own.
+///
+/// Errors are written to `output` and reading carries on until `input` ends.
+/// Blank lines are skipped. Each line is evaluated separately, so solids aren't
+/// shared between lines.
+pub fn repl<'src, R: BufRead, W: Write>(
+    source_arena: &'src Arena<u8>,
+    docs: &mut DocSet<'src>,
+    doc_path: &FQPath,
+    input: R,
+    mut output: W,
+) -> io::Result<()> {
+    for line in input.lines() {
+        let line = &*source_arena.alloc_str(line?.trim());
+        if line.is_empty() {
+            continue;
+        }
+
+        match parse_document(line) {
+            Ok(session) if !session.imports.is_empty() => {
+                writeln!(output, "Imports can't be added in a session")?;
+            }
+            Ok(session) => {
+                let doc = docs.entry(doc_path.clone()).or_default();
+                doc.funcs.extend(session.funcs);
+            }
+            Err(_) => match eval_expr_str(docs, doc_path, line) {
+                Ok(val) => writeln!(output, "{val}")?,
+                Err(e) => writeln!(output, "{e}")?,
+            },
+        }
+    }
+
+    Ok(())
+}
@@ -1,10 +1,19 @@
-use std::fmt::Display;
+use std::{
+    fmt::Display,
+    panic::{self, AssertUnwindSafe},
+};
 
-use truck_modeling::Solid;
+use pest::Span;
+use truck_meshalgo::{
+    analyzers::{CalcVolume, Collision, IncludingPointInDomain},
+    prelude::{Invertible, PolygonMesh},
+    tessellation::{MeshableShape, MeshedShape},
+};
+use truck_modeling::{builder, cgmath::SquareMatrix, Matrix4, Point3, Solid};
 
 use crate::{
     error::{EvalErrorType, EvalResult},
-    EvalError,
+    line_col, EvalError, FQPath,
 };
 
 /// A reference to a solid in [`SolidSet`].
@@ -18,16 +27,60 @@ pub enum SolidId {
 impl Display for SolidId {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            SolidId::Regular(id) => write!(f, "ID = {id}"),
+            SolidId::Regular(id) => write!(f, "{id}"),
             SolidId::Empty => write!(f, "Empty"),
             SolidId::Universal => write!(f, "Universal"),
         }
     }
 }
 
+/// A position in a document.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SourceLocation {
+    pub doc: FQPath,
+    pub line: usize,
+    pub col: usize,
+}
+
+impl SourceLocation {
+    pub(crate) fn new(span: Span, doc: &FQPath) -> Self {
+        let (line, col) = line_col(span.get_input(), span.start());
+        Self {
+            doc: doc.clone(),
+            line,
+            col,
+        }
+    }
+}
+
+/// The kind of operation that produced a solid.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SolidOp {
+    /// A call to the built-in function with this name. Any intermediate solids
+    /// the built-in creates are attributed to it too.
+    BuiltIn(String),
+    Negate,
+    Union,
+    Difference,
+    Intersection,
+}
+
+/// How a solid in a [`SolidSet`] was produced.
+#[derive(Clone, Debug, PartialEq)]
+pub struct OpDescriptor {
+    pub op: SolidOp,
+    /// Where the operation is in the source, if known.
+    pub location: Option<SourceLocation>,
+}
+
 /// A collection of [`Solid`]s.
 pub struct SolidSet {
     solids: Vec<Solid>,
+    // The operation that produced each solid, filled in once the operation
+    // that pushed it finishes.
+    provenance: Vec<Option<OpDescriptor>>,
+    // Problems that didn't stop evaluation but probably aren't intended.
+    warnings: Vec<String>,
     pub(crate) tolerance: f64,
 }
 
@@ -35,13 +88,15 @@ impl Default for SolidSet {
     fn default() -> Self {
         Self {
             solids: Default::default(),
+            provenance: Default::default(),
+            warnings: Default::default(),
             tolerance: Self::DEFAULT_TOLERANCE,
         }
     }
 }
 
 impl SolidSet {
-    const DEFAULT_TOLERANCE: f64 = 0.00001;
+    pub(crate) const DEFAULT_TOLERANCE: f64 = 0.00001;
 
     pub fn try_get<'src>(&self, id: &SolidId) -> EvalResult<'src, &Solid> {
         match id {
@@ -56,15 +111,65 @@ impl SolidSet {
         }
     }
 
-    pub(crate) fn push(&mut self, new: Solid) -> SolidId {
+    /// Adds `new` to the set, returning its id.
+    ///
+    /// This is how a [`crate::CustomBuiltIn`] hands back the solids it builds.
+    pub fn push(&mut self, new: Solid) -> SolidId {
         self.solids.push(new);
+        self.provenance.push(None);
         SolidId::Regular(self.solids.len() - 1)
     }
 
+    /// The number of solids in the set.
+    pub fn len(&self) -> usize {
+        self.solids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.solids.is_empty()
+    }
+
+    /// Attributes every solid pushed since the set had `start` solids, and
+    /// that isn't already attributed, to `op`.
+    ///
+    /// Operations nest, so the innermost operation records first and keeps
+    /// its solids.
+    pub(crate) fn record_since(&mut self, start: usize, op: OpDescriptor) {
+        for entry in self.provenance.iter_mut().skip(start) {
+            if entry.is_none() {
+                *entry = Some(op.clone());
+            }
+        }
+    }
+
+    pub(crate) fn warn(&mut self, warning: String) {
+        self.warnings.push(warning);
+    }
+
+    #[cfg(feature = "watch")]
+    pub(crate) fn clear_warnings(&mut self) {
+        self.warnings.clear();
+    }
+
+    /// Problems noticed while building solids that weren't errors, like a
+    /// feature that ended up partly outside the solid it was cut into.
+    pub fn warnings(&self) -> &[String] {
+        &self.warnings
+    }
+
+    /// Every solid in the set, in the order they were produced, along with the
+    /// operation that produced it.
+    pub fn provenance(&self) -> impl Iterator<Item = (SolidId, &OpDescriptor)> {
+        self.provenance
+            .iter()
+            .enumerate()
+            .filter_map(|(index, op)| Some((SolidId::Regular(index), op.as_ref()?)))
+    }
+
     pub(crate) fn push_or_empty(&mut self, new: Option<Solid>) -> SolidId {
         match new {
-            Some(new) => self.push(new),
-            None => SolidId::Empty,
+            Some(new) if !new.boundaries().is_empty() => self.push(new),
+            _ => SolidId::Empty,
         }
     }
 
@@ -80,15 +185,39 @@ impl SolidSet {
         }
     }
 
+    /// Pushes a copy of `solid` transformed by `mat`.
+    ///
+    /// Transforms with a negative determinant (reflections) turn a solid inside
+    /// out, so the copy is re-oriented to keep its faces pointing outward.
+    pub(crate) fn transform<'src>(
+        &mut self,
+        solid: &SolidId,
+        mat: Matrix4,
+    ) -> EvalResult<'src, SolidId> {
+        match solid {
+            SolidId::Regular(_) => {
+                let mut new = builder::transformed(self.try_get(solid)?, mat);
+                if mat.determinant() < 0. {
+                    new.not();
+                }
+                Ok(self.push(new))
+            }
+            SolidId::Empty | SolidId::Universal => Ok(*solid),
+        }
+    }
+
     pub(crate) fn union<'src>(
         &mut self,
         lhs: &SolidId,
         rhs: &SolidId,
     ) -> EvalResult<'src, SolidId> {
         match (lhs, rhs) {
+            // truck can't combine a solid with itself, and there's nothing to
+            // combine anyway.
+            (SolidId::Regular(_), SolidId::Regular(_)) if lhs == rhs => Ok(*lhs),
             (SolidId::Regular(_), SolidId::Regular(_)) => {
-                let new =
-                    truck_shapeops::or(self.try_get(lhs)?, self.try_get(rhs)?, self.tolerance);
+                let new = or(self.try_get(lhs)?, self.try_get(rhs)?, self.tolerance)
+                    .ok_or_else(|| geometry_operation_failed("Union"))?;
                 Ok(self.push_or_empty(new))
             }
 
@@ -103,9 +232,10 @@ impl SolidSet {
         rhs: &SolidId,
     ) -> EvalResult<'src, SolidId> {
         match (lhs, rhs) {
+            (SolidId::Regular(_), SolidId::Regular(_)) if lhs == rhs => Ok(*lhs),
             (SolidId::Regular(_), SolidId::Regular(_)) => {
-                let new =
-                    truck_shapeops::and(self.try_get(lhs)?, self.try_get(rhs)?, self.tolerance);
+                let new = and(self.try_get(lhs)?, self.try_get(rhs)?, self.tolerance)
+                    .ok_or_else(|| geometry_operation_failed("Intersection"))?;
                 Ok(self.push_or_empty(new))
             }
 
@@ -114,16 +244,39 @@ impl SolidSet {
         }
     }
 
+    /// Like [`SolidSet::intersection`] but with a solid that isn't in the set.
+    ///
+    /// Only the result is pushed, so temporary solids like clipping regions
+    /// aren't kept around after the operation.
+    pub(crate) fn intersection_with<'src>(
+        &mut self,
+        lhs: &SolidId,
+        rhs: &Solid,
+    ) -> EvalResult<'src, SolidId> {
+        match lhs {
+            SolidId::Regular(_) => {
+                let new = and(self.try_get(lhs)?, rhs, self.tolerance)
+                    .ok_or_else(|| geometry_operation_failed("Intersection"))?;
+                Ok(self.push_or_empty(new))
+            }
+
+            SolidId::Empty => Ok(SolidId::Empty),
+            SolidId::Universal => Ok(self.push(rhs.clone())),
+        }
+    }
+
     pub(crate) fn difference<'src>(
         &mut self,
         lhs: &SolidId,
         rhs: &SolidId,
     ) -> EvalResult<'src, SolidId> {
         match (lhs, rhs) {
+            (SolidId::Regular(_), SolidId::Regular(_)) if lhs == rhs => Ok(SolidId::Empty),
             (SolidId::Regular(_), SolidId::Regular(_)) => {
                 let mut rhs = self.try_get(rhs)?.clone();
                 rhs.not();
-                let new = truck_shapeops::and(self.try_get(lhs)?, &rhs, self.tolerance);
+                let new = and(self.try_get(lhs)?, &rhs, self.tolerance)
+                    .ok_or_else(|| geometry_operation_failed("Difference"))?;
                 Ok(self.push_or_empty(new))
             }
 
@@ -135,3 +288,97 @@ impl SolidSet {
         }
     }
 }
+
+fn geometry_operation_failed<'src>(op: &'static str) -> EvalError<'src> {
+    EvalError {
+        error_type: EvalErrorType::GeometryOperationFailed { op },
+        context_entries: Vec::default(),
+    }
+}
+
+/// Like [`truck_shapeops::and`] but also handles solids whose boundaries don't
+/// cross, which [`combine_apart`] describes.
+///
+/// See [`truck_boolean`] for what the result means.
+fn and(lhs: &Solid, rhs: &Solid, tolerance: f64) -> Option<Option<Solid>> {
+    truck_boolean(
+        || truck_shapeops::and(lhs, rhs, tolerance),
+        || combine_apart(lhs, rhs, true, tolerance),
+    )
+}
+
+/// Like [`truck_shapeops::or`] but also handles solids whose boundaries don't
+/// cross, which [`combine_apart`] describes.
+///
+/// See [`truck_boolean`] for what the result means.
+fn or(lhs: &Solid, rhs: &Solid, tolerance: f64) -> Option<Option<Solid>> {
+    truck_boolean(
+        || truck_shapeops::or(lhs, rhs, tolerance),
+        || combine_apart(lhs, rhs, false, tolerance),
+    )
+}
+
+/// The result of one of truck's boolean operations, falling back to `apart`
+/// if it failed, panicked or gave a solid without any boundary.
+///
+/// The inner `None` is an empty result. The outer `None` means truck panicked,
+/// as it does on some overlapping coplanar faces, and `apart` couldn't combine
+/// the solids either, so there's no telling what the result should be.
+fn truck_boolean(
+    op: impl FnOnce() -> Option<Solid>,
+    apart: impl FnOnce() -> Option<Solid>,
+) -> Option<Option<Solid>> {
+    let res = panic::catch_unwind(AssertUnwindSafe(op));
+    if let Ok(Some(solid)) = &res {
+        if !solid.boundaries().is_empty() {
+            return res.ok();
+        }
+    }
+
+    match (apart(), res) {
+        (Some(solid), _) => Some(Some(solid)),
+        (None, Ok(_)) => Some(None),
+        (None, Err(_)) => None,
+    }
+}
+
+/// Combines two solids whose boundaries don't cross, or returns `None` if
+/// they do.
+///
+/// truck finds the result of a boolean by following the curves where
+/// boundaries cross, so it can't combine a solid with one inside a cavity of
+/// it or off to the side. Then each boundary shell is wholly inside or
+/// outside the other solid, and the result is made of the shells of each
+/// that are `inside` the other, for an intersection, or outside it, for a
+/// union. A solid can be given inverted to take a difference.
+fn combine_apart(lhs: &Solid, rhs: &Solid, inside: bool, tolerance: f64) -> Option<Solid> {
+    let lhs_mesh = lhs.triangulation(tolerance).to_polygon();
+    let rhs_mesh = rhs.triangulation(tolerance).to_polygon();
+    if lhs_mesh.collide_with(&rhs_mesh).is_some() {
+        return None;
+    }
+
+    let mut shells = Vec::new();
+    for (solid, other) in [(lhs, rhs_mesh), (rhs, lhs_mesh)] {
+        let contains = containment(other);
+        for shell in solid.boundaries() {
+            let point = shell.vertex_iter().next()?.point();
+            if contains(point) == inside {
+                shells.push(shell.clone());
+            }
+        }
+    }
+    Some(Solid::new(shells))
+}
+
+/// Whether points are inside the solid `mesh` is the boundary of.
+///
+/// A mesh with a negative volume is of an inverted solid, which contains
+/// everything outside of its boundary.
+fn containment(mut mesh: PolygonMesh) -> impl Fn(Point3) -> bool {
+    let inverted = mesh.volume() < 0.;
+    if inverted {
+        mesh.invert();
+    }
+    move |point| mesh.inside(point) != inverted
+}
@@ -0,0 +1,167 @@
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, HashMap, HashSet},
+    fs,
+    io::Cursor,
+    ops::ControlFlow,
+    path::{Path, PathBuf},
+    sync::mpsc,
+    time::Duration,
+};
+
+use notify::{RecursiveMode, Watcher};
+use typed_arena::Arena;
+
+use crate::{
+    eval::{CachedValues, EvalCache, EvalContext, Scope},
+    parse_all, DocSet, FQPath, SolidSet, Value,
+};
+
+/// How long to wait for more changes once one arrives, so saving a file in
+/// several writes only causes one run.
+const SETTLE_TIME: Duration = Duration::from_millis(50);
+
+/// Evaluates `func_name` in the file `main`, then again every time `main` or a
+/// file it imports, directly or not, changes, passing each result to `on_run`.
+///
+/// Parse and evaluation errors are passed to `on_run` as their messages rather
+/// than ending the watch, so fixing the source causes the next run. While the
+/// sources don't parse, any change to a `.fc` file in or below the directory
+/// of `main` causes a run.
+///
+/// Every run parses from scratch but reuses the values cached by the last run
+/// that parsed, except those from documents that changed or import one that
+/// did, directly or not. The solids of earlier runs are kept for as long as
+/// values referring to them might be, so `on_run` may be passed a set holding
+/// solids that aren't part of its value. Watching ends when `on_run` returns
+/// [`ControlFlow::Break`] and is otherwise only left on an error setting it
+/// up.
+pub fn watch<F>(main: &Path, func_name: &str, mut on_run: F) -> notify::Result<()>
+where
+    F: FnMut(Result<(Value, &SolidSet), String>) -> ControlFlow<()>,
+{
+    // Changes are reported with absolute paths.
+    let main = fs::canonicalize(main).map_err(notify::Error::io)?;
+    let Some(dir) = main.parent() else {
+        return Err(notify::Error::path_not_found().add_path(main));
+    };
+
+    let (sender, events) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(sender)?;
+    watcher.watch(dir, RecursiveMode::Recursive)?;
+
+    let mut last_run = None;
+    loop {
+        let (flow, sources) = run(&main, dir, func_name, &mut last_run, &mut on_run);
+        if flow.is_break() {
+            return Ok(());
+        }
+
+        loop {
+            let Ok(event) = events.recv() else {
+                return Ok(());
+            };
+            let changed = event.is_ok_and(|event| {
+                !event.kind.is_access()
+                    && event.paths.iter().any(|path| match &sources {
+                        Some(sources) => sources.contains(path),
+                        None => path.extension().is_some_and(|ext| ext == "fc"),
+                    })
+            });
+            if changed {
+                break;
+            }
+        }
+        while events.recv_timeout(SETTLE_TIME).is_ok() {}
+    }
+}
+
+/// What a run that parsed leaves for the next one to reuse.
+struct LastRun {
+    /// The source of every document read.
+    sources: HashMap<FQPath, Vec<u8>>,
+    values: CachedValues,
+}
+
+/// Parses and evaluates once, returning whether to carry on and the files
+/// read, if they all parsed.
+fn run<F>(
+    main: &Path,
+    dir: &Path,
+    func_name: &str,
+    last_run: &mut Option<LastRun>,
+    on_run: &mut F,
+) -> (ControlFlow<()>, Option<HashSet<PathBuf>>)
+where
+    F: FnMut(Result<(Value, &SolidSet), String>) -> ControlFlow<()>,
+{
+    let main_name = main.file_stem().unwrap_or_default().to_string_lossy();
+    let doc_path = FQPath(vec![main_name.into()]);
+
+    let read = RefCell::new(HashMap::new());
+    let source_arena = Arena::new();
+    let parsed = parse_all(&source_arena, &doc_path, |path| {
+        let src = fs::read(path.file_path(dir))?;
+        read.borrow_mut().insert(path.clone(), src.clone());
+        Ok(Cursor::new(src))
+    });
+    let docs = match parsed {
+        Ok(docs) => docs,
+        Err(e) => return (on_run(Err(e.to_string())), None),
+    };
+    let sources = read.into_inner();
+    let paths = docs.keys().map(|path| path.file_path(dir)).collect();
+
+    let values = match last_run.take() {
+        Some(LastRun {
+            sources: last_sources,
+            mut values,
+        }) => {
+            let stale = stale_docs(&docs, |path| last_sources.get(path) != sources.get(path));
+            values.retain_docs(|path| docs.contains_key(path) && !stale.contains(path));
+            values
+        }
+        None => CachedValues::default(),
+    };
+
+    let mut cache = EvalCache::new(&docs).with_cached(values);
+    let scope = Scope::FuncCall {
+        name: func_name.into(),
+        args: BTreeMap::new(),
+        doc_path,
+    };
+    let res = cache.eval_scope(&scope, &EvalContext::default());
+    let flow = on_run(
+        res.map(|val| (val, cache.solids()))
+            .map_err(|e| e.to_string()),
+    );
+
+    *last_run = Some(LastRun {
+        sources,
+        values: cache.into_cached(),
+    });
+    (flow, Some(paths))
+}
+
+/// The documents in `docs` that `changed`, along with every document that
+/// imports one of them, directly or not.
+fn stale_docs(docs: &DocSet, changed: impl Fn(&FQPath) -> bool) -> HashSet<FQPath> {
+    let mut stale: HashSet<_> = docs.keys().filter(|path| changed(path)).cloned().collect();
+    loop {
+        let importers: Vec<_> = docs
+            .iter()
+            .filter(|(path, doc)| {
+                !stale.contains(*path)
+                    && doc.imports.values().any(|import| {
+                        path.import_path(import)
+                            .is_ok_and(|imported| stale.contains(&imported))
+                    })
+            })
+            .map(|(path, _)| path.clone())
+            .collect();
+        if importers.is_empty() {
+            return stale;
+        }
+        stale.extend(importers);
+    }
+}
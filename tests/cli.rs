@@ -0,0 +1,113 @@
+use std::{
+    env, fs,
+    io::{BufRead, BufReader, Write},
+    process::{self, Command, Stdio},
+    sync::mpsc,
+    thread,
+    time::Duration,
+};
+
+const FUNCAD: &str = env!("CARGO_BIN_EXE_funcad");
+
+/// A function is exported once in the format the output's extension names.
+#[test]
+fn cli_export_ok() {
+    let dir = env::temp_dir().join(format!("funcad_cli_{}", process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let main = dir.join("main.fc");
+    fs::write(&main, "a = Cube()").unwrap();
+    let out = dir.join("a.step");
+
+    let output = Command::new(FUNCAD)
+        .args([main.as_os_str(), "a".as_ref(), out.as_os_str()])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(fs::read_to_string(&out)
+        .unwrap()
+        .starts_with("ISO-10303-21;"));
+
+    fs::write(&main, "a = 1").unwrap();
+    let output = Command::new(FUNCAD)
+        .args([main.as_os_str(), "a".as_ref(), out.as_os_str()])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+/// `repl` evaluates lines piped to it against the model, and a session of
+/// expressions and definitions keeps going after an error.
+#[test]
+fn cli_repl_ok() {
+    let dir = env::temp_dir().join(format!("funcad_cli_repl_{}", process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let main = dir.join("main.fc");
+    fs::write(&main, "a = 1").unwrap();
+
+    let mut child = Command::new(FUNCAD)
+        .arg("repl")
+        .arg(&main)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let input = "1 + 1\n\nf(x) = x * 2\nf(3) + a\nb\nimport other\nf = 5\nf\n";
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+
+    let output = String::from_utf8(output.stdout).unwrap();
+    assert!(output.starts_with("2\n7\n"));
+    assert!(output.contains("Imports can't be added in a session\n"));
+    assert!(output.ends_with("\n5\n"));
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+/// With `--watch`, touching the source exports the function again, and an
+/// error is reported without ending the watch.
+#[cfg(feature = "watch")]
+#[test]
+fn cli_watch_ok() {
+    let dir = env::temp_dir().join(format!("funcad_cli_watch_{}", process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let main = dir.join("main.fc");
+    fs::write(&main, "a = Cube()").unwrap();
+    let out = dir.join("a.stl");
+
+    let mut child = Command::new(FUNCAD)
+        .arg("--watch")
+        .args([main.as_os_str(), "a".as_ref(), out.as_os_str()])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+    let (sender, lines) = mpsc::channel();
+    let stdout = BufReader::new(child.stdout.take().unwrap());
+    thread::spawn(move || {
+        for line in stdout.lines() {
+            if sender.send(line.unwrap()).is_err() {
+                break;
+            }
+        }
+    });
+    let next = || lines.recv_timeout(Duration::from_secs(10)).unwrap();
+
+    let exported = format!("Exported a to {}", out.display());
+    assert_eq!(next(), exported);
+
+    fs::write(&main, "a = Missing()").unwrap();
+    fs::write(&main, "a = Box(1, 2, 3)").unwrap();
+    assert_eq!(next(), exported);
+
+    child.kill().unwrap();
+    child.wait().unwrap();
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -1,7 +1,11 @@
 #![feature(assert_matches)]
 use std::assert_matches::assert_matches;
 
+use std::collections::{BTreeMap, HashMap};
+
 use funcad::*;
+use truck_meshalgo::analyzers::CalcVolume;
+use truck_modeling::{builder, EuclideanSpace, Point3, Vector3};
 use typed_arena::Arena;
 
 mod util;
@@ -75,6 +79,42 @@ fn eval_call_args_shadow_and_default_ok() {
     assert_matches!(eval_result, Ok(Value::Number(2.)))
 }
 
+/// Positional args can be followed by named ones, but not name an arg that
+/// was already given by position.
+#[test]
+fn eval_call_mixed_args_ok() {
+    let mut set = FileSet::default();
+    set.insert(
+        "main",
+        "a = b(1, b=2)\n\
+         c = b(1, a=2)\n\
+         d = Diagonal(Box(1, 2, z=2))\n\
+         b(a, b) = a + b",
+    );
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let eval_result = eval_function(&doc_set, &entry, "a");
+    assert_matches!(eval_result, Ok(Value::Number(3.)));
+
+    let eval_result = eval_function(&doc_set, &entry, "c");
+    assert_matches!(
+        eval_result,
+        Err(EvalError {
+            error_type: EvalErrorType::ArgSuppliedTwice { .. },
+            ..
+        })
+    );
+
+    let eval_result = eval_function(&doc_set, &entry, "d");
+    assert_matches!(eval_result, Ok(Value::Number(diag)) if (diag - 3.).abs() < 1e-9);
+}
+
 /// Calling a function in an import.
 #[test]
 fn eval_call_in_import_ok() {
@@ -93,9 +133,32 @@ fn eval_call_in_import_ok() {
     assert_matches!(eval_result, Ok(Value::Number(2.)))
 }
 
-/// Division by zero.
+/// Overflow.
 #[test]
 fn eval_nan_errors() {
+    let mut set = FileSet::default();
+    set.insert("main", "a = 1e308 * 10");
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let eval_result = eval_function(&doc_set, &entry, "a");
+    assert_matches!(
+        eval_result,
+        Err(EvalError {
+            error_type: EvalErrorType::NumExprNotFinite { .. },
+            ..
+        })
+    )
+}
+
+/// Division by zero.
+#[test]
+fn eval_division_by_zero_errors() {
     let mut set = FileSet::default();
     set.insert("main", "a = 1 / 0");
 
@@ -110,7 +173,7 @@ fn eval_nan_errors() {
     assert_matches!(
         eval_result,
         Err(EvalError {
-            error_type: EvalErrorType::NumExprNotFinite,
+            error_type: EvalErrorType::DivisionByZero { .. },
             ..
         })
     )
@@ -155,3 +218,995 @@ fn eval_multiple_calls_ok() {
     let eval_result = eval_function(&doc_set, &entry, "a");
     assert_matches!(eval_result, Ok(Value::Number(2.)))
 }
+
+/// List literals evaluate each item.
+#[test]
+fn eval_list_ok() {
+    let mut set = FileSet::default();
+    set.insert("main", "a = [1, b + 1, []]\nb = 1");
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let eval_result = eval_function(&doc_set, &entry, "a");
+    assert_eq!(
+        eval_result.unwrap(),
+        Value::List(vec![
+            Value::Number(1.),
+            Value::Number(2.),
+            Value::List(vec![])
+        ])
+    );
+}
+
+/// Lists can't be negated.
+#[test]
+fn eval_list_negate_errors() {
+    let mut set = FileSet::default();
+    set.insert("main", "a = -[1]");
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let eval_result = eval_function(&doc_set, &entry, "a");
+    assert_matches!(
+        eval_result,
+        Err(EvalError {
+            error_type: EvalErrorType::UnaryOpWrongType { .. },
+            ..
+        })
+    );
+}
+
+/// Functions in a document shadow built-ins of the same name.
+#[test]
+fn eval_local_shadows_built_in_ok() {
+    let mut set = FileSet::default();
+    set.insert("main", "a = Sin(30)\nSin(x) = x + 1");
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let eval_result = eval_function(&doc_set, &entry, "a");
+    assert_matches!(eval_result, Ok(Value::Number(31.)))
+}
+
+/// Cache shares a solid across calls that differ only in arguments the cached
+/// expression doesn't refer to.
+#[test]
+fn eval_cache_shares_solid() {
+    let mut set = FileSet::default();
+    set.insert(
+        "main",
+        "a = [f(1), f(2), g(1), g(2)]\n\
+         f(x) = Cache(Cube() + Translate(Cube(), 3))\n\
+         g(x) = Cache(Cube(x) + Translate(Cube(), 3))",
+    );
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let eval_result = eval_function(&doc_set, &entry, "a");
+    assert_matches!(
+        eval_result,
+        Ok(Value::List(ref items)) if matches!(
+            items[..],
+            [Value::Solid(f1), Value::Solid(f2), Value::Solid(g1), Value::Solid(g2)]
+                if f1 == f2 && g1 != g2
+        )
+    );
+}
+
+/// Numbers in error context are rounded to hide floating point noise.
+#[test]
+fn eval_error_rounds_numbers() {
+    let mut set = FileSet::default();
+    set.insert(
+        "main",
+        "a = Assert(0, [0.1 + 0.2, 2 / 3, 1e21, -123456789012.5])",
+    );
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let eval_result = eval_function(&doc_set, &entry, "a");
+    assert_matches!(eval_result, Err(_));
+    let message = eval_result.unwrap_err().to_string();
+    assert!(
+        message.contains("built-in function \"Assert\" with condition = 0, value = [0.3, 0.6666666667, 1000000000000000000000, -123456789000]"),
+        "{message}"
+    );
+}
+
+/// Values display the way they'd be written, with solids shown by ID.
+#[test]
+fn value_display() {
+    assert_eq!(format!("{}", Value::Number(1.5)), "1.5");
+    assert_eq!(
+        format!("{}", Value::Number(0.1234567890123)),
+        "0.1234567890123"
+    );
+    assert_eq!(
+        format!("{}", Value::Number(123456789012.5)),
+        "123456789012.5"
+    );
+    assert_eq!(format!("{}", Value::Solid(SolidId::Regular(3))), "solid#3");
+    assert_eq!(format!("{}", Value::Solid(SolidId::Empty)), "solid#Empty");
+    assert_eq!(
+        format!(
+            "{}",
+            Value::List(vec![Value::Bool(true), Value::String("a".into())])
+        ),
+        "[true, \"a\"]"
+    );
+}
+
+/// Values can be unwrapped without matching on them.
+#[test]
+fn value_accessors() {
+    let num = Value::Number(1.5);
+    let solid = Value::Solid(SolidId::Regular(3));
+
+    assert_eq!(num.as_number(), Some(1.5));
+    assert_eq!(solid.as_number(), None);
+    assert_eq!(solid.as_solid(), Some(SolidId::Regular(3)));
+    assert_eq!(num.as_solid(), None);
+
+    assert_matches!(num.clone().try_into_number(), Ok(1.5));
+    assert_matches!(solid.clone().try_into_solid(), Ok(SolidId::Regular(3)));
+    assert_matches!(
+        solid.try_into_number(),
+        Err(EvalError {
+            error_type: EvalErrorType::ArgWrongType {
+                expected: "number",
+                got: "solid",
+                ..
+            },
+            ..
+        })
+    );
+    assert_matches!(
+        num.try_into_solid(),
+        Err(EvalError {
+            error_type: EvalErrorType::ArgWrongType {
+                expected: "solid",
+                got: "number",
+                ..
+            },
+            ..
+        })
+    );
+}
+
+/// Param reads a host parameter or falls back to its default.
+#[test]
+fn eval_param_ok() {
+    let mut set = FileSet::default();
+    set.insert(
+        "main",
+        "a = [Param(\"width\", 1), Param(\"height\", 2)]\nb = Param(3, 4)",
+    );
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let params = HashMap::from([("width".into(), Value::Number(10.))]);
+    let eval_result = EvalOptions::new()
+        .with_params(params)
+        .eval_function(&doc_set, &entry, "a")
+        .result;
+    assert_matches!(
+        eval_result,
+        Ok(Value::List(items)) if items == vec![Value::Number(10.), Value::Number(2.)]
+    );
+
+    let eval_result = eval_function(&doc_set, &entry, "b");
+    assert_matches!(
+        eval_result,
+        Err(EvalError {
+            error_type: EvalErrorType::ArgWrongType { .. },
+            ..
+        })
+    );
+}
+
+/// Geometry can be evaluated to a tolerance other than the default, and the
+/// solids come back at that tolerance.
+#[test]
+fn eval_options_tolerance_ok() {
+    let mut set = FileSet::default();
+    set.insert(
+        "main",
+        "a = Cube() + Translate(Cube(), 0.5, 0.5, 0.5)\nb = Cylinder(1, 1)",
+    );
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    for tolerance in [0.001, 0.000001] {
+        let eval_result = EvalOptions::new()
+            .with_tolerance(tolerance)
+            .eval_function(&doc_set, &entry, "a")
+            .result;
+        assert_matches!(eval_result, Ok(Value::Solid(SolidId::Regular(_))));
+    }
+
+    // Meshes of the solids are only as fine as the tolerance they were
+    // evaluated at.
+    let triangles = |tolerance| {
+        let Evaluation {
+            result: eval_result,
+            solids,
+            ..
+        } = EvalOptions::new()
+            .with_tolerance(tolerance)
+            .eval_function(&doc_set, &entry, "b");
+        let Ok(Value::Solid(id)) = eval_result else {
+            panic!("expected a solid, got {eval_result:?}");
+        };
+        tessellate_solid(&solids, &id).unwrap().tri_faces().len()
+    };
+    assert!(triangles(0.1) < triangles(0.01));
+
+    for tolerance in [0., -0.001, f64::NAN, f64::INFINITY] {
+        let eval_result = EvalOptions::new()
+            .with_tolerance(tolerance)
+            .eval_function(&doc_set, &entry, "a")
+            .result;
+        assert_matches!(
+            eval_result,
+            Err(EvalError {
+                error_type: EvalErrorType::InvalidDimension { .. },
+                ..
+            })
+        );
+    }
+}
+
+/// Each solid records the operation that produced it and where.
+#[test]
+fn eval_solid_provenance() {
+    let mut set = FileSet::default();
+    set.insert("main", "a = Cube() + Translate(Cube(), 0.5, 0.5, 0.5)");
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let Evaluation {
+        result: eval_result,
+        solids,
+        ..
+    } = EvalOptions::new().eval_function(&doc_set, &entry, "a");
+    assert_matches!(eval_result, Ok(Value::Solid(SolidId::Regular(2))));
+
+    let provenance: Vec<_> = solids
+        .provenance()
+        .map(|(id, desc)| {
+            let location = desc.location.as_ref().unwrap();
+            (id, desc.op.clone(), location.line, location.col)
+        })
+        .collect();
+    assert_eq!(
+        provenance,
+        vec![
+            (SolidId::Regular(0), SolidOp::BuiltIn("Cube".into()), 1, 5),
+            (
+                SolidId::Regular(1),
+                SolidOp::BuiltIn("Translate".into()),
+                1,
+                14
+            ),
+            (SolidId::Regular(2), SolidOp::Union, 1, 5),
+        ]
+    );
+}
+
+/// The geometry of a solid result can be fetched from the returned set.
+#[test]
+fn eval_options_solids_fetches_solid() {
+    let mut set = FileSet::default();
+    set.insert("main", "a = Cube()");
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let Evaluation {
+        result: eval_result,
+        solids,
+        ..
+    } = EvalOptions::new().eval_function(&doc_set, &entry, "a");
+    let Ok(Value::Solid(id)) = eval_result else {
+        panic!("expected a solid, got {eval_result:?}");
+    };
+
+    let solid = solids.try_get(&id);
+    assert_matches!(solid, Ok(solid) if solid.boundaries()[0].len() == 6);
+}
+
+/// Combining a solid with itself doesn't build anything new, and subtracting
+/// it from itself leaves nothing.
+#[test]
+fn eval_same_solid_ops() {
+    let mut set = FileSet::default();
+    set.insert(
+        "main",
+        "a = Cube() - Cube()\nb = Cube() + Cube()\nc = Cube() * Cube()",
+    );
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let eval_result = eval_function(&doc_set, &entry, "a");
+    assert_matches!(eval_result, Ok(Value::Solid(SolidId::Empty)));
+
+    for name in ["b", "c"] {
+        let Evaluation {
+            result: eval_result,
+            solids,
+            ..
+        } = EvalOptions::new().eval_function(&doc_set, &entry, name);
+        assert_matches!(eval_result, Ok(Value::Solid(SolidId::Regular(0))));
+        assert_eq!(solids.len(), 1);
+    }
+}
+
+/// A bare expression can use the functions of the document it's evaluated in.
+#[test]
+fn eval_expr_str_ok() {
+    let mut set = FileSet::default();
+    set.insert("main", "a = 2\nf(x) = x * 10");
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let eval_result = eval_expr_str(&doc_set, &entry, "1 + f(a)");
+    assert_matches!(eval_result, Ok(Value::Number(21.)));
+
+    let eval_result = eval_expr_str(&doc_set, &entry, "1 + Sin(30)");
+    assert_matches!(eval_result, Ok(Value::Number(val)) if (val - 1.5).abs() < 1e-9);
+
+    let eval_result = eval_expr_str(&doc_set, &entry, "1 + f(a) b = 2");
+    assert_matches!(
+        eval_result,
+        Err(EvalError {
+            error_type: EvalErrorType::Parse(_),
+            ..
+        })
+    );
+}
+
+/// Empty is the identity of union.
+#[test]
+fn eval_empty_accumulator_ok() {
+    let mut set = FileSet::default();
+    set.insert("main", "a = [Empty(), Empty() + Cube()]");
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let eval_result = eval_function(&doc_set, &entry, "a");
+    assert_matches!(
+        eval_result,
+        Ok(Value::List(ref items))
+            if items == &[Value::Solid(SolidId::Empty), Value::Solid(SolidId::Regular(0))]
+    );
+}
+
+/// Empty and Universal can be written without parentheses. Carving from
+/// Universal leaves an inside out solid that's unbounded, so it can't be
+/// exported until it's cut down by a bounded one.
+#[test]
+fn eval_empty_and_universal_ok() {
+    let mut set = FileSet::default();
+    set.insert(
+        "main",
+        "a = Cube() + Empty\n\
+         b = Cube() - Universal\n\
+         c = Universal - Cube()\n\
+         d = Cube() * Universal\n\
+         e = Volume(c * Box(4, 4, 4))",
+    );
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let Evaluation {
+        result: eval_result,
+        solids,
+        ..
+    } = EvalOptions::new().eval_function(&doc_set, &entry, "a");
+    assert_matches!(eval_result, Ok(Value::Solid(SolidId::Regular(0))));
+    assert_eq!(solids.len(), 1);
+
+    let eval_result = eval_function(&doc_set, &entry, "b");
+    assert_matches!(eval_result, Ok(Value::Solid(SolidId::Empty)));
+
+    let Evaluation {
+        result: eval_result,
+        solids,
+        ..
+    } = EvalOptions::new().eval_function(&doc_set, &entry, "c");
+    let Ok(Value::Solid(id)) = eval_result else {
+        panic!("expected a solid, got {eval_result:?}");
+    };
+    assert_matches!(id, SolidId::Regular(_));
+    assert_matches!(tessellate_solid(&solids, &id), Err(_));
+    assert_matches!(export_solid_stl(&solids, &id, Vec::new()), Err(_));
+    assert_matches!(export_solid_step(&solids, &id, Vec::new()), Err(_));
+    assert_matches!(tessellate_solid(&solids, &SolidId::Universal), Err(_));
+
+    let eval_result = eval_function(&doc_set, &entry, "d");
+    assert_matches!(eval_result, Ok(Value::Solid(SolidId::Regular(0))));
+
+    let eval_result = eval_function(&doc_set, &entry, "e");
+    assert_matches!(eval_result, Ok(Value::Number(vol)) if (vol - 63.).abs() < 1e-6);
+}
+
+/// Deep self-recursion in tail position iterates rather than growing the
+/// stack, so a long cycle of calls is still caught.
+#[test]
+fn eval_deep_tail_recursion_errors() {
+    let mut set = FileSet::default();
+    set.insert(
+        "main",
+        "a = f(0)\nf(n) = f(Switch(IsInt((n + 1) / 100000), {\"true\": 0, \"false\": n + 1}))",
+    );
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let eval_result = eval_function(&doc_set, &entry, "a");
+    assert_matches!(
+        eval_result,
+        Err(EvalError {
+            error_type: EvalErrorType::InfiniteRecursion,
+            ..
+        })
+    );
+}
+
+/// Self-recursion through the cases of Switch and the bodies of lets is still
+/// in tail position, so recursion to a base case can go deeper than the stack.
+#[test]
+fn eval_deep_tail_recursion_ok() {
+    let mut set = FileSet::default();
+    set.insert(
+        "main",
+        "a = count(100000, 0)\n\
+         count(n, acc) = Switch(n == 0, {\"true\": acc, \"false\": let m = n - 1 in count(m, acc + 1)})\n\
+         b = down(10000)\n\
+         down(n) = let m = n - 1 in Switch(m < 0, {\"true\": n, \"false\": down(m)})",
+    );
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let eval_result = eval_function(&doc_set, &entry, "a");
+    assert_matches!(eval_result, Ok(Value::Number(100000.)));
+
+    let eval_result = eval_function(&doc_set, &entry, "b");
+    assert_matches!(eval_result, Ok(Value::Number(0.)));
+}
+
+/// A solid can be accumulated from `Empty()` by tail recursion a thousand
+/// unions deep. Only every hundredth union adds a cube, since unioning a
+/// thousand of them would be slow.
+#[test]
+fn eval_deep_tail_recursion_solids_ok() {
+    let mut set = FileSet::default();
+    set.insert(
+        "main",
+        "a = row(1000, Empty())\n\
+         row(n, acc) = Switch(n == 0, {\"true\": acc, \"false\": row(n - 1, acc + cube(n))})\n\
+         cube(n) = Switch(IsInt(n / 100), {\"true\": Translate(Cube(), 2 * n, 0, 0), \"false\": Empty()})",
+    );
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let Evaluation {
+        result: eval_result,
+        solids,
+        ..
+    } = EvalOptions::new().eval_function(&doc_set, &entry, "a");
+    let Ok(Value::Solid(id)) = eval_result else {
+        panic!("expected a solid, got {eval_result:?}");
+    };
+    assert_matches!(solids.try_get(&id), Ok(solid) if solid.boundaries().len() == 10);
+}
+
+/// Switch evaluates only the case matching its key, and a bool key picks the
+/// "true" or "false" case.
+#[test]
+fn eval_switch_ok() {
+    let mut set = FileSet::default();
+    set.insert(
+        "main",
+        "a = [f(\"small\"), f(\"large\"), f(\"other\")]\n\
+         f(size) = Switch(size, {\"small\": 1, \"large\": 2, \"huge\": Assert(0, 3)}, 0)\n\
+         b = Switch(\"other\", {\"small\": 1})\n\
+         c = [Switch(1 < 2, {\"true\": 1, \"false\": Assert(0, 2)}), Switch(1 > 2, {\"true\": 1}, 3)]",
+    );
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let eval_result = eval_function(&doc_set, &entry, "a");
+    assert_matches!(
+        eval_result,
+        Ok(Value::List(ref items))
+            if items == &[Value::Number(1.), Value::Number(2.), Value::Number(0.)]
+    );
+
+    let eval_result = eval_function(&doc_set, &entry, "b");
+    assert_matches!(
+        eval_result,
+        Err(EvalError {
+            error_type: EvalErrorType::NoMatchingCase { ref key },
+            ..
+        }) if key == "other"
+    );
+
+    let eval_result = eval_function(&doc_set, &entry, "c");
+    assert_matches!(
+        eval_result,
+        Ok(Value::List(ref items)) if items == &[Value::Number(1.), Value::Number(3.)]
+    );
+}
+
+/// Repeated calls with the same arguments are cache hits.
+#[test]
+fn eval_cache_stats_ok() {
+    let mut set = FileSet::default();
+    set.insert(
+        "main",
+        "a = b(1) + b(1) + b(2)\n\
+         b(x) = x\n\
+         c = Translate(Cube(), 1, 0, 0)",
+    );
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let Evaluation {
+        result: eval_result,
+        stats,
+        ..
+    } = EvalOptions::new().eval_function(&doc_set, &entry, "a");
+    assert_matches!(eval_result, Ok(Value::Number(num)) if num == 4.);
+    assert_eq!(
+        stats,
+        CacheStats {
+            hits: 1,
+            misses: 3,
+            solids_created: 0,
+        }
+    );
+
+    let Evaluation {
+        result: eval_result,
+        stats,
+        ..
+    } = EvalOptions::new().eval_function(&doc_set, &entry, "c");
+    assert_matches!(eval_result, Ok(Value::Solid(_)));
+    assert_eq!(
+        stats,
+        CacheStats {
+            hits: 0,
+            misses: 3,
+            solids_created: 2,
+        }
+    );
+}
+
+/// Adding a number to a solid is a type error rather than a panic.
+#[test]
+fn eval_solid_plus_number_errors() {
+    let mut set = FileSet::default();
+    set.insert("main", "a = Cube() + 1");
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let eval_result = eval_function(&doc_set, &entry, "a");
+    assert_matches!(
+        eval_result,
+        Err(EvalError {
+            error_type: EvalErrorType::BinaryOpWrongTypes {
+                lhs_type: "solid",
+                rhs_type: "number",
+                ..
+            },
+            ..
+        })
+    );
+}
+
+/// Comparisons give bools, and solids are equal only to the same solid.
+#[test]
+fn eval_comparison_ok() {
+    let mut set = FileSet::default();
+    set.insert(
+        "main",
+        "a = 1 < 2\nb = Cube() == Cube()\nc = Cube() != Translate(Cube(), 1, 0, 0)\nd = 1 + 1 >= 3\ne = Assert(2 ^ 2 == 4, 5)\nf = 1 < Cube()",
+    );
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    for (name, expected) in [("a", true), ("b", true), ("c", true), ("d", false)] {
+        let eval_result = eval_function(&doc_set, &entry, name);
+        assert_matches!(eval_result, Ok(Value::Bool(val)) if val == expected, "{name}");
+    }
+
+    let eval_result = eval_function(&doc_set, &entry, "e");
+    assert_matches!(eval_result, Ok(Value::Number(5.)));
+
+    let eval_result = eval_function(&doc_set, &entry, "f");
+    assert_matches!(
+        eval_result,
+        Err(EvalError {
+            error_type: EvalErrorType::BinaryOpWrongTypes {
+                lhs_type: "number",
+                rhs_type: "solid",
+                ..
+            },
+            ..
+        })
+    );
+}
+
+/// Type errors in binary expressions report the location of the operator.
+#[test]
+fn eval_binary_op_error_location() {
+    let mut set = FileSet::default();
+    set.insert("main", "b = 2\na = (1) + Cube()");
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let eval_result = eval_function(&doc_set, &entry, "a");
+    assert_matches!(
+        eval_result,
+        Err(EvalError {
+            error_type: EvalErrorType::BinaryOpWrongTypes {
+                location: Some(SourceLocation {
+                    line: 2,
+                    col: 9,
+                    ..
+                }),
+                ..
+            },
+            ..
+        })
+    );
+}
+
+/// Type errors in nested negations report the start of the negation that
+/// failed, including its parentheses.
+#[test]
+fn eval_unary_op_error_location() {
+    let mut set = FileSet::default();
+    set.insert("main", "a = 1 +\n  -(-\"s\")\nb = --\"s\"\nc = -(-(\"s\"))");
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    for (func, line, col) in [("a", 2, 4), ("b", 3, 6), ("c", 4, 6)] {
+        let eval_result = eval_function(&doc_set, &entry, func);
+        assert_matches!(
+            eval_result,
+            Err(EvalError {
+                error_type: EvalErrorType::UnaryOpWrongType {
+                    location: Some(SourceLocation { line: l, col: c, .. }),
+                    ..
+                },
+                ..
+            }) if l == line && c == col,
+            "{func}"
+        );
+    }
+}
+
+/// Errors can be rendered with the offending line and a caret under the
+/// operator.
+#[test]
+fn eval_error_render_with_source() {
+    let src = "b = 2\na = (1) + Cube()";
+    let mut set = FileSet::default();
+    set.insert("main", src);
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let eval_result = eval_function(&doc_set, &entry, "a");
+    assert_matches!(eval_result, Err(_));
+    let rendered = eval_result.unwrap_err().render_with_source(src);
+    assert_eq!(
+        rendered,
+        "Cannot perform Addition between a number and a solid\n\
+         \t\t in body of function \"a\" on line 2, col 1 of \"main\"\n  \
+         --> line 2, col 9 of \"main\"\n  \
+         |\n\
+         2 | a = (1) + Cube()\n  \
+         |         ^\n"
+    );
+}
+
+/// Let binds names within its body, each binding seeing the ones before it.
+#[test]
+fn eval_let_ok() {
+    let mut set = FileSet::default();
+    set.insert(
+        "main",
+        "a = let r = Sin(30) in r + r\n\
+         b(x) = let x = x * 2, y = x + 1 in x * y\n\
+         c = b(1)\n\
+         d = let letter = 1 in letter + 1\n\
+         e = 1 + let r = 2 in r * 3",
+    );
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+    assert_matches!(validate_names(&doc_set)[..], []);
+
+    let eval_result = eval_function(&doc_set, &entry, "a");
+    assert_matches!(eval_result, Ok(Value::Number(num)) if (num - 1.).abs() < 0.0001);
+
+    for (name, expected) in [("c", 6.), ("d", 2.), ("e", 7.)] {
+        let eval_result = eval_function(&doc_set, &entry, name);
+        assert_matches!(eval_result, Ok(Value::Number(num)) if num == expected, "{name}");
+    }
+}
+
+/// A built-in registered by the host that doubles a number.
+struct Double;
+
+impl CustomBuiltIn for Double {
+    fn arg_defs(&self) -> &'static [BuiltInArgDef] {
+        const ARGS: &[BuiltInArgDef] = &[BuiltInArgDef {
+            name: "x",
+            default: None,
+        }];
+        ARGS
+    }
+
+    fn eval(
+        &self,
+        _solids: &mut SolidSet,
+        args: &BTreeMap<String, Value>,
+    ) -> Result<Value, EvalErrorType<'static>> {
+        match args.get("x") {
+            Some(Value::Number(x)) => Ok(Value::Number(x * 2.)),
+            _ => Err(EvalErrorType::ArgWrongType {
+                name: "x".into(),
+                expected: "number",
+                got: "something else",
+            }),
+        }
+    }
+}
+
+/// Registered built-ins can be called like any other, and their errors are
+/// reported with the usual context.
+#[test]
+fn eval_registered_built_in() {
+    let mut set = FileSet::default();
+    set.insert("main", "a = Double(21)\nb = Double(x = Cube())");
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let mut built_ins = BuiltInRegistry::new();
+    built_ins.register_builtin("Double", Box::new(Double));
+
+    let eval_result = EvalOptions::new()
+        .with_built_ins(&built_ins)
+        .eval_function(&doc_set, &entry, "a")
+        .result;
+    assert_matches!(eval_result, Ok(Value::Number(42.)));
+
+    let eval_result = EvalOptions::new()
+        .with_built_ins(&built_ins)
+        .eval_function(&doc_set, &entry, "b")
+        .result;
+    assert_matches!(
+        eval_result,
+        Err(EvalError {
+            error_type: EvalErrorType::ArgWrongType { .. },
+            ..
+        })
+    );
+
+    let eval_result = eval_function(&doc_set, &entry, "a");
+    assert_matches!(
+        eval_result,
+        Err(EvalError {
+            error_type: EvalErrorType::FuncNotFound { .. },
+            ..
+        })
+    );
+}
+
+/// A built-in registered by the host that makes a box `h` high on a unit
+/// square.
+struct Slab;
+
+impl CustomBuiltIn for Slab {
+    fn arg_defs(&self) -> &'static [BuiltInArgDef] {
+        const ARGS: &[BuiltInArgDef] = &[BuiltInArgDef {
+            name: "h",
+            default: None,
+        }];
+        ARGS
+    }
+
+    fn eval(
+        &self,
+        solids: &mut SolidSet,
+        args: &BTreeMap<String, Value>,
+    ) -> Result<Value, EvalErrorType<'static>> {
+        let Some(Value::Number(h)) = args.get("h") else {
+            return Err(EvalErrorType::ArgWrongType {
+                name: "h".into(),
+                expected: "number",
+                got: "something else",
+            });
+        };
+
+        let vertex = builder::vertex(Point3::origin());
+        let edge = builder::tsweep(&vertex, Vector3::unit_x());
+        let face = builder::tsweep(&edge, Vector3::unit_y());
+        let solid = builder::tsweep(&face, *h * Vector3::unit_z());
+        Ok(Value::Solid(solids.push(solid)))
+    }
+}
+
+/// Solids pushed by registered built-ins are returned for export, and
+/// validating with the registry resolves calls to them.
+#[test]
+fn eval_registered_built_in_solids() {
+    let mut set = FileSet::default();
+    set.insert("main", "a = Slab(Double(1)) - Box(1, 1, 1)");
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let mut built_ins = BuiltInRegistry::new();
+    built_ins.register_builtin("Double", Box::new(Double));
+    built_ins.register_builtin("Slab", Box::new(Slab));
+
+    let Evaluation {
+        result: eval_result,
+        solids,
+        ..
+    } = EvalOptions::new()
+        .with_built_ins(&built_ins)
+        .with_tolerance(0.001)
+        .eval_function(&doc_set, &entry, "a");
+    let Ok(Value::Solid(id)) = eval_result else {
+        panic!("expected a solid, got {eval_result:?}");
+    };
+    let mesh = tessellate_solid(&solids, &id).unwrap();
+    // The box is centered, so only an eighth of it overlaps the slab.
+    assert!((mesh.volume() - 1.875).abs() < 1e-6);
+
+    assert_matches!(validate_names_with_built_ins(&doc_set, &built_ins)[..], []);
+    assert_matches!(
+        &validate_names(&doc_set)[..],
+        [
+            EvalError {
+                error_type: EvalErrorType::FuncNotFound { .. },
+                ..
+            },
+            EvalError {
+                error_type: EvalErrorType::FuncNotFound { .. },
+                ..
+            }
+        ]
+    );
+}
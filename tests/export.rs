@@ -0,0 +1,228 @@
+#![feature(assert_matches)]
+use std::assert_matches::assert_matches;
+
+use std::io;
+
+use funcad::*;
+use typed_arena::Arena;
+
+mod util;
+use util::FileSet;
+
+/// The number of triangles in a binary STL file, from its header.
+fn stl_triangle_count(stl: &[u8]) -> u32 {
+    u32::from_le_bytes(stl[80..84].try_into().unwrap())
+}
+
+/// A cube is written as triangles.
+#[test]
+fn export_stl_ok() {
+    let mut set = FileSet::default();
+    set.insert("main", "a = Cube()\nb = PlaceAt(Cube(), [])");
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let Evaluation {
+        result: eval_result,
+        solids,
+        ..
+    } = EvalOptions::new().eval_function(&doc_set, &entry, "a");
+    let Ok(Value::Solid(id)) = eval_result else {
+        panic!("expected a solid, got {eval_result:?}");
+    };
+
+    let mut stl = Vec::new();
+    assert_matches!(export_solid_stl(&solids, &id, &mut stl), Ok(()));
+    assert!(stl_triangle_count(&stl) > 0);
+
+    let Evaluation {
+        result: eval_result,
+        solids,
+        ..
+    } = EvalOptions::new().eval_function(&doc_set, &entry, "b");
+    assert_matches!(eval_result, Ok(Value::Solid(SolidId::Empty)));
+
+    let mut stl = Vec::new();
+    assert_matches!(export_solid_stl(&solids, &SolidId::Empty, &mut stl), Ok(()));
+    assert_eq!(stl_triangle_count(&stl), 0);
+}
+
+/// A cube is written as a STEP file but the empty solid can't be.
+#[test]
+fn export_step_ok() {
+    let mut set = FileSet::default();
+    set.insert("main", "a = Cube()");
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let Evaluation {
+        result: eval_result,
+        solids,
+        ..
+    } = EvalOptions::new().eval_function(&doc_set, &entry, "a");
+    let Ok(Value::Solid(id)) = eval_result else {
+        panic!("expected a solid, got {eval_result:?}");
+    };
+
+    let mut step = Vec::new();
+    assert_matches!(export_solid_step(&solids, &id, &mut step), Ok(()));
+    assert!(step.starts_with(b"ISO-10303-21"));
+
+    let mut step = Vec::new();
+    assert_matches!(
+        export_solid_step(&solids, &SolidId::Empty, &mut step),
+        Err(ExportError::Eval(EvalError {
+            error_type: EvalErrorType::InvalidSolidId,
+            ..
+        }))
+    );
+    assert!(step.is_empty());
+}
+
+/// Each face of a box is two triangles with their own four vertices.
+#[test]
+fn tessellate_box_ok() {
+    let mut set = FileSet::default();
+    set.insert("main", "a = Box(x=1, y=1, z=1)");
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let Evaluation {
+        result: eval_result,
+        solids,
+        ..
+    } = EvalOptions::new().eval_function(&doc_set, &entry, "a");
+    let Ok(Value::Solid(id)) = eval_result else {
+        panic!("expected a solid, got {eval_result:?}");
+    };
+
+    let mesh = tessellate_solid(&solids, &id).unwrap();
+    assert_eq!(mesh.positions().len(), 24);
+    assert_eq!(mesh.tri_faces().len(), 12);
+    assert_eq!(mesh.faces().len(), 12);
+
+    let mesh = tessellate_solid(&solids, &SolidId::Empty).unwrap();
+    assert_eq!(mesh.faces().len(), 0);
+
+    let mut obj = Vec::new();
+    assert_matches!(export_solid_obj(&solids, &id, &mut obj), Ok(()));
+    let obj = String::from_utf8(obj).unwrap();
+    assert_eq!(
+        obj.lines().filter(|line| line.starts_with("v ")).count(),
+        24
+    );
+    assert_eq!(
+        obj.lines().filter(|line| line.starts_with("f ")).count(),
+        12
+    );
+}
+
+/// Solids can be meshed into triangles but numbers can't.
+#[test]
+fn mesh_of_ok() {
+    let mut set = FileSet::default();
+    set.insert("main", "a = Cube()");
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let Evaluation {
+        result: eval_result,
+        solids,
+        ..
+    } = EvalOptions::new().eval_function(&doc_set, &entry, "a");
+    let Ok(value) = eval_result else {
+        panic!("expected a value, got {eval_result:?}");
+    };
+
+    let triangles = mesh_of(&value, &solids, 0.001).unwrap();
+    assert_eq!(triangles.len(), 12);
+    assert!(triangles
+        .iter()
+        .flatten()
+        .all(|pt| pt.x.is_finite() && pt.y.is_finite() && pt.z.is_finite()));
+
+    let triangles = mesh_of(&Value::Solid(SolidId::Empty), &solids, 0.001);
+    assert_matches!(triangles, Ok(triangles) if triangles.is_empty());
+
+    let triangles = mesh_of(&Value::Number(1.), &solids, 0.001);
+    assert_matches!(
+        triangles,
+        Err(EvalError {
+            error_type: EvalErrorType::ArgWrongType { .. },
+            ..
+        })
+    );
+}
+
+/// A solid carved from the universal solid is inside out, so no export path
+/// writes it, while a curved solid that isn't inside out is written.
+#[test]
+fn export_inverted_errors() {
+    let mut set = FileSet::default();
+    set.insert("main", "a = Universal - Cylinder(1, 1)\nb = Cylinder(1, 1)");
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let Evaluation {
+        result: eval_result,
+        solids,
+        ..
+    } = EvalOptions::new().eval_function(&doc_set, &entry, "a");
+    let Ok(value @ Value::Solid(id)) = eval_result else {
+        panic!("expected a solid, got {eval_result:?}");
+    };
+
+    assert_matches!(tessellate_solid(&solids, &id), Err(_));
+    assert_matches!(export_solid_obj(&solids, &id, Vec::new()), Err(_));
+    let mut step = Vec::new();
+    assert_matches!(
+        export_solid_step(&solids, &id, &mut step),
+        Err(ExportError::IO(ref e)) if e.kind() == io::ErrorKind::InvalidInput
+    );
+    assert!(step.is_empty());
+    assert_matches!(
+        mesh_of(&value, &solids, 0.1),
+        Err(EvalError {
+            error_type: EvalErrorType::InvertedSolid,
+            ..
+        })
+    );
+
+    let Evaluation {
+        result: eval_result,
+        solids,
+        ..
+    } = EvalOptions::new().eval_function(&doc_set, &entry, "b");
+    let Ok(Value::Solid(id)) = eval_result else {
+        panic!("expected a solid, got {eval_result:?}");
+    };
+
+    let mut step = Vec::new();
+    assert_matches!(export_solid_step(&solids, &id, &mut step), Ok(()));
+    assert!(step.starts_with(b"ISO-10303-21"));
+}
@@ -0,0 +1,337 @@
+#![feature(assert_matches)]
+use std::assert_matches::assert_matches;
+
+use funcad::*;
+use typed_arena::Arena;
+
+mod util;
+use util::FileSet;
+
+/// Hole counts must be whole numbers.
+#[test]
+fn hole_grid_fractional_count_errors() {
+    let mut set = FileSet::default();
+    set.insert("main", "a = HoleGrid(Box(4, 4, 2), 0.5, 1, 1, 1.5, 1, 1)");
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let eval_result = eval_function(&doc_set, &entry, "a");
+    assert_matches!(
+        eval_result,
+        Err(EvalError {
+            error_type: EvalErrorType::ArgNotInteger { .. },
+            ..
+        })
+    );
+}
+
+/// Holes need a positive radius.
+#[test]
+fn hole_grid_zero_radius_errors() {
+    let mut set = FileSet::default();
+    set.insert("main", "a = HoleGrid(Box(4, 4, 2), 0, 1, 1, 2, 2, 1)");
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let eval_result = eval_function(&doc_set, &entry, "a");
+    assert_matches!(
+        eval_result,
+        Err(EvalError {
+            error_type: EvalErrorType::InvalidDimension { .. },
+            ..
+        })
+    );
+}
+
+/// Two blind holes of radius 0.5 and depth 1 take 2 * pi / 4 out of a box.
+#[test]
+fn hole_grid_ok() {
+    let mut set = FileSet::default();
+    set.insert(
+        "main",
+        "a = Volume(HoleGrid(Box(4, 2, 2), 0.5, 2, 2, 2, 1, 1))",
+    );
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    // Cutting cylinders at the default tolerance is very slow.
+    let eval_result = EvalOptions::new()
+        .with_tolerance(0.01)
+        .eval_function(&doc_set, &entry, "a")
+        .result;
+    let expected = 16. - std::f64::consts::PI / 2.;
+    assert_matches!(eval_result, Ok(Value::Number(vol)) if (vol - expected).abs() < 0.05);
+}
+
+/// Hole counts are capped.
+#[test]
+fn hole_grid_too_many_errors() {
+    let mut set = FileSet::default();
+    set.insert(
+        "main",
+        "a = HoleGrid(Box(4, 4, 2), 0.01, 0.02, 0.02, 101, 1, 1)",
+    );
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let eval_result = eval_function(&doc_set, &entry, "a");
+    assert_matches!(
+        eval_result,
+        Err(EvalError {
+            error_type: EvalErrorType::ArgAboveMax { max: 100, .. },
+            ..
+        })
+    );
+}
+
+/// Patterns share the cap on hole counts.
+#[test]
+fn pattern_too_many_errors() {
+    let mut set = FileSet::default();
+    set.insert(
+        "main",
+        "a = LinearPattern(Cube(), 2, 0, 0, 101)\n\
+         b = PolarPattern(Cube(), 101)",
+    );
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    for name in ["a", "b"] {
+        let eval_result = eval_function(&doc_set, &entry, name);
+        assert_matches!(
+            eval_result,
+            Err(EvalError {
+                error_type: EvalErrorType::ArgAboveMax { max: 100, .. },
+                ..
+            })
+        );
+    }
+}
+
+/// Chamfering the ridge of a triangular prism lowers its top.
+#[test]
+fn chamfer_z_ridge_ok() {
+    let mut set = FileSet::default();
+    set.insert(
+        "main",
+        "a = TransformedBounds(AssertValid(ChamferZ(prism, 0.25, 1, 0)))\n\
+         prism = Mesh(\n\
+             [0, 0, 0, 2, 0, 0, 1, 0, 1, 0, 2, 0, 2, 2, 0, 1, 2, 1],\n\
+             [0, 1, 2, 3, 5, 4, 0, 4, 1, 0, 3, 4, 1, 5, 2, 1, 4, 5, 2, 3, 0, 2, 5, 3]\n\
+         )",
+    );
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let eval_result = eval_function(&doc_set, &entry, "a");
+    let top = 1. - 0.25 * std::f64::consts::FRAC_1_SQRT_2;
+    assert_matches!(
+        eval_result,
+        Ok(Value::List(bounds)) if matches!(
+            bounds[..],
+            [_, Value::Vector(_, _, z)] if (z - top).abs() < 0.0001
+        )
+    );
+}
+
+/// Chamfering the top edges of a box cuts a wedge of cross-section
+/// `d * d / 2` from each, less the corners where neighbouring wedges overlap,
+/// which are pyramids of volume `d ^ 3 / 3`.
+#[test]
+fn chamfer_z_box_ok() {
+    let mut set = FileSet::default();
+    set.insert(
+        "main",
+        "a = Volume(AssertValid(ChamferZ(Box(2, 2, 2), 0.2)))\n\
+         b = Volume(AssertValid(ChamferZ(Box(2, 2, 2), 0.2, 1, 0)))",
+    );
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let removed = 4. * 0.02 * 2. - 4. * 0.008 / 3.;
+
+    let eval_result = eval_function(&doc_set, &entry, "a");
+    assert_matches!(
+        eval_result,
+        Ok(Value::Number(vol)) if (vol - (8. - 2. * removed)).abs() < 1e-6
+    );
+
+    let eval_result = eval_function(&doc_set, &entry, "b");
+    assert_matches!(
+        eval_result,
+        Ok(Value::Number(vol)) if (vol - (8. - removed)).abs() < 1e-6
+    );
+}
+
+/// Chamfers need a positive distance.
+#[test]
+fn chamfer_z_zero_distance_errors() {
+    let mut set = FileSet::default();
+    set.insert("main", "a = ChamferZ(Box(2, 2, 2), 0, 1, 1)");
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let eval_result = eval_function(&doc_set, &entry, "a");
+    assert_matches!(
+        eval_result,
+        Err(EvalError {
+            error_type: EvalErrorType::InvalidDimension { .. },
+            ..
+        })
+    );
+}
+
+/// Three unit cubes two apart along X span five units.
+#[test]
+fn linear_pattern_ok() {
+    let mut set = FileSet::default();
+    set.insert(
+        "main",
+        "a = BBoxSize(LinearPattern(Cube(), 2, 0, 0, 3))\n\
+         b = LinearPattern(Cube(), 2, 0, 0, 1.5)\n\
+         c = LinearPattern(Cube(), 2, 0, 0, 0)",
+    );
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let eval_result = eval_function(&doc_set, &entry, "a");
+    assert_matches!(
+        eval_result,
+        Ok(Value::Vector(x, y, z))
+            if (x - 5.).abs() < 1e-9 && (y - 1.).abs() < 1e-9 && (z - 1.).abs() < 1e-9
+    );
+
+    let eval_result = eval_function(&doc_set, &entry, "b");
+    assert_matches!(
+        eval_result,
+        Err(EvalError {
+            error_type: EvalErrorType::ArgNotInteger { .. },
+            ..
+        })
+    );
+
+    let eval_result = eval_function(&doc_set, &entry, "c");
+    assert_matches!(
+        eval_result,
+        Err(EvalError {
+            error_type: EvalErrorType::ArgNotInteger { .. },
+            ..
+        })
+    );
+}
+
+/// Four copies of an off-center cube a quarter turn apart fill out a square
+/// around the origin, unlike the single cube.
+#[test]
+fn polar_pattern_ok() {
+    let mut set = FileSet::default();
+    set.insert(
+        "main",
+        "c = Translate(Cube(), 3, 0, 0)\n\
+         a = BBoxSize(PolarPattern(c, 4))\n\
+         b = BBoxSize(PolarPattern(c, 1))\n\
+         d = PolarPattern(c, 2.5, 90)",
+    );
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let eval_result = eval_function(&doc_set, &entry, "a");
+    assert_matches!(
+        eval_result,
+        Ok(Value::Vector(x, y, z))
+            if (x - 7.).abs() < 1e-6 && (y - 7.).abs() < 1e-6 && (z - 1.).abs() < 1e-6
+    );
+
+    let eval_result = eval_function(&doc_set, &entry, "b");
+    assert_matches!(
+        eval_result,
+        Ok(Value::Vector(x, y, z))
+            if (x - 1.).abs() < 1e-6 && (y - 1.).abs() < 1e-6 && (z - 1.).abs() < 1e-6
+    );
+
+    let eval_result = eval_function(&doc_set, &entry, "d");
+    assert_matches!(
+        eval_result,
+        Err(EvalError {
+            error_type: EvalErrorType::ArgNotInteger { .. },
+            ..
+        })
+    );
+}
+
+/// Four bars meeting at the origin overlap with coplanar faces, which truck
+/// can't union, so the pattern fails rather than giving the empty solid.
+#[test]
+fn polar_pattern_overlapping_errors() {
+    let mut set = FileSet::default();
+    set.insert(
+        "main",
+        "a = PolarPattern(Translate(Box(2, 1, 1), 1, 0, 0), 4)",
+    );
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let eval_result = eval_function(&doc_set, &entry, "a");
+    assert_matches!(
+        eval_result,
+        Err(EvalError {
+            error_type: EvalErrorType::GeometryOperationFailed { op: "PolarPattern" },
+            ..
+        })
+    );
+}
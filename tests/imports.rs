@@ -1,7 +1,11 @@
 #![feature(assert_matches)]
 use std::assert_matches::assert_matches;
+use std::collections::HashMap;
 
-use funcad::{parse_all, FQPath, ParseError};
+use funcad::{
+    eval_function, eval_function_lazy, parse_all, validate_names, EvalError, EvalErrorType, FQPath,
+    ParseError, Value,
+};
 use typed_arena::Arena;
 
 mod util;
@@ -22,6 +26,20 @@ fn import_past_entry_errors() {
     assert_matches!(result, Err(ParseError::ImportNotInDir(_)));
 }
 
+/// A document importing itself should result in an error.
+#[test]
+fn import_self_errors() {
+    let mut set = FileSet::default();
+    set.insert("main", "import main");
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let result = parse_all(&arena, &entry, |s| set.get_source(s));
+
+    assert_matches!(result, Err(ParseError::SelfImport(_)));
+}
+
 /// `..` should import from parent directory.
 #[test]
 fn import_parent_dir_ok() {
@@ -69,3 +87,157 @@ fn import_missing_errors() {
 
     assert_matches!(result, Err(ParseError::IO(_)));
 }
+
+/// A function can be called through a chain of imports, and every link in
+/// the chain must be imported by the document before it.
+#[test]
+fn import_chain_call_ok() {
+    let mut set = FileSet::default();
+    set.insert("main", "import a\nd = a.b.c + 1\ne = b.c");
+    set.insert("a", "import lib/b");
+    set.insert("lib/b", "c = 2");
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(result, Ok(_));
+    let docs = result.unwrap();
+
+    assert_matches!(eval_function(&docs, &entry, "d"), Ok(Value::Number(3.)));
+    assert_matches!(
+        eval_function(&docs, &entry, "e"),
+        Err(EvalError {
+            error_type: EvalErrorType::ImportNotFound { .. },
+            ..
+        })
+    );
+
+    let errors = validate_names(&docs);
+    assert_matches!(
+        &errors[..],
+        [EvalError {
+            error_type: EvalErrorType::ImportNotFound { .. },
+            ..
+        }]
+    );
+}
+
+/// Functions from a wildcard import can be called unqualified or qualified,
+/// but not when two wildcard imports define them.
+#[test]
+fn import_wildcard_ok() {
+    let mut set = FileSet::default();
+    set.insert(
+        "main",
+        "import * from lib/geom\n\
+         import * from other\n\
+         a = c + 1\n\
+         b = geom.c\n\
+         d = e",
+    );
+    set.insert("lib/geom", "c = 2\ne = 1");
+    set.insert("other", "e = 3");
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(result, Ok(_));
+    let docs = result.unwrap();
+
+    assert_matches!(eval_function(&docs, &entry, "a"), Ok(Value::Number(3.)));
+    assert_matches!(eval_function(&docs, &entry, "b"), Ok(Value::Number(2.)));
+    assert_matches!(
+        eval_function(&docs, &entry, "d"),
+        Err(EvalError {
+            error_type: EvalErrorType::AmbiguousFunction { .. },
+            ..
+        })
+    );
+
+    let errors = validate_names(&docs);
+    assert_matches!(
+        &errors[..],
+        [EvalError {
+            error_type: EvalErrorType::AmbiguousFunction { .. },
+            ..
+        }]
+    );
+}
+
+/// Built-ins and special forms take precedence over functions of the same name
+/// from a wildcard import, which can still be called qualified.
+#[test]
+fn import_wildcard_builtin_precedence() {
+    let mut set = FileSet::default();
+    set.insert(
+        "main",
+        "import * from geom\n\
+         a = Pi\n\
+         b = Switch(\"x\", {\"x\": 1}, 2)\n\
+         c = geom.Pi",
+    );
+    set.insert("geom", "Pi = 3\nSwitch(key, cases, default) = 4");
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(result, Ok(_));
+    let docs = result.unwrap();
+
+    assert_matches!(
+        eval_function(&docs, &entry, "a"),
+        Ok(Value::Number(pi)) if pi == std::f64::consts::PI
+    );
+    assert_matches!(eval_function(&docs, &entry, "b"), Ok(Value::Number(1.)));
+    assert_matches!(eval_function(&docs, &entry, "c"), Ok(Value::Number(3.)));
+    assert_matches!(&validate_names(&docs)[..], []);
+}
+
+/// Lazy evaluation only parses the imports it uses.
+#[test]
+fn lazy_skips_unused_import_ok() {
+    let mut set = FileSet::default();
+    set.insert("main", "import b\nimport c\na = b.d + 1");
+    set.insert("b", "d = 2");
+    set.insert("c", "not valid (");
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+    let mut docs = HashMap::new();
+
+    let result = eval_function_lazy(&arena, &mut docs, &entry, "a", |s| set.get_source(s));
+
+    assert_matches!(result, Ok(Value::Number(3.)));
+    assert!(docs.contains_key(&FQPath(vec!["b".into()])));
+    assert!(!docs.contains_key(&FQPath(vec!["c".into()])));
+}
+
+/// Parsing in parallel gives the same documents as parsing sequentially, with
+/// a shared import only parsed once.
+#[cfg(feature = "parallel")]
+#[test]
+fn parse_all_parallel_matches_sequential() {
+    use funcad::parse_all_parallel;
+
+    let mut set = FileSet::default();
+    set.insert("main", "import a\nimport b\nimport c\nm = a.x + b.x + c.x");
+    set.insert("a", "import shared\nx = shared.y");
+    set.insert("b", "import shared\nx = shared.y * 2");
+    set.insert("c", "import shared\nx = shared.y * 3");
+    set.insert("shared", "y = 1");
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let sequential = parse_all(&arena, &entry, |s| set.get_source(s));
+    let parallel = parse_all_parallel(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parallel, Ok(ref docs) if docs.len() == 5);
+    assert_eq!(parallel.unwrap(), sequential.unwrap());
+
+    set.insert("c", "import missing");
+    let parallel = parse_all_parallel(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parallel, Err(ParseError::IO(_)));
+}
@@ -0,0 +1,78 @@
+#![cfg(feature = "json")]
+#![feature(assert_matches)]
+use std::assert_matches::assert_matches;
+
+use funcad::*;
+use typed_arena::Arena;
+
+mod util;
+use util::FileSet;
+
+/// Solids are meshed and include their bounding box.
+#[test]
+fn json_solid() {
+    let mut set = FileSet::default();
+    set.insert("main", "a = [2, Cube()]");
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let json = evaluate_to_json(&doc_set, &entry, "a");
+    let items = &json["result"]["items"];
+    assert_eq!(items[0]["value"], 2.);
+
+    let solid = &items[1];
+    assert_eq!(solid["type"], "solid");
+    assert_eq!(solid["bbox"]["min"][0], -0.5);
+    assert_eq!(solid["bbox"]["max"][2], 0.5);
+    assert!(solid["triangles"]
+        .as_array()
+        .is_some_and(|tris| tris.len() >= 12));
+}
+
+/// Errors are reported with their context.
+#[test]
+fn json_error() {
+    let mut set = FileSet::default();
+    set.insert("main", "a = b");
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let json = evaluate_to_json(&doc_set, &entry, "a");
+    assert_eq!(json["error"]["message"], "The function \"b\" was not found");
+    assert!(json["error"]["context"]
+        .as_array()
+        .is_some_and(|context| !context.is_empty()));
+}
+
+/// With the serde feature values serialize as plain data, with solids
+/// referenced by id.
+#[cfg(feature = "serde")]
+#[test]
+fn value_serialize() {
+    use serde_json::json;
+
+    let number = serde_json::to_value(Value::Number(2.0));
+    assert_matches!(number, Ok(json) if json == json!(2.0));
+
+    let solid = serde_json::to_value(Value::Solid(SolidId::Regular(3)));
+    assert_matches!(solid, Ok(json) if json == json!({ "solid": 3 }));
+
+    let list = serde_json::to_value(Value::List(vec![
+        Value::Vector(1., 2., 3.),
+        Value::Solid(SolidId::Empty),
+    ]));
+    assert_matches!(list, Ok(json) if json == json!([[1.0, 2.0, 3.0], { "solid": "empty" }]));
+
+    let nan = serde_json::to_value(Value::Number(f64::NAN));
+    assert_matches!(nan, Err(_));
+}
@@ -0,0 +1,143 @@
+#![feature(assert_matches)]
+use std::assert_matches::assert_matches;
+
+use funcad::*;
+use typed_arena::Arena;
+
+mod util;
+use util::FileSet;
+
+/// Ranges exclude their end.
+#[test]
+fn range_half_open() {
+    let mut set = FileSet::default();
+    set.insert("main", "a = Range(1, 4)\nb = RangeStep(3, 0, -1.5)");
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let eval_result = eval_function(&doc_set, &entry, "a");
+    assert_matches!(
+        eval_result,
+        Ok(Value::List(ref items))
+            if items == &[Value::Number(1.), Value::Number(2.), Value::Number(3.)]
+    );
+
+    let eval_result = eval_function(&doc_set, &entry, "b");
+    assert_matches!(
+        eval_result,
+        Ok(Value::List(ref items)) if items == &[Value::Number(3.), Value::Number(1.5)]
+    );
+}
+
+/// A step of zero would never reach the end.
+#[test]
+fn range_zero_step_errors() {
+    let mut set = FileSet::default();
+    set.insert("main", "a = RangeStep(0, 1, 0)");
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let eval_result = eval_function(&doc_set, &entry, "a");
+    assert_matches!(
+        eval_result,
+        Err(EvalError {
+            error_type: EvalErrorType::NumExprNotFinite { .. },
+            ..
+        })
+    );
+}
+
+/// Ranges too long to hold in memory are errors rather than allocation
+/// failures.
+#[test]
+fn range_too_long_errors() {
+    let mut set = FileSet::default();
+    set.insert(
+        "main",
+        "a = Range(0, 1e300)\nb = RangeStep(0, 1e300, 1)\nc = RangeStep(0, 1, 1e-300)\nd = RangeStep(-1e308, 1e308, 1e-10)",
+    );
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    for func in ["a", "b", "c"] {
+        let eval_result = eval_function(&doc_set, &entry, func);
+        assert_matches!(
+            eval_result,
+            Err(EvalError {
+                error_type: EvalErrorType::InvalidDimension { .. },
+                ..
+            })
+        );
+    }
+
+    let eval_result = eval_function(&doc_set, &entry, "d");
+    assert_matches!(
+        eval_result,
+        Err(EvalError {
+            error_type: EvalErrorType::NumExprNotFinite { .. },
+            ..
+        })
+    );
+}
+
+/// Map calls a function in the same document on each item.
+#[test]
+fn map_ok() {
+    let mut set = FileSet::default();
+    set.insert(
+        "main",
+        "a = Map(\"double\", Range(1, 4))\ndouble(x, factor = 2) = x * factor",
+    );
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let eval_result = eval_function(&doc_set, &entry, "a");
+    assert_matches!(
+        eval_result,
+        Ok(Value::List(ref items))
+            if items == &[Value::Number(2.), Value::Number(4.), Value::Number(6.)]
+    );
+}
+
+/// Mapping an unknown function is an error.
+#[test]
+fn map_unknown_func_errors() {
+    let mut set = FileSet::default();
+    set.insert("main", "a = Map(\"missing\", [1])");
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let eval_result = eval_function(&doc_set, &entry, "a");
+    assert_matches!(
+        eval_result,
+        Err(EvalError {
+            error_type: EvalErrorType::FuncNotFound { .. },
+            ..
+        })
+    );
+}
@@ -44,8 +44,537 @@ fn tan_not_finite() {
     assert_matches!(
         eval_result,
         Err(EvalError {
-            error_type: EvalErrorType::NumExprNotFinite,
+            error_type: EvalErrorType::NumExprNotFinite { .. },
             ..
         })
     );
 }
+
+/// Inverse trig functions give degrees and are undefined outside their domain.
+#[test]
+fn inverse_trig() {
+    let mut set = FileSet::default();
+    set.insert(
+        "main",
+        "a = Asin(1)\nb = Acos(0.5)\nc = Atan(1)\nd = Atan2(1, 1)\ne = Atan2(-1, -1)\nf = Asin(1.5)\ng = Acos(-2)",
+    );
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    for (name, expected) in [("a", 90.), ("b", 60.), ("c", 45.), ("d", 45.), ("e", -135.)] {
+        let eval_result = eval_function(&doc_set, &entry, name);
+        assert_matches!(
+            eval_result,
+            Ok(Value::Number(num)) if (num - expected).abs() < 0.0001,
+            "{name}"
+        );
+    }
+
+    for name in ["f", "g"] {
+        let eval_result = eval_function(&doc_set, &entry, name);
+        assert_matches!(
+            eval_result,
+            Err(EvalError {
+                error_type: EvalErrorType::NumExprNotFinite { .. },
+                ..
+            }),
+            "{name}"
+        );
+    }
+}
+
+/// Rand is deterministic and in [0, 1).
+#[test]
+fn rand_deterministic() {
+    let mut set = FileSet::default();
+    set.insert("main", "a = Rand(7) - Rand(7)\nb = Rand(7)\nc = Rand(8)");
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let eval_result = eval_function(&doc_set, &entry, "a");
+    assert_matches!(eval_result, Ok(Value::Number(0.)));
+
+    let b = eval_function(&doc_set, &entry, "b");
+    assert_matches!(b, Ok(Value::Number(num)) if (0. ..1.).contains(&num));
+
+    let c = eval_function(&doc_set, &entry, "c");
+    assert_matches!(c, Ok(Value::Number(num)) if (0. ..1.).contains(&num));
+    assert_ne!(b.unwrap(), c.unwrap());
+}
+
+/// Rand only takes integer seeds.
+#[test]
+fn rand_non_integer_seed_errors() {
+    let mut set = FileSet::default();
+    set.insert("main", "a = Rand(1.5)");
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let eval_result = eval_function(&doc_set, &entry, "a");
+    assert_matches!(
+        eval_result,
+        Err(EvalError {
+            error_type: EvalErrorType::ArgNotInteger { .. },
+            ..
+        })
+    );
+}
+
+/// SafeDiv divides normally or falls back on division by zero.
+#[test]
+fn safe_div_fallback() {
+    let mut set = FileSet::default();
+    set.insert("main", "a = SafeDiv(1, 4, 7)\nb = SafeDiv(1, 0, 7)");
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let eval_result = eval_function(&doc_set, &entry, "a");
+    assert_matches!(eval_result, Ok(Value::Number(0.25)));
+
+    let eval_result = eval_function(&doc_set, &entry, "b");
+    assert_matches!(eval_result, Ok(Value::Number(7.)));
+}
+
+/// Odd roots of negative numbers are real, even roots aren't.
+#[test]
+fn root_negative() {
+    let mut set = FileSet::default();
+    set.insert("main", "a = Root(-8, 3)\nb = Root(-8, 2)");
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let eval_result = eval_function(&doc_set, &entry, "a");
+    assert_matches!(
+        eval_result,
+        Ok(Value::Number(num)) if (num + 2.).abs() < 0.0001
+    );
+
+    let eval_result = eval_function(&doc_set, &entry, "b");
+    assert_matches!(
+        eval_result,
+        Err(EvalError {
+            error_type: EvalErrorType::NumExprNotFinite { .. },
+            ..
+        })
+    );
+}
+
+/// There's no zeroth root, even of numbers where x ^ inf is finite.
+#[test]
+fn root_zero_errors() {
+    let mut set = FileSet::default();
+    set.insert("main", "a = Root(0.5, 0)\nb = Root(8, 0)");
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    for func in ["a", "b"] {
+        let eval_result = eval_function(&doc_set, &entry, func);
+        assert_matches!(
+            eval_result,
+            Err(EvalError {
+                error_type: EvalErrorType::NumExprNotFinite { .. },
+                ..
+            })
+        );
+    }
+}
+
+/// Square roots of negative numbers aren't real.
+#[test]
+fn sqrt_ok() {
+    let mut set = FileSet::default();
+    set.insert("main", "a = Sqrt(9)\nb = Sqrt(-1)");
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let eval_result = eval_function(&doc_set, &entry, "a");
+    assert_matches!(eval_result, Ok(Value::Number(3.)));
+
+    let eval_result = eval_function(&doc_set, &entry, "b");
+    assert_matches!(
+        eval_result,
+        Err(EvalError {
+            error_type: EvalErrorType::NumExprNotFinite { .. },
+            ..
+        })
+    );
+}
+
+/// Powers are right associative and bind tighter than negation.
+#[test]
+fn pow_ok() {
+    let mut set = FileSet::default();
+    set.insert(
+        "main",
+        "a = 2 ^ 10\nb = 2 ^ 3 ^ 2\nc = -2 ^ 2\nd = Pow(2, 0.5)\ne = (-8) ^ 0.5\nf = Pow(0, -1)",
+    );
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let eval_result = eval_function(&doc_set, &entry, "a");
+    assert_matches!(eval_result, Ok(Value::Number(1024.)));
+
+    let eval_result = eval_function(&doc_set, &entry, "b");
+    assert_matches!(eval_result, Ok(Value::Number(512.)));
+
+    let eval_result = eval_function(&doc_set, &entry, "c");
+    assert_matches!(eval_result, Ok(Value::Number(-4.)));
+
+    let eval_result = eval_function(&doc_set, &entry, "d");
+    assert_matches!(
+        eval_result,
+        Ok(Value::Number(num)) if (num - 2f64.sqrt()).abs() < 0.0001
+    );
+
+    for name in ["e", "f"] {
+        let eval_result = eval_function(&doc_set, &entry, name);
+        assert_matches!(
+            eval_result,
+            Err(EvalError {
+                error_type: EvalErrorType::NumExprNotFinite { .. },
+                ..
+            })
+        );
+    }
+}
+
+/// Logarithms of non-positive numbers and overflowing exponentials aren't
+/// finite.
+#[test]
+fn log_exp() {
+    let mut set = FileSet::default();
+    set.insert(
+        "main",
+        "a = Ln(2.718281828)\nb = Log(1000)\nc = Log(8, 2)\nd = Exp(0)\ne = Ln(0)\nf = Log(-1)\ng = Exp(1000)",
+    );
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    for (name, expected) in [("a", 1.), ("b", 3.), ("c", 3.), ("d", 1.)] {
+        let eval_result = eval_function(&doc_set, &entry, name);
+        assert_matches!(
+            eval_result,
+            Ok(Value::Number(num)) if (num - expected).abs() < 0.0001,
+            "{name}"
+        );
+    }
+
+    for name in ["e", "f", "g"] {
+        let eval_result = eval_function(&doc_set, &entry, name);
+        assert_matches!(
+            eval_result,
+            Err(EvalError {
+                error_type: EvalErrorType::NumExprNotFinite { .. },
+                ..
+            }),
+            "{name}"
+        );
+    }
+}
+
+/// Abs, Floor, Ceil and Round, with halves rounded away from zero.
+#[test]
+fn abs_floor_ceil_round() {
+    let cases = [
+        ("Abs(-1.5)", 1.5),
+        ("Abs(2)", 2.),
+        ("Floor(1.7)", 1.),
+        ("Floor(-1.2)", -2.),
+        ("Ceil(1.2)", 2.),
+        ("Ceil(-1.7)", -1.),
+        ("Round(2.5)", 3.),
+        ("Round(-2.5)", -3.),
+        ("Round(2.4)", 2.),
+    ];
+
+    for (expr, expected) in cases {
+        let mut set = FileSet::default();
+        set.insert("main", &format!("a = {expr}"));
+
+        let arena = Arena::new();
+        let entry = FQPath(vec!["main".into()]);
+
+        let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+        assert_matches!(parse_result, Ok(_));
+        let doc_set = parse_result.unwrap();
+
+        let eval_result = eval_function(&doc_set, &entry, "a");
+        assert_matches!(eval_result, Ok(Value::Number(num)) if num == expected, "{expr}");
+    }
+}
+
+/// ApproxEq tolerates floating point error but not real differences.
+#[test]
+fn approx_eq_assert() {
+    let mut set = FileSet::default();
+    set.insert(
+        "main",
+        "a = Assert(ApproxEq(Sin(30), 0.5), 7)\nb = Assert(ApproxEq(1, 1.1, 0.01), 7)",
+    );
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let eval_result = eval_function(&doc_set, &entry, "a");
+    assert_matches!(eval_result, Ok(Value::Number(7.)));
+
+    let eval_result = eval_function(&doc_set, &entry, "b");
+    assert_matches!(
+        eval_result,
+        Err(EvalError {
+            error_type: EvalErrorType::AssertionFailed,
+            ..
+        })
+    );
+}
+
+/// ApproxEq and IsInt give bools like the comparison operators, so they can be
+/// used as conditions and compared with them.
+#[test]
+fn approx_eq_is_int_bool() {
+    let mut set = FileSet::default();
+    set.insert(
+        "main",
+        "a = [ApproxEq(1, 1.1, 0.01), IsInt(4), IsInt(4) == (1 < 2)]\n\
+         b = Switch(ApproxEq(Cos(60), 0.5), {\"true\": \"close\", \"false\": \"far\"})",
+    );
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let eval_result = eval_function(&doc_set, &entry, "a");
+    assert_matches!(
+        eval_result,
+        Ok(Value::List(ref items))
+            if items == &[Value::Bool(false), Value::Bool(true), Value::Bool(true)]
+    );
+
+    let eval_result = eval_function(&doc_set, &entry, "b");
+    assert_matches!(eval_result, Ok(Value::String(ref s)) if s == "close");
+}
+
+/// Converting to radians and back gives the original angle.
+#[test]
+fn deg_rad_round_trip() {
+    let mut set = FileSet::default();
+    set.insert("main", "a = RadToDeg(DegToRad(30))\nb = DegToRad(180)");
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let eval_result = eval_function(&doc_set, &entry, "a");
+    assert_matches!(
+        eval_result,
+        Ok(Value::Number(num)) if (num - 30.).abs() < 0.0001
+    );
+
+    let eval_result = eval_function(&doc_set, &entry, "b");
+    assert_matches!(
+        eval_result,
+        Ok(Value::Number(num)) if (num - std::f64::consts::PI).abs() < 0.0001
+    );
+}
+
+/// IsInt can guard parameters with Assert.
+#[test]
+fn is_int_assert() {
+    let mut set = FileSet::default();
+    set.insert(
+        "main",
+        "a = f(3)\nb = f(2.5)\nf(n) = Assert(IsInt(n), n * 2)",
+    );
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let eval_result = eval_function(&doc_set, &entry, "a");
+    assert_matches!(eval_result, Ok(Value::Number(6.)));
+
+    let eval_result = eval_function(&doc_set, &entry, "b");
+    assert_matches!(
+        eval_result,
+        Err(EvalError {
+            error_type: EvalErrorType::AssertionFailed,
+            ..
+        })
+    );
+}
+
+/// Sort orders numbers and rejects anything else.
+#[test]
+fn sort_numbers() {
+    let mut set = FileSet::default();
+    set.insert("main", "a = Sort([3, -1, 2])\nb = Sort([1, Cube()])");
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let eval_result = eval_function(&doc_set, &entry, "a");
+    assert_matches!(
+        eval_result,
+        Ok(Value::List(ref items))
+            if items == &[Value::Number(-1.), Value::Number(2.), Value::Number(3.)]
+    );
+
+    let eval_result = eval_function(&doc_set, &entry, "b");
+    assert_matches!(
+        eval_result,
+        Err(EvalError {
+            error_type: EvalErrorType::ArgWrongType { .. },
+            ..
+        })
+    );
+}
+
+/// Factorials and binomials are exact until they'd lose precision.
+#[test]
+fn factorial_binomial() {
+    let mut set = FileSet::default();
+    set.insert(
+        "main",
+        "a = Factorial(5) + Binomial(10, 3)\nb = Factorial(30)\nc = Binomial(3, 5)",
+    );
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let eval_result = eval_function(&doc_set, &entry, "a");
+    assert_matches!(eval_result, Ok(Value::Number(240.)));
+
+    let eval_result = eval_function(&doc_set, &entry, "b");
+    assert_matches!(
+        eval_result,
+        Err(EvalError {
+            error_type: EvalErrorType::NumExprNotFinite { .. },
+            ..
+        })
+    );
+
+    let eval_result = eval_function(&doc_set, &entry, "c");
+    assert_matches!(eval_result, Ok(Value::Number(0.)));
+}
+
+/// Passing a solid where a number is expected names both types.
+#[test]
+fn sin_of_solid_errors() {
+    let mut set = FileSet::default();
+    set.insert("main", "a = Sin(Cube())");
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let eval_result = eval_function(&doc_set, &entry, "a");
+    assert_matches!(
+        eval_result,
+        Err(EvalError {
+            error_type: EvalErrorType::ArgWrongType {
+                expected: "number",
+                got: "solid",
+                ..
+            },
+            ..
+        })
+    );
+}
+
+/// Constants can be called without parentheses.
+#[test]
+fn math_constants() {
+    let mut set = FileSet::default();
+    set.insert("main", "a = Pi\nb = Ln(E)\nc = Tau / Pi");
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let eval_result = eval_function(&doc_set, &entry, "a");
+    assert_matches!(
+        eval_result,
+        Ok(Value::Number(num)) if (num - std::f64::consts::PI).abs() < 1e-12
+    );
+
+    for (name, expected) in [("b", 1.), ("c", 2.)] {
+        let eval_result = eval_function(&doc_set, &entry, name);
+        assert_matches!(
+            eval_result,
+            Ok(Value::Number(num)) if (num - expected).abs() < 1e-12,
+            "{name}"
+        );
+    }
+}
@@ -0,0 +1,370 @@
+#![feature(assert_matches)]
+use std::assert_matches::assert_matches;
+
+use funcad::*;
+use typed_arena::Arena;
+
+mod util;
+use util::FileSet;
+
+/// The centroid of a tetrahedron is the mean of its vertices.
+#[test]
+fn centroid_tetrahedron() {
+    let mut set = FileSet::default();
+    set.insert(
+        "main",
+        "a = CentroidX(t) + CentroidY(t) + CentroidZ(t)\n\
+         t = Mesh([0, 0, 0, 1, 0, 0, 0, 1, 0, 0, 0, 1], [0, 2, 1, 0, 1, 3, 0, 3, 2, 1, 2, 3])",
+    );
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let eval_result = eval_function(&doc_set, &entry, "a");
+    assert_matches!(
+        eval_result,
+        Ok(Value::Number(num)) if (num - 0.75).abs() < 0.0001
+    );
+}
+
+/// The empty solid has no centroid.
+#[test]
+fn centroid_empty_errors() {
+    let mut set = FileSet::default();
+    set.insert(
+        "main",
+        "a = CentroidX(Cube() * t)\n\
+         t = Mesh([5, 5, 5, 6, 5, 5, 5, 6, 5, 5, 5, 6], [0, 2, 1, 0, 1, 3, 0, 3, 2, 1, 2, 3])",
+    );
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let eval_result = eval_function(&doc_set, &entry, "a");
+    assert_matches!(
+        eval_result,
+        Err(EvalError {
+            error_type: EvalErrorType::EmptySolid,
+            ..
+        })
+    );
+}
+
+/// The enclosing box spans every solid in the list.
+#[test]
+fn enclosing_box_ok() {
+    let mut set = FileSet::default();
+    set.insert(
+        "main",
+        "a = CentroidX(EnclosingBox([Cube(), Translate(Cube(), 3)]))\nb = EnclosingBox([])",
+    );
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let eval_result = eval_function(&doc_set, &entry, "a");
+    assert_matches!(
+        eval_result,
+        Ok(Value::Number(num)) if (num - 1.5).abs() < 0.0001
+    );
+
+    let eval_result = eval_function(&doc_set, &entry, "b");
+    assert_matches!(eval_result, Ok(Value::Solid(SolidId::Empty)));
+}
+
+/// Disjoint pieces are counted but voids aren't.
+#[test]
+fn component_count() {
+    let mut set = FileSet::default();
+    set.insert(
+        "main",
+        "a = [ComponentCount(PlaceAt(Cube(), [[0, 0, 0], [3, 0, 0]])), \
+         ComponentCount(hollow), ComponentCount(PlaceAt(Cube(), []))]\n\
+         hollow = Mesh(\
+            [0, 0, 0, 4, 0, 0, 0, 4, 0, 0, 0, 4, \
+             0.5, 0.5, 0.5, 1.5, 0.5, 0.5, 0.5, 1.5, 0.5, 0.5, 0.5, 1.5], \
+            [0, 2, 1, 0, 1, 3, 0, 3, 2, 1, 2, 3, \
+             4, 5, 6, 4, 7, 5, 4, 6, 7, 5, 7, 6])",
+    );
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let eval_result = eval_function(&doc_set, &entry, "a");
+    assert_matches!(
+        eval_result,
+        Ok(Value::List(ref items))
+            if items == &[Value::Number(2.), Value::Number(1.), Value::Number(0.)]
+    );
+}
+
+/// Only the biggest piece is kept.
+#[test]
+fn largest_component() {
+    let mut set = FileSet::default();
+    set.insert(
+        "main",
+        "a = LargestComponent(Cube() + Translate(Cube(2), 5))\n\
+         b = CentroidX(a) + ComponentCount(a)",
+    );
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let eval_result = eval_function(&doc_set, &entry, "b");
+    assert_matches!(
+        eval_result,
+        Ok(Value::Number(num)) if (num - 6.).abs() < 0.0001
+    );
+}
+
+/// Closed solids, including boolean results and solids with voids, pass
+/// through AssertValid unchanged.
+#[test]
+fn assert_valid_ok() {
+    let mut set = FileSet::default();
+    set.insert(
+        "main",
+        "a = [ComponentCount(AssertValid(Cube() + Translate(Cube(), 0.5, 0.5, 0.5))), \
+         ComponentCount(AssertValid(hollow)), ComponentCount(AssertValid(PlaceAt(Cube(), [])))]\n\
+         hollow = Mesh(\
+            [0, 0, 0, 4, 0, 0, 0, 4, 0, 0, 0, 4, \
+             0.5, 0.5, 0.5, 1.5, 0.5, 0.5, 0.5, 1.5, 0.5, 0.5, 0.5, 1.5], \
+            [0, 2, 1, 0, 1, 3, 0, 3, 2, 1, 2, 3, \
+             4, 5, 6, 4, 7, 5, 4, 6, 7, 5, 7, 6])",
+    );
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let eval_result = eval_function(&doc_set, &entry, "a");
+    assert_matches!(
+        eval_result,
+        Ok(Value::List(ref items))
+            if items == &[Value::Number(1.), Value::Number(1.), Value::Number(0.)]
+    );
+}
+
+/// A cube meshes into two triangles per face and the empty solid into none.
+#[test]
+fn triangle_count() {
+    let mut set = FileSet::default();
+    set.insert(
+        "main",
+        "a = [TriangleCount(Cube()), TriangleCount(Cube(), 0.1), \
+         TriangleCount(PlaceAt(Cube(), []))]",
+    );
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let eval_result = eval_function(&doc_set, &entry, "a");
+    assert_matches!(
+        eval_result,
+        Ok(Value::List(ref items))
+            if items == &[Value::Number(12.), Value::Number(12.), Value::Number(0.)]
+    );
+}
+
+/// Without a tolerance the count follows the solid tolerance, as export does.
+#[test]
+fn triangle_count_solid_tolerance() {
+    let mut set = FileSet::default();
+    set.insert(
+        "main",
+        "a = TriangleCount(Cylinder(1, 1))\n\
+         b = TriangleCount(Cylinder(1, 1), 0.1)",
+    );
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let Ok(Value::Number(coarse)) = eval_function(&doc_set, &entry, "b") else {
+        panic!("expected a count");
+    };
+    let Ok(Value::Number(fine)) = eval_function(&doc_set, &entry, "a") else {
+        panic!("expected a count");
+    };
+    assert!(fine > coarse);
+
+    let eval_result = EvalOptions::new()
+        .with_tolerance(0.1)
+        .eval_function(&doc_set, &entry, "a")
+        .result;
+    assert_matches!(eval_result, Ok(Value::Number(num)) if num == coarse);
+}
+
+/// The diagonal of a 1x2x2 box is 3.
+#[test]
+fn diagonal_ok() {
+    let mut set = FileSet::default();
+    set.insert(
+        "main",
+        "a = Diagonal(Box(1, 2, 2))\nb = Diagonal(PlaceAt(Cube(), []))",
+    );
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let eval_result = eval_function(&doc_set, &entry, "a");
+    assert_matches!(eval_result, Ok(Value::Number(len)) if (len - 3.).abs() < 1e-9);
+
+    let eval_result = eval_function(&doc_set, &entry, "b");
+    assert_matches!(
+        eval_result,
+        Err(EvalError {
+            error_type: EvalErrorType::EmptySolid,
+            ..
+        })
+    );
+}
+
+/// Voids are subtracted from the volume and the empty solid has none.
+#[test]
+fn volume_ok() {
+    let mut set = FileSet::default();
+    set.insert(
+        "main",
+        "a = Volume(Cube())\nb = Volume(Shell(Box(2, 2, 2), 0.5))\nc = Volume(Empty())",
+    );
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let eval_result = eval_function(&doc_set, &entry, "a");
+    assert_matches!(eval_result, Ok(Value::Number(vol)) if (vol - 1.).abs() < 1e-9);
+
+    let eval_result = eval_function(&doc_set, &entry, "b");
+    assert_matches!(eval_result, Ok(Value::Number(vol)) if (vol - 7.).abs() < 1e-9);
+
+    let eval_result = eval_function(&doc_set, &entry, "c");
+    assert_matches!(eval_result, Ok(Value::Number(0.)));
+}
+
+/// A unit cube is 1 along each axis and the empty solid has no size.
+#[test]
+fn bbox_size_ok() {
+    let mut set = FileSet::default();
+    set.insert("main", "a = BBoxSize(Cube())\nb = BBoxSize(Empty())");
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let eval_result = eval_function(&doc_set, &entry, "a");
+    assert_matches!(
+        eval_result,
+        Ok(Value::Vector(x, y, z))
+            if (x - 1.).abs() < 1e-9 && (y - 1.).abs() < 1e-9 && (z - 1.).abs() < 1e-9
+    );
+
+    let eval_result = eval_function(&doc_set, &entry, "b");
+    assert_matches!(eval_result, Ok(Value::Vector(0., 0., 0.)));
+}
+
+/// The corners of the bounding box are scaled, rotated, then translated, and
+/// the transform can be given by position or with `offset` and `axis`
+/// vectors as for `Translate` and `Rotate`.
+#[test]
+fn transformed_bounds_ok() {
+    let mut set = FileSet::default();
+    set.insert(
+        "main",
+        "a = TransformedBounds(Box(1, 2, 4), 10, 0, 0, 0, 0, 1, 90, 2)\n\
+         b = TransformedBounds(Box(1, 2, 4), offset=Vec3(10, 0, 0), axis=Vec3(0, 0, 1), angle=90, factor=2)",
+    );
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    for func in ["a", "b"] {
+        let eval_result = eval_function(&doc_set, &entry, func);
+        let Ok(Value::List(corners)) = eval_result else {
+            panic!("Expected a list of corners");
+        };
+        let expected = [[8., -1., -4.], [12., 1., 4.]];
+        for (corner, expected) in corners.iter().zip(expected) {
+            let &Value::Vector(x, y, z) = corner else {
+                panic!("Expected a vector");
+            };
+            for (coord, expected) in [x, y, z].into_iter().zip(expected) {
+                assert!((coord - expected).abs() < 1e-6);
+            }
+        }
+    }
+}
+
+/// A factor that isn't positive is rejected as it is by `Scale`.
+#[test]
+fn transformed_bounds_non_positive_factor_errors() {
+    let mut set = FileSet::default();
+    set.insert(
+        "main",
+        "a = TransformedBounds(Cube(), factor=0)\nb = TransformedBounds(Cube(), factor=-1)",
+    );
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    for func in ["a", "b"] {
+        let eval_result = eval_function(&doc_set, &entry, func);
+        assert_matches!(
+            eval_result,
+            Err(EvalError {
+                error_type: EvalErrorType::NumExprNotFinite { .. },
+                ..
+            })
+        );
+    }
+}
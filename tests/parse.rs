@@ -0,0 +1,358 @@
+#![feature(assert_matches)]
+use std::assert_matches::assert_matches;
+
+use std::collections::HashMap;
+
+use funcad::*;
+use pest::error::LineColLocation;
+use typed_arena::Arena;
+
+mod util;
+use util::FileSet;
+
+fn error_line(result: Result<impl std::fmt::Debug, ParseError>) -> usize {
+    let Err(ParseError::Parse(error)) = result else {
+        panic!("expected a parsing error, got {result:?}");
+    };
+    match error.line_col {
+        LineColLocation::Pos((line, _)) | LineColLocation::Span((line, _), _) => line,
+    }
+}
+
+/// CRLF line breaks and a byte order mark parse, and errors are reported on
+/// the right line.
+#[test]
+fn parse_crlf_bom_lines() {
+    let mut set = FileSet::default();
+    set.insert("main", "\u{feff}a = 1\r\nb = 2\r\n\r\nc = *\r\n");
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_eq!(error_line(result), 4);
+}
+
+/// Lone CRs count as line breaks too.
+#[test]
+fn parse_cr_lines() {
+    let mut set = FileSet::default();
+    set.insert("main", "a = 1\rb = *\r");
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_eq!(error_line(result), 2);
+}
+
+/// A valid CRLF document with a byte order mark.
+#[test]
+fn parse_crlf_bom_ok() {
+    let mut set = FileSet::default();
+    set.insert("main", "\u{feff}a = b\r\nb = 1\r\n");
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(result, Ok(_));
+}
+
+/// Sources parsed from memory get the same handling of line breaks and byte
+/// order marks as ones read from files, including in the positions of spans.
+#[test]
+fn parse_document_cr_lines() {
+    assert_eq!(error_line(parse_document("a = 1\rb = *\r")), 2);
+    assert_eq!(error_line(parse_document("\u{feff}a = 1\r\n\r\nb = *")), 3);
+    assert_matches!(parse_document("\u{feff}a = b\r\nb = 1\r\n"), Ok(_));
+
+    let result = parse_document("a = 1\r\rb = 2\r\n  a = 3");
+    let Err(ParseError::DuplicateFuncDef(_, second)) = result else {
+        panic!("expected a duplicate definition, got {result:?}");
+    };
+    assert!(second.to_string().ends_with("on line 4 col 3"));
+}
+
+/// Documents compare structurally, ignoring formatting and ordering.
+#[test]
+fn document_eq_ignores_formatting() {
+    let a = parse_document("import b\nf(x = 1) = x*2+b.c\ng = [1, 2]").unwrap();
+    let b = parse_document("g=[1,2]\n\n  f( x=1 )=x * 2 + b.c\nimport b").unwrap();
+    let c = parse_document("import b\nf(x = 1) = x*3+b.c\ng = [1, 2]").unwrap();
+
+    assert_eq!(a, b);
+    assert_ne!(a, c);
+}
+
+/// A parsed document survives a round trip through JSON.
+#[cfg(feature = "serde")]
+#[test]
+fn document_serde_round_trip() {
+    let doc = parse_document(
+        "import b\nf(x = 1) = let y = -x in [y ^ 2, b.c(y)]\ng = Switch(\"a\", {\"a\": 1})",
+    )
+    .unwrap();
+
+    let json = serde_json::to_string(&doc).unwrap();
+    let round_trip: ast::Document = serde_json::from_str(&json).unwrap();
+
+    let mut names: Vec<_> = round_trip.function_names().collect();
+    names.sort();
+    assert_eq!(names, ["f", "g"]);
+    assert_eq!(round_trip, doc);
+}
+
+/// A document's functions can be listed along with their arguments.
+#[test]
+fn list_functions_ok() {
+    let mut set = FileSet::default();
+    set.insert("main", "b(x, y = 2) = x + y\na = b(1)");
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    assert_eq!(list_functions(&doc_set, &entry), Some(vec!["a", "b"]));
+    assert_eq!(
+        list_functions(&doc_set, &FQPath(vec!["other".into()])),
+        None
+    );
+
+    let doc = &doc_set[&entry];
+    assert_matches!(doc.arg_defs("a"), None);
+    assert_matches!(doc.arg_defs("b"), Some(defs) if defs.args.len() == 2);
+}
+
+/// Block comments can span lines and sit inside argument lists without
+/// throwing off the lines of what follows.
+#[test]
+fn parse_block_comment_ok() {
+    let doc =
+        parse_document("/* leading\ncomment */ a = f(1, /* first\nsecond */ 2)\nb /* /* */ = 3")
+            .unwrap();
+
+    let expected = parse_document("a = f(1, 2)\nb = 3").unwrap();
+    assert_eq!(doc, expected);
+    assert_eq!(doc.funcs["b"].span.start_pos().line_col(), (4, 1));
+
+    assert_matches!(
+        parse_document("a = 1 /* unterminated"),
+        Err(ParseError::Parse(_))
+    );
+}
+
+/// String literals keep their contents verbatim, including whitespace.
+#[test]
+fn parse_string_ok() {
+    let doc = parse_document("a = \" two  words \"").unwrap();
+
+    assert_matches!(
+        doc.funcs["a"].body.inner,
+        ast::Expr::Str(ast::StringLit {
+            val: " two  words "
+        })
+    );
+}
+
+/// Nested and parenthesized prefix expressions span their full source.
+#[test]
+fn parse_nested_prefix_span() {
+    let doc = parse_document("a = --b\nc = -(-b) * 2").unwrap();
+
+    assert_eq!(doc.funcs["a"].body.span.as_str(), "--b");
+
+    let c = &doc.funcs["c"].body;
+    assert_eq!(c.span.as_str(), "-(-b) * 2");
+    let ast::Expr::Binary(binary) = &c.inner else {
+        panic!("Expected a binary expression");
+    };
+    assert_eq!(binary.lhs.span.as_str(), "-(-b)");
+    let ast::Expr::Unary(unary) = &binary.lhs.inner else {
+        panic!("Expected a unary expression");
+    };
+    assert_eq!(unary.unit.span.as_str(), "(-b)");
+}
+
+/// Dictionary literals can't repeat a key.
+#[test]
+fn parse_dict_duplicate_key_errors() {
+    let doc = parse_document("a = {\"small\": 1, \"large\": 2}").unwrap();
+    assert_matches!(
+        &doc.funcs["a"].body.inner,
+        ast::Expr::Dict(dict) if dict.entries.len() == 2
+    );
+
+    assert_matches!(
+        parse_document("a = {\"small\": 1, \"small\": 2}"),
+        Err(ParseError::DuplicateDictKey(_, _))
+    );
+}
+
+/// A function definition can't repeat an argument name.
+#[test]
+fn parse_duplicate_arg_def_errors() {
+    assert_matches!(
+        parse_document("foo(a, a) = a"),
+        Err(ParseError::DuplicateArgDef(old, new))
+            if old.name.text == "a" && new.name.text == "a" && old.span != new.span
+    );
+}
+
+/// Digits can be separated by single underscores, but not at either end of a
+/// number or next to its decimal point.
+#[test]
+fn parse_digit_separators() {
+    let doc = parse_document("a = 1_000\nb = 1_0.2_5e1_0").unwrap();
+    assert_matches!(
+        doc.funcs["a"].body.inner,
+        ast::Expr::Number(ast::Number { val: 1000. })
+    );
+    assert_matches!(
+        doc.funcs["b"].body.inner,
+        ast::Expr::Number(ast::Number { val: 10.25e10 })
+    );
+
+    for src in ["a = 1__0", "a = 1_", "a = 1_.5", "a = 1._5", "a = 1e_5"] {
+        assert_matches!(parse_document(src), Err(ParseError::Parse(_)), "{src}");
+    }
+}
+
+/// Number literals too large for an f64 don't parse as infinity.
+#[test]
+fn parse_infinite_number_errors() {
+    assert_matches!(parse_document("a = 1e308"), Ok(_));
+    assert_matches!(
+        parse_document("a = 1e999"),
+        Err(ParseError::NonFiniteNumber(span)) if span.as_str() == "1e999"
+    );
+}
+
+/// Identifiers can contain underscores after their first letter.
+#[test]
+fn parse_identifier_underscore_ok() {
+    let doc = parse_document("snake_case(arg_1) = arg_1 + 1").unwrap();
+    let args = doc.arg_defs("snake_case").unwrap();
+    assert_eq!(args.args[0].name.text, "arg_1");
+
+    assert_matches!(parse_document("_a = 1"), Err(_));
+}
+
+/// Formatting re-emits canonical source that parses back to the same document
+/// and formats the same way again.
+#[test]
+fn format_document_ok() {
+    let src = "b(x,y=1+2)=-(x+y)*(2^3)^2-(-2)^2+ - 2 ^ -x
+        import * from  ../shapes
+        import util/geom
+        a =let p=(let q=1 in q),r=[1_000,\"s\"] in p>=Max(r, size =2, mode=\"x\" )
+        c=geom.f(  )+ {\"k\":1.5e-7}  /* note */ - (a - (b(1) - 3))";
+    let doc = parse_document(src).unwrap();
+
+    let formatted = format_document(&doc);
+    assert_eq!(
+        formatted,
+        "import * from ../shapes\n\
+         import util/geom\n\
+         \n\
+         b(x, y = 1 + 2) = -(x + y) * (2 ^ 3) ^ 2 - (-2) ^ 2 + -2 ^ (-x)\n\
+         a = let p = (let q = 1 in q), r = [1000, \"s\"] in p >= Max(r, mode = \"x\", size = 2)\n\
+         /* note */\n\
+         c = geom.f + {\"k\": 0.00000015} - (a - (b(1) - 3))\n"
+    );
+
+    let reparsed = parse_document(&formatted).unwrap();
+    assert_eq!(reparsed, doc);
+    assert_eq!(format_document(&reparsed), formatted);
+}
+
+/// Comments are kept through formatting, moved onto their own lines before
+/// what they were in or in front of, and left alone in strings.
+#[test]
+fn format_document_comments_ok() {
+    let src = "/* header */ import b /* on b */
+        import a
+        f(x /* arg */) = x + \"/* not a comment */\" /* after f */
+        /* before g
+           over lines */ g = f(1) /* trailing */";
+    let doc = parse_document(src).unwrap();
+
+    let formatted = format_document(&doc);
+    assert_eq!(
+        formatted,
+        "/* on b */\n\
+         import a\n\
+         /* header */\n\
+         import b\n\
+         \n\
+         /* arg */\n\
+         f(x) = x + \"/* not a comment */\"\n\
+         /* after f */\n\
+         /* before g\n           over lines */\n\
+         g = f(1)\n\
+         /* trailing */\n"
+    );
+
+    let reparsed = parse_document(&formatted).unwrap();
+    assert_eq!(reparsed, doc);
+    assert_eq!(format_document(&reparsed), formatted);
+}
+
+/// A visitor only overriding function calls still reaches every call, however
+/// deeply it's nested.
+#[test]
+fn visitor_counts_calls() {
+    struct CallCounter(usize);
+
+    impl<'src> ast::Visitor<'src> for CallCounter {
+        fn visit_func_call(&mut self, call: &ast::FuncCallExpr<'src>) {
+            self.0 += 1;
+            ast::walk_func_call(self, call);
+        }
+    }
+
+    let doc = parse_document(
+        "a = f(1, g(2)) + -[h, {\"k\": i.j}]
+        b(x = k(n = l)) = let y = m in y ^ 2",
+    )
+    .unwrap();
+
+    let mut counter = CallCounter(0);
+    ast::walk_document(&mut counter, &doc);
+    assert_eq!(counter.0, 8);
+}
+
+/// Arithmetic on number literals folds into a single number, but calls and
+/// operations that would fail when evaluated don't.
+#[test]
+fn fold_constants_ok() {
+    let doc = parse_document("a = 2 * 3 + 1").unwrap();
+    let folded = fold_constants(doc.funcs["a"].body.clone());
+    assert_matches!(folded.inner, ast::Expr::Number(ast::Number { val: 7. }));
+    assert_eq!(folded.span.as_str(), "2 * 3 + 1");
+
+    let expr = parse_expr("-(2 ^ 2) + [Sin(6 / 2), 1 / 0, 10 ^ 400]").unwrap();
+    // A leading minus parses as negation, so the expected literal is folded
+    // too.
+    let folded = parse_expr("-4 + [Sin(3), 1 / 0, 10 ^ 400]").unwrap();
+    assert_eq!(fold_constants(expr), fold_constants(folded));
+}
+
+/// Folding a document folds argument defaults and bodies without changing
+/// what its functions evaluate to.
+#[test]
+fn fold_document_constants_ok() {
+    let src = "f(x = 2 ^ 3) = x * (1 + 1)\na = f + f(4 / 2)";
+    let mut doc = parse_document(src).unwrap();
+    fold_document_constants(&mut doc);
+
+    let folded = format_document(&doc);
+    assert_eq!(folded, "f(x = 8) = x * 2\na = f + f(2)\n");
+
+    let entry = FQPath(vec!["main".into()]);
+    let docs = HashMap::from([(entry.clone(), doc)]);
+    assert_matches!(eval_function(&docs, &entry, "a"), Ok(Value::Number(20.)));
+}
@@ -0,0 +1,403 @@
+#![feature(assert_matches)]
+use std::assert_matches::assert_matches;
+
+use std::f64::consts::PI;
+
+use funcad::*;
+use typed_arena::Arena;
+
+mod util;
+use util::FileSet;
+
+const TETRAHEDRON_VERTICES: &str = "[0, 0, 0, 1, 0, 0, 0, 1, 0, 0, 0, 1]";
+
+/// A closed, outward facing tetrahedron.
+#[test]
+fn mesh_tetrahedron_ok() {
+    let mut set = FileSet::default();
+    set.insert(
+        "main",
+        &format!("a = Mesh({TETRAHEDRON_VERTICES}, [0, 2, 1, 0, 1, 3, 0, 3, 2, 1, 2, 3])"),
+    );
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let eval_result = eval_function(&doc_set, &entry, "a");
+    assert_matches!(eval_result, Ok(Value::Solid(SolidId::Regular(_))));
+}
+
+/// A tetrahedron missing a face isn't closed.
+#[test]
+fn mesh_open_errors() {
+    let mut set = FileSet::default();
+    set.insert(
+        "main",
+        &format!("a = Mesh({TETRAHEDRON_VERTICES}, [0, 2, 1, 0, 1, 3, 0, 3, 2])"),
+    );
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let eval_result = eval_function(&doc_set, &entry, "a");
+    assert_matches!(
+        eval_result,
+        Err(EvalError {
+            error_type: EvalErrorType::InvalidMesh { .. },
+            ..
+        })
+    );
+}
+
+/// Face indices must refer to a vertex.
+#[test]
+fn mesh_index_out_of_bounds_errors() {
+    let mut set = FileSet::default();
+    set.insert(
+        "main",
+        &format!("a = Mesh({TETRAHEDRON_VERTICES}, [0, 2, 1, 0, 1, 3, 0, 3, 2, 1, 2, 4])"),
+    );
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let eval_result = eval_function(&doc_set, &entry, "a");
+    assert_matches!(
+        eval_result,
+        Err(EvalError {
+            error_type: EvalErrorType::InvalidMesh { .. },
+            ..
+        })
+    );
+}
+
+/// An inside out tetrahedron.
+#[test]
+fn mesh_inside_out_errors() {
+    let mut set = FileSet::default();
+    set.insert(
+        "main",
+        &format!("a = Mesh({TETRAHEDRON_VERTICES}, [0, 1, 2, 0, 3, 1, 0, 2, 3, 1, 3, 2])"),
+    );
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let eval_result = eval_function(&doc_set, &entry, "a");
+    assert_matches!(
+        eval_result,
+        Err(EvalError {
+            error_type: EvalErrorType::InvalidMesh { .. },
+            ..
+        })
+    );
+}
+
+/// A frustum with a collapsed top is a pyramid with a quarter of the height
+/// as its centroid.
+#[test]
+fn rect_frustum_pyramid_ok() {
+    let mut set = FileSet::default();
+    set.insert(
+        "main",
+        "a = CentroidZ(RectFrustum(2, 2, 0, 0, 4))\nb = CentroidZ(RectFrustum(2, 2, 2, 0, 4))",
+    );
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let eval_result = eval_function(&doc_set, &entry, "a");
+    assert_matches!(
+        eval_result,
+        Ok(Value::Number(num)) if (num - 1.).abs() < 0.0001
+    );
+
+    // A wedge has a ridge rather than an apex.
+    let eval_result = eval_function(&doc_set, &entry, "b");
+    assert_matches!(
+        eval_result,
+        Ok(Value::Number(num)) if (num - 4. / 3.).abs() < 0.0001
+    );
+}
+
+/// Negative dimensions are rejected.
+#[test]
+fn rect_frustum_negative_errors() {
+    let mut set = FileSet::default();
+    set.insert("main", "a = RectFrustum(2, 2, -1, 1, 4)");
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let eval_result = eval_function(&doc_set, &entry, "a");
+    assert_matches!(
+        eval_result,
+        Err(EvalError {
+            error_type: EvalErrorType::InvalidDimension { .. },
+            ..
+        })
+    );
+}
+
+/// A box with a zero or negative extent is rejected rather than built flat or
+/// inside out.
+#[test]
+fn box_non_positive_errors() {
+    let mut set = FileSet::default();
+    set.insert("main", "a = Box(1, 0, 1)\nb = Box(1, 1, -2)");
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let eval_result = eval_function(&doc_set, &entry, "a");
+    assert_matches!(
+        eval_result,
+        Err(EvalError {
+            error_type: EvalErrorType::InvalidDimension { .. },
+            ..
+        }),
+        "a"
+    );
+
+    let eval_result = eval_function(&doc_set, &entry, "b");
+    assert_matches!(
+        eval_result,
+        Err(EvalError {
+            error_type: EvalErrorType::InvalidDimension { .. },
+            ..
+        }),
+        "b"
+    );
+}
+
+/// A cylinder needs a positive radius and height.
+#[test]
+fn cylinder_ok() {
+    let mut set = FileSet::default();
+    set.insert("main", "a = Cylinder(1, 2)\nb = Cylinder(0, 2)");
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let eval_result = eval_function(&doc_set, &entry, "a");
+    assert_matches!(eval_result, Ok(Value::Solid(SolidId::Regular(_))));
+
+    let eval_result = eval_function(&doc_set, &entry, "b");
+    assert_matches!(
+        eval_result,
+        Err(EvalError {
+            error_type: EvalErrorType::InvalidDimension { .. },
+            ..
+        })
+    );
+}
+
+/// Frustums of cones and rectangular pyramids have the volume of the
+/// prismatoid between their ends.
+#[test]
+fn frustum_volume_ok() {
+    let mut set = FileSet::default();
+    set.insert(
+        "main",
+        "a = Volume(Cone(2, 1, 3))\n\
+         b = Volume(RectFrustum(4, 2, 2, 1, 3))",
+    );
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    // pi * h / 3 * (R^2 + R * r + r^2)
+    let eval_result = eval_function(&doc_set, &entry, "a");
+    assert_matches!(
+        eval_result,
+        Ok(Value::Number(num)) if (num - 7. * PI).abs() < 0.001
+    );
+
+    // h / 6 * (A_bottom + A_top + (x_bottom + x_top) * (y_bottom + y_top))
+    let eval_result = eval_function(&doc_set, &entry, "b");
+    assert_matches!(
+        eval_result,
+        Ok(Value::Number(num)) if (num - 14.).abs() < 0.0001
+    );
+}
+
+/// The centroid of a cone is a quarter of the way up.
+#[test]
+fn cone_ok() {
+    let mut set = FileSet::default();
+    set.insert(
+        "main",
+        "a = Cone(bottom_radius=2, height=3)\n\
+         b = CentroidZ(a)\n\
+         c = Cone(0, 0, 3)",
+    );
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let eval_result = eval_function(&doc_set, &entry, "a");
+    assert_matches!(eval_result, Ok(Value::Solid(SolidId::Regular(_))));
+
+    let eval_result = eval_function(&doc_set, &entry, "b");
+    assert_matches!(
+        eval_result,
+        Ok(Value::Number(num)) if (num - 0.75).abs() < 0.0001
+    );
+
+    let eval_result = eval_function(&doc_set, &entry, "c");
+    assert_matches!(
+        eval_result,
+        Err(EvalError {
+            error_type: EvalErrorType::NumExprNotFinite { .. },
+            ..
+        })
+    );
+}
+
+/// The hull of two cubes offset diagonally is a hexagonal prism, with the
+/// coplanar triangles of each side merged into one face.
+#[test]
+fn hull_ok() {
+    let mut set = FileSet::default();
+    set.insert(
+        "main",
+        "a = AssertValid(Hull(Cube(), Translate(Cube(), 3, 3, 0)))\n\
+         b = CentroidX(a)\n\
+         c = Hull(Empty())",
+    );
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let Evaluation {
+        result: eval_result,
+        solids,
+        ..
+    } = EvalOptions::new().eval_function(&doc_set, &entry, "a");
+    let Ok(Value::Solid(id)) = eval_result else {
+        panic!("expected a solid, got {eval_result:?}");
+    };
+
+    let solid = solids.try_get(&id);
+    assert_matches!(solid, Ok(solid) if solid.boundaries()[0].len() == 8);
+
+    let eval_result = eval_function(&doc_set, &entry, "b");
+    assert_matches!(
+        eval_result,
+        Ok(Value::Number(num)) if (num - 1.5).abs() < 0.0001
+    );
+
+    let eval_result = eval_function(&doc_set, &entry, "c");
+    assert_matches!(eval_result, Ok(Value::Solid(SolidId::Empty)));
+}
+
+/// Three cubes in a row have coplanar sides and collinear edges, which the
+/// hull merges into a box. A hull of a single cube is the cube, and the
+/// solids can also be passed as a list.
+#[test]
+fn hull_degenerate_ok() {
+    let mut set = FileSet::default();
+    set.insert(
+        "main",
+        "a = Volume(AssertValid(Hull(Cube(), Translate(Cube(), 2, 0, 0), Translate(Cube(), 4, 0, 0))))\n\
+         b = Volume(Hull(Cube()))\n\
+         c = Volume(Hull(solids=[Cube(), Translate(Cube(), 2, 0, 0)]))\n\
+         d = Hull([Cube()])",
+    );
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    for (name, expected) in [("a", 5.), ("b", 1.), ("c", 3.)] {
+        let eval_result = eval_function(&doc_set, &entry, name);
+        assert_matches!(
+            eval_result,
+            Ok(Value::Number(vol)) if (vol - expected).abs() < 0.0001
+        );
+    }
+
+    let eval_result = eval_function(&doc_set, &entry, "d");
+    assert_matches!(
+        eval_result,
+        Err(EvalError {
+            error_type: EvalErrorType::ArgWrongType { .. },
+            ..
+        })
+    );
+}
+
+/// The hull of a cylinder is about the cylinder itself, and curved faces are
+/// sampled coarsely enough not to hang at the default tolerance.
+#[test]
+fn hull_curved_ok() {
+    let mut set = FileSet::default();
+    set.insert(
+        "main",
+        "a = Volume(Hull(Cylinder(1, 2)))\n\
+         b = Volume(Hull(Cylinder(1, 2), Translate(Cube(), 5, 0, 0)))",
+    );
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let eval_result = eval_function(&doc_set, &entry, "a");
+    assert_matches!(
+        eval_result,
+        Ok(Value::Number(vol)) if (vol - 2. * std::f64::consts::PI).abs() < 0.01
+    );
+
+    let eval_result = eval_function(&doc_set, &entry, "b");
+    assert_matches!(eval_result, Ok(Value::Number(vol)) if vol > 2. * std::f64::consts::PI + 1.);
+}
@@ -0,0 +1,215 @@
+#![feature(assert_matches)]
+use std::assert_matches::assert_matches;
+
+use funcad::*;
+use typed_arena::Arena;
+
+mod util;
+use util::FileSet;
+
+/// Circles and rectangles are single-face 2D shapes, not solids.
+#[test]
+fn sketch_primitives_ok() {
+    let mut set = FileSet::default();
+    set.insert(
+        "main",
+        "a = Circle(1)\nb = Rectangle(2, 3)\nc = Rectangle(2, 0)",
+    );
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    for name in ["a", "b"] {
+        let eval_result = eval_function(&doc_set, &entry, name);
+        assert_matches!(eval_result, Ok(Value::Shape2D(faces)) if faces.len() == 1);
+    }
+
+    let eval_result = eval_function(&doc_set, &entry, "c");
+    assert_matches!(
+        eval_result,
+        Err(EvalError {
+            error_type: EvalErrorType::InvalidDimension { .. },
+            ..
+        })
+    );
+}
+
+/// 2D shapes combine with the same operators as solids.
+#[test]
+fn sketch_booleans_ok() {
+    let mut set = FileSet::default();
+    set.insert(
+        "main",
+        "a = Rectangle(4, 1) + Rectangle(1, 4)\n\
+         b = Rectangle(4, 4) - Rectangle(1, 1)\n\
+         c = Rectangle(4, 1) - Rectangle(1, 4)\n\
+         d = Rectangle(4, 1) * Rectangle(1, 4)\n\
+         e = Circle(1) + Cube()",
+    );
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    // A cross, a square with a hole, the ends of a bar and its middle.
+    let eval_result = eval_function(&doc_set, &entry, "a");
+    assert_matches!(eval_result, Ok(Value::Shape2D(faces)) if faces.len() == 1);
+
+    let eval_result = eval_function(&doc_set, &entry, "b");
+    assert_matches!(eval_result, Ok(Value::Shape2D(faces)) if faces.len() == 1);
+
+    let eval_result = eval_function(&doc_set, &entry, "c");
+    assert_matches!(eval_result, Ok(Value::Shape2D(faces)) if faces.len() == 2);
+
+    let eval_result = eval_function(&doc_set, &entry, "d");
+    assert_matches!(eval_result, Ok(Value::Shape2D(faces)) if faces.len() == 1);
+
+    let eval_result = eval_function(&doc_set, &entry, "e");
+    assert_matches!(
+        eval_result,
+        Err(EvalError {
+            error_type: EvalErrorType::BinaryOpWrongTypes { .. },
+            ..
+        })
+    );
+}
+
+/// truck can't combine profiles with overlapping edges, including a profile
+/// with itself.
+#[test]
+fn sketch_booleans_overlapping_edges_fail() {
+    let mut set = FileSet::default();
+    set.insert(
+        "main",
+        "a = Rectangle(2, 2) + Rectangle(2, 1)\n\
+         b = Rectangle(2, 2) - Rectangle(2, 1)\n\
+         c = let r = Rectangle(2, 2) in r + r",
+    );
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    for func in ["a", "b", "c"] {
+        let eval_result = eval_function(&doc_set, &entry, func);
+        assert_matches!(
+            eval_result,
+            Err(EvalError {
+                error_type: EvalErrorType::GeometryOperationFailed { .. },
+                ..
+            })
+        );
+    }
+}
+
+/// An extruded circle is a cylinder, with a volume of about pi for a unit
+/// radius and height. Only 2D shapes can be extruded.
+#[test]
+fn extrude_ok() {
+    let mut set = FileSet::default();
+    set.insert(
+        "main",
+        "a = Volume(Extrude(Circle(1), 1))\n\
+         b = BBoxSize(Extrude(Rectangle(1, 2), 3))\n\
+         c = Extrude(Cube(), 1)",
+    );
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    // Meshing a circular face at the default tolerance is very slow.
+    let eval_result = EvalOptions::new()
+        .with_tolerance(0.001)
+        .eval_function(&doc_set, &entry, "a")
+        .result;
+    assert_matches!(
+        eval_result,
+        Ok(Value::Number(vol)) if (vol - std::f64::consts::PI).abs() < 0.01
+    );
+
+    let eval_result = eval_function(&doc_set, &entry, "b");
+    assert_matches!(
+        eval_result,
+        Ok(Value::Vector(x, y, z))
+            if (x - 1.).abs() < 1e-9 && (y - 2.).abs() < 1e-9 && (z - 3.).abs() < 1e-9
+    );
+
+    let eval_result = eval_function(&doc_set, &entry, "c");
+    assert_matches!(
+        eval_result,
+        Err(EvalError {
+            error_type: EvalErrorType::ArgWrongType { .. },
+            ..
+        })
+    );
+}
+
+#[test]
+fn extrude_non_positive_height_errors() {
+    let mut set = FileSet::default();
+    set.insert(
+        "main",
+        "a = Extrude(Circle(1), 0)\nb = Extrude(Rectangle(1, 2), -1)",
+    );
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    for name in ["a", "b"] {
+        let eval_result = eval_function(&doc_set, &entry, name);
+        assert_matches!(
+            eval_result,
+            Err(EvalError {
+                error_type: EvalErrorType::NumExprNotFinite { .. },
+                ..
+            })
+        );
+    }
+}
+
+/// The edges a sketch boolean makes can be extruded: a cross of 4 by 1 bars
+/// has an area of 7 and a square with a hole an area of 15.
+#[test]
+fn extrude_combined_ok() {
+    let mut set = FileSet::default();
+    set.insert(
+        "main",
+        "a = Volume(Extrude(Rectangle(4, 1) + Rectangle(1, 4), 1))\n\
+         b = Volume(Extrude(Rectangle(4, 4) - Rectangle(1, 1), 2))\n\
+         c = Volume(Extrude(Rectangle(4, 1) * Rectangle(1, 4), 3))",
+    );
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let eval_result = eval_function(&doc_set, &entry, "a");
+    assert_matches!(eval_result, Ok(Value::Number(vol)) if (vol - 7.).abs() < 1e-6);
+
+    let eval_result = eval_function(&doc_set, &entry, "b");
+    assert_matches!(eval_result, Ok(Value::Number(vol)) if (vol - 30.).abs() < 1e-6);
+
+    let eval_result = eval_function(&doc_set, &entry, "c");
+    assert_matches!(eval_result, Ok(Value::Number(vol)) if (vol - 3.).abs() < 1e-6);
+}
@@ -0,0 +1,725 @@
+#![feature(assert_matches)]
+use std::assert_matches::assert_matches;
+
+use funcad::*;
+use typed_arena::Arena;
+
+mod util;
+use util::FileSet;
+
+/// A translated copy is disjoint from the original.
+#[test]
+fn translate_ok() {
+    let mut set = FileSet::default();
+    set.insert(
+        "main",
+        "a = ComponentCount(Cube() + Translate(solid=Cube(), x=5))\n\
+         b = Translate(solid=1, x=5)",
+    );
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let eval_result = eval_function(&doc_set, &entry, "a");
+    assert_matches!(eval_result, Ok(Value::Number(num)) if num == 2.);
+
+    let eval_result = eval_function(&doc_set, &entry, "b");
+    assert_matches!(
+        eval_result,
+        Err(EvalError {
+            error_type: EvalErrorType::ArgWrongType { .. },
+            ..
+        })
+    );
+}
+
+/// A bar rotated a quarter turn about Z cuts the middle out of the original.
+#[test]
+fn rotate_quarter_turn_ok() {
+    let mut set = FileSet::default();
+    set.insert(
+        "main",
+        "a = ComponentCount(Box(2, 1, 1) - Rotate(Box(2, 1, 2), 0, 0, 1, 90))\n\
+         b = Rotate(Cube(), 0, 0, 0, 90)",
+    );
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let eval_result = eval_function(&doc_set, &entry, "a");
+    assert_matches!(eval_result, Ok(Value::Number(num)) if num == 2.);
+
+    let eval_result = eval_function(&doc_set, &entry, "b");
+    assert_matches!(
+        eval_result,
+        Err(EvalError {
+            error_type: EvalErrorType::NumExprNotFinite { .. },
+            ..
+        })
+    );
+}
+
+/// Translate, Rotate and LinearPattern take a vector in place of their three
+/// components, but not along with any of them.
+#[test]
+fn transform_by_vector_ok() {
+    let mut set = FileSet::default();
+    set.insert(
+        "main",
+        "a = ComponentCount(Cube() + Translate(Cube(), offset=Vec3(5, 0, 0)))\n\
+         b = ComponentCount(Box(2, 1, 1) - Rotate(Box(2, 1, 2), angle=90, axis=Vec3(0, 0, 1)))\n\
+         c = ComponentCount(LinearPattern(Cube(), count=2, offset=Vec3(2, 0, 0)))\n\
+         d = Translate(Cube(), 5, offset=Vec3(5, 0, 0))\n\
+         e = LinearPattern(Cube(), count=3, offset=Vec3(2, 0, 0), z=1)\n\
+         f = Translate(Cube(), Vec3(5, 0, 0))",
+    );
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    for name in ["a", "b", "c"] {
+        let eval_result = eval_function(&doc_set, &entry, name);
+        assert_matches!(eval_result, Ok(Value::Number(num)) if num == 2., "{name}");
+    }
+
+    for (func, component) in [("d", "x"), ("e", "z")] {
+        let eval_result = eval_function(&doc_set, &entry, func);
+        assert_matches!(
+            eval_result,
+            Err(EvalError {
+                error_type: EvalErrorType::ConflictingArgs { ref name, ref other },
+                ..
+            }) if name == "offset" && other == component
+        );
+    }
+
+    let eval_result = eval_function(&doc_set, &entry, "f");
+    assert_matches!(
+        eval_result,
+        Err(EvalError {
+            error_type: EvalErrorType::ArgWrongType { ref name, .. },
+            ..
+        }) if name == "x"
+    );
+}
+
+/// A wedge mirrored across the X plane ends up on the other side of it.
+#[test]
+fn mirror_wedge_ok() {
+    let mut set = FileSet::default();
+    set.insert(
+        "main",
+        "a = CentroidX(AssertValid(Mirror(wedge, 1, 0, 0)))\n\
+         wedge = Translate(RectFrustum(2, 1, 0, 1, 1), 2)\n\
+         b = Mirror(solid=Cube(), normal_x=0, normal_y=0, normal_z=0)",
+    );
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let eval_result = eval_function(&doc_set, &entry, "a");
+    assert_matches!(
+        eval_result,
+        Ok(Value::Number(num)) if (num + 2.).abs() < 0.0001
+    );
+
+    let eval_result = eval_function(&doc_set, &entry, "b");
+    assert_matches!(
+        eval_result,
+        Err(EvalError {
+            error_type: EvalErrorType::NumExprNotFinite { .. },
+            ..
+        })
+    );
+}
+
+/// Symmetrizing a solid that already straddles the mirror plane.
+#[test]
+fn symmetrize_straddling_ok() {
+    let mut set = FileSet::default();
+    set.insert("main", "a = Symmetrize(Cube(), 1, 0, 0)");
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let eval_result = eval_function(&doc_set, &entry, "a");
+    assert_matches!(eval_result, Ok(Value::Solid(SolidId::Regular(_))));
+}
+
+/// Symmetrizing a solid off to one side of the mirror plane unions it with its
+/// mirror image.
+#[test]
+fn symmetrize_offset_ok() {
+    let mut set = FileSet::default();
+    set.insert(
+        "main",
+        "a = Symmetrize(Translate(Cube(), 2, 0, 0), 1, 0, 0)\nb = Volume(a)",
+    );
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let Evaluation {
+        result: eval_result,
+        solids,
+        ..
+    } = EvalOptions::new().eval_function(&doc_set, &entry, "a");
+    let Ok(Value::Solid(id)) = eval_result else {
+        panic!("expected a solid, got {eval_result:?}");
+    };
+    assert_matches!(solids.try_get(&id), Ok(solid) if solid.boundaries().len() == 2);
+
+    let eval_result = eval_function(&doc_set, &entry, "b");
+    assert_matches!(
+        eval_result,
+        Ok(Value::Number(vol)) if (vol - 2.).abs() < 0.001
+    );
+}
+
+/// Mirroring half of a part across its cut face gives the whole part, even
+/// though the half's only vertices off the curved face are on the plane.
+#[test]
+fn symmetrize_half_ok() {
+    let mut set = FileSet::default();
+    set.insert(
+        "main",
+        "h = Cylinder(1, 1) - Translate(Box(2, 4, 4), 1, 0, 0)\na = Volume(Symmetrize(h, 1, 0, 0))",
+    );
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let eval_result = eval_function(&doc_set, &entry, "a");
+    assert_matches!(
+        eval_result,
+        Ok(Value::Number(vol)) if (vol - std::f64::consts::PI).abs() < 0.001
+    );
+}
+
+/// A zero normal doesn't define a plane.
+#[test]
+fn symmetrize_zero_normal_errors() {
+    let mut set = FileSet::default();
+    set.insert("main", "a = Symmetrize(Cube(), 0, 0, 0)");
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let eval_result = eval_function(&doc_set, &entry, "a");
+    assert_matches!(
+        eval_result,
+        Err(EvalError {
+            error_type: EvalErrorType::NumExprNotFinite { .. },
+            ..
+        })
+    );
+}
+
+/// Copies are placed at each point.
+#[test]
+fn place_at_ok() {
+    let mut set = FileSet::default();
+    set.insert(
+        "main",
+        "a = CentroidX(PlaceAt(Cube(), [[0, 0, 0], [3, 0, 0]]))",
+    );
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let eval_result = eval_function(&doc_set, &entry, "a");
+    assert_matches!(
+        eval_result,
+        Ok(Value::Number(num)) if (num - 1.5).abs() < 0.0001
+    );
+}
+
+/// Placing at no points gives the empty solid.
+#[test]
+fn place_at_no_points_empty() {
+    let mut set = FileSet::default();
+    set.insert("main", "a = PlaceAt(Cube(), [])");
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let eval_result = eval_function(&doc_set, &entry, "a");
+    assert_matches!(eval_result, Ok(Value::Solid(SolidId::Empty)));
+}
+
+/// Overlapping copies with coplanar faces can't be unioned by truck, so
+/// placing them fails rather than giving the empty solid.
+#[test]
+fn place_at_overlapping_errors() {
+    let mut set = FileSet::default();
+    set.insert("main", "a = PlaceAt(Box(2, 1, 1), [[0, 0, 0], [1, 0, 0]])");
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let eval_result = eval_function(&doc_set, &entry, "a");
+    assert_matches!(
+        eval_result,
+        Err(EvalError {
+            error_type: EvalErrorType::GeometryOperationFailed { op: "PlaceAt" },
+            ..
+        })
+    );
+}
+
+/// Clipping keeps only the part of a solid inside the box.
+#[test]
+fn clip_box_ok() {
+    let mut set = FileSet::default();
+    set.insert(
+        "main",
+        "a = CentroidX(ClipBox(Translate(Box(6, 1, 1), 1), 2, 2, 2))",
+    );
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let eval_result = eval_function(&doc_set, &entry, "a");
+    assert_matches!(
+        eval_result,
+        Ok(Value::Number(num)) if num.abs() < 0.0001
+    );
+}
+
+/// Clipping to a box with a zero or negative extent is an error.
+#[test]
+fn clip_box_non_positive_errors() {
+    let mut set = FileSet::default();
+    set.insert(
+        "main",
+        "a = ClipBox(Cube(), 0, 1, 1)\nb = ClipBox(Cube(), 1, 1, -1)",
+    );
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let eval_result = eval_function(&doc_set, &entry, "a");
+    assert_matches!(
+        eval_result,
+        Err(EvalError {
+            error_type: EvalErrorType::InvalidDimension { .. },
+            ..
+        }),
+        "a"
+    );
+
+    let eval_result = eval_function(&doc_set, &entry, "b");
+    assert_matches!(
+        eval_result,
+        Err(EvalError {
+            error_type: EvalErrorType::InvalidDimension { .. },
+            ..
+        }),
+        "b"
+    );
+}
+
+/// A dropped solid's lowest point is at z = 0.
+#[test]
+fn drop_to_floor_ok() {
+    let mut set = FileSet::default();
+    set.insert(
+        "main",
+        "a = CentroidZ(DropToFloor(Translate(Cube(2), 0, 0, -7)))",
+    );
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let eval_result = eval_function(&doc_set, &entry, "a");
+    assert_matches!(
+        eval_result,
+        Ok(Value::Number(num)) if (num - 1.).abs() < 0.0001
+    );
+}
+
+/// The empty solid has nowhere to drop to.
+#[test]
+fn drop_to_floor_empty_errors() {
+    let mut set = FileSet::default();
+    set.insert("main", "a = DropToFloor(PlaceAt(Cube(), []))");
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let eval_result = eval_function(&doc_set, &entry, "a");
+    assert_matches!(
+        eval_result,
+        Err(EvalError {
+            error_type: EvalErrorType::EmptySolid,
+            ..
+        })
+    );
+}
+
+/// Blending moves a solid part way to another's position.
+#[test]
+fn blend_position_ok() {
+    let mut set = FileSet::default();
+    set.insert(
+        "main",
+        "a = CentroidX(BlendPosition(Cube(), Translate(Cube(2), 4), 0.25))",
+    );
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let eval_result = eval_function(&doc_set, &entry, "a");
+    assert_matches!(
+        eval_result,
+        Ok(Value::Number(num)) if (num - 1.).abs() < 0.0001
+    );
+}
+
+/// Scaling along one axis stretches a solid and its distance from the origin.
+#[test]
+fn scale_non_uniform_ok() {
+    let mut set = FileSet::default();
+    set.insert(
+        "main",
+        "a = AssertValid(Scale(solid=Cube(), x=2))\n\
+         b = CentroidX(Scale(solid=Translate(Cube(), 1), x=2, y=3))\n\
+         c = Scale(solid=Cube(), y=-1)",
+    );
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let eval_result = eval_function(&doc_set, &entry, "a");
+    assert_matches!(eval_result, Ok(Value::Solid(SolidId::Regular(_))));
+
+    let eval_result = eval_function(&doc_set, &entry, "b");
+    assert_matches!(
+        eval_result,
+        Ok(Value::Number(num)) if (num - 2.).abs() < 0.0001
+    );
+
+    let eval_result = eval_function(&doc_set, &entry, "c");
+    assert_matches!(
+        eval_result,
+        Err(EvalError {
+            error_type: EvalErrorType::NumExprNotFinite { .. },
+            ..
+        })
+    );
+}
+
+#[test]
+fn scale_non_positive_factor_errors() {
+    let mut set = FileSet::default();
+    set.insert("main", "a = Scale(Cube(), 0)\nb = Scale(Cube(), -2)");
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    for name in ["a", "b"] {
+        let eval_result = eval_function(&doc_set, &entry, name);
+        assert_matches!(
+            eval_result,
+            Err(EvalError {
+                error_type: EvalErrorType::NumExprNotFinite { .. },
+                ..
+            })
+        );
+    }
+}
+
+/// The pivot stays fixed when scaling about it, unlike scaling about the
+/// origin.
+#[test]
+fn scale_about_fixes_pivot() {
+    let mut set = FileSet::default();
+    set.insert(
+        "main",
+        "a = [CentroidX(Scale(c, 2)), CentroidX(ScaleAbout(c, 2, 2.5, 0, 0)), \
+         CentroidX(ScaleAbout(c, 2, 3, 0, 0))]\n\
+         c = Translate(Cube(), 3, 0, 0)",
+    );
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let eval_result = eval_function(&doc_set, &entry, "a");
+    assert_matches!(eval_result, Ok(Value::List(ref items)) if matches!(
+        items[..],
+        [Value::Number(scaled), Value::Number(face), Value::Number(center)]
+            if (scaled - 6.).abs() < 1e-6
+                && (face - 3.5).abs() < 1e-6
+                && (center - 3.).abs() < 1e-6
+    ));
+}
+
+/// A zero factor would collapse the solid and a negative one would mirror it.
+#[test]
+fn scale_zero_errors() {
+    let mut set = FileSet::default();
+    set.insert(
+        "main",
+        "a = ScaleAbout(Cube(), 0, 1, 1, 1)\nb = ScaleAbout(Cube(), -2, 1, 1, 1)",
+    );
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    for name in ["a", "b"] {
+        let eval_result = eval_function(&doc_set, &entry, name);
+        assert_matches!(
+            eval_result,
+            Err(EvalError {
+                error_type: EvalErrorType::NumExprNotFinite { .. },
+                ..
+            })
+        );
+    }
+}
+
+/// The pivot stays fixed when rotating about it, unlike rotating about the
+/// origin.
+#[test]
+fn rotate_about_fixes_pivot() {
+    let mut set = FileSet::default();
+    set.insert(
+        "main",
+        "a = [CentroidY(Rotate(c, 0, 0, 1, 90)), CentroidX(RotateAbout(c, 3, 0, 0, 0, 0, 1, 90)), \
+         CentroidX(RotateAbout(c, 2.5, 0, 0, 0, 1, 0, 180))]\n\
+         c = Translate(Cube(), 3, 0, 0)",
+    );
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let eval_result = eval_function(&doc_set, &entry, "a");
+    assert_matches!(eval_result, Ok(Value::List(ref items)) if matches!(
+        items[..],
+        [Value::Number(origin), Value::Number(center), Value::Number(face)]
+            if (origin - 3.).abs() < 1e-6
+                && (center - 3.).abs() < 1e-6
+                && (face - 2.).abs() < 1e-6
+    ));
+}
+
+/// A shelled cube keeps its outer faces and gains an inner shell around the
+/// cavity. Walls that meet in the middle leave no cavity, and walls need some
+/// thickness.
+#[test]
+fn shell_ok() {
+    let mut set = FileSet::default();
+    set.insert(
+        "main",
+        "a = Shell(Cube(), 0.1)\nb = Shell(Cube(), 0.5)\nc = Shell(Cube(), 0)",
+    );
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let Evaluation {
+        result: eval_result,
+        solids,
+        ..
+    } = EvalOptions::new().eval_function(&doc_set, &entry, "a");
+    let Ok(Value::Solid(id)) = eval_result else {
+        panic!("expected a solid, got {eval_result:?}");
+    };
+
+    let solid = solids.try_get(&id);
+    assert_matches!(
+        solid,
+        Ok(solid) if solid.boundaries().len() == 2
+            && solid.boundaries().iter().all(|shell| shell.len() == 6)
+    );
+
+    for name in ["b", "c"] {
+        let eval_result = eval_function(&doc_set, &entry, name);
+        assert_matches!(
+            eval_result,
+            Err(EvalError {
+                error_type: EvalErrorType::NumExprNotFinite { .. },
+                ..
+            })
+        );
+    }
+}
+
+/// A shelled solid combines with others, including ones that only touch its
+/// cavity or sit inside it: the walls of a 2 unit box shelled by 0.1 are
+/// `8 - 1.8 ^ 3`.
+#[test]
+fn shell_boolean_ok() {
+    let mut set = FileSet::default();
+    set.insert(
+        "main",
+        "s = Shell(Box(2, 2, 2), 0.1)\n\
+         a = Volume(s - Cube())\n\
+         b = Volume(s + Cube())\n\
+         c = s * Cube()\n\
+         d = Volume(s - Translate(Box(0.5, 0.5, 0.5), 0.95, 0, 0))",
+    );
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let walls = 8. - 1.8f64.powi(3);
+
+    let eval_result = eval_function(&doc_set, &entry, "a");
+    assert_matches!(eval_result, Ok(Value::Number(vol)) if (vol - walls).abs() < 1e-6);
+
+    let eval_result = eval_function(&doc_set, &entry, "b");
+    assert_matches!(eval_result, Ok(Value::Number(vol)) if (vol - walls - 1.).abs() < 1e-6);
+
+    let eval_result = eval_function(&doc_set, &entry, "c");
+    assert_matches!(eval_result, Ok(Value::Solid(SolidId::Empty)));
+
+    // The notch through the wall is 0.5 by 0.5 by the wall's thickness.
+    let eval_result = eval_function(&doc_set, &entry, "d");
+    assert_matches!(
+        eval_result,
+        Ok(Value::Number(vol)) if (vol - walls + 0.025).abs() < 1e-6
+    );
+}
+
+/// Chamfering a box bevels all twelve of its edges, leaving a closed solid
+/// with a face for each edge alongside the box's own faces.
+#[test]
+fn chamfer_ok() {
+    let mut set = FileSet::default();
+    set.insert(
+        "main",
+        "a = Chamfer(Box(2, 2, 2), 0.2)\n\
+         b = Volume(a)\n\
+         c = Chamfer(Box(2, 2, 2), 0)",
+    );
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let Evaluation {
+        result: eval_result,
+        solids,
+        ..
+    } = EvalOptions::new().eval_function(&doc_set, &entry, "a");
+    let Ok(Value::Solid(id)) = eval_result else {
+        panic!("expected a solid, got {eval_result:?}");
+    };
+
+    assert_matches!(solids.try_get(&id), Ok(solid) if solid.face_iter().count() == 18);
+    assert_matches!(
+        tessellate_solid(&solids, &id),
+        Ok(mesh) if !mesh.tri_faces().is_empty()
+    );
+
+    let eval_result = eval_function(&doc_set, &entry, "b");
+    assert_matches!(
+        eval_result,
+        Ok(Value::Number(vol)) if (vol - 7.568).abs() < 0.01
+    );
+
+    let eval_result = eval_function(&doc_set, &entry, "c");
+    assert_matches!(
+        eval_result,
+        Err(EvalError {
+            error_type: EvalErrorType::InvalidDimension { .. },
+            ..
+        })
+    );
+}
@@ -0,0 +1,70 @@
+#![feature(assert_matches)]
+use std::assert_matches::assert_matches;
+
+use funcad::*;
+use typed_arena::Arena;
+
+mod util;
+use util::FileSet;
+
+/// Functions, arguments, built-ins and imports all resolve.
+#[test]
+fn validate_all_resolved_ok() {
+    let mut set = FileSet::default();
+    set.insert("main", "import b\na(x = c) = b.c + x + Sin(c)\nc = 1");
+    set.insert("b", "c = 1");
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    assert!(validate_names(&doc_set).is_empty());
+}
+
+/// Every unresolved name is reported, including those in argument defaults
+/// which can't see other arguments.
+#[test]
+fn validate_unresolved_errors() {
+    let mut set = FileSet::default();
+    set.insert("main", "import b\na(x, y = x) = b.d + e");
+    set.insert("b", "c = 1");
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let errors = validate_names(&doc_set);
+    assert_eq!(errors.len(), 3);
+    for error in errors {
+        assert_matches!(error.error_type, EvalErrorType::FuncNotFound { .. });
+    }
+}
+
+/// Calls through an import that doesn't exist.
+#[test]
+fn validate_missing_import_errors() {
+    let mut set = FileSet::default();
+    set.insert("main", "a = b.c");
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let errors = validate_names(&doc_set);
+    assert_matches!(
+        errors.as_slice(),
+        [EvalError {
+            error_type: EvalErrorType::ImportNotFound { .. },
+            ..
+        }]
+    );
+}
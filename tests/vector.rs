@@ -0,0 +1,47 @@
+#![feature(assert_matches)]
+use std::assert_matches::assert_matches;
+
+use funcad::*;
+use typed_arena::Arena;
+
+mod util;
+use util::FileSet;
+
+/// Vectors are built from components and give them back.
+#[test]
+fn vec3_components_ok() {
+    let mut set = FileSet::default();
+    set.insert(
+        "main",
+        "v = Vec3(1, 2, 3)\na = Y(v)\nb = Vec3(z=4)\nc = X(5)",
+    );
+
+    let arena = Arena::new();
+    let entry = FQPath(vec!["main".into()]);
+
+    let parse_result = parse_all(&arena, &entry, |s| set.get_source(s));
+    assert_matches!(parse_result, Ok(_));
+    let doc_set = parse_result.unwrap();
+
+    let eval_result = eval_function(&doc_set, &entry, "v");
+    assert_matches!(eval_result, Ok(Value::Vector(1., 2., 3.)));
+
+    let eval_result = eval_function(&doc_set, &entry, "a");
+    assert_matches!(eval_result, Ok(Value::Number(2.)));
+
+    let eval_result = eval_function(&doc_set, &entry, "b");
+    assert_matches!(eval_result, Ok(Value::Vector(0., 0., 4.)));
+
+    let eval_result = eval_function(&doc_set, &entry, "c");
+    assert_matches!(
+        eval_result,
+        Err(EvalError {
+            error_type: EvalErrorType::ArgWrongType {
+                expected: "vector",
+                got: "number",
+                ..
+            },
+            ..
+        })
+    );
+}
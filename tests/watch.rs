@@ -0,0 +1,76 @@
+#![cfg(feature = "watch")]
+#![feature(assert_matches)]
+use std::assert_matches::assert_matches;
+
+use std::{env, fs, ops::ControlFlow, process, sync::mpsc, thread, time::Duration};
+
+use funcad::*;
+
+/// Changing the watched file or a file it imports evaluates it again, and an
+/// error is reported without ending the watch.
+#[test]
+fn watch_reevaluates_ok() {
+    let dir = env::temp_dir().join(format!("funcad_watch_{}", process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let main = dir.join("main.fc");
+    fs::write(&main, "import part\na = part.b + 1").unwrap();
+    fs::write(dir.join("part.fc"), "b = 1").unwrap();
+
+    let (sender, results) = mpsc::channel();
+    let watched = main.clone();
+    thread::spawn(move || {
+        watch(&watched, "a", |res| {
+            match sender.send(res.map(|(val, _)| val.to_string())) {
+                Ok(()) => ControlFlow::Continue(()),
+                Err(_) => ControlFlow::Break(()),
+            }
+        })
+    });
+    let next = || results.recv_timeout(Duration::from_secs(10)).unwrap();
+
+    assert_eq!(next(), Ok("2".into()));
+
+    fs::write(dir.join("part.fc"), "b = 2").unwrap();
+    assert_eq!(next(), Ok("3".into()));
+
+    fs::write(&main, "a = Missing()").unwrap();
+    assert_matches!(next(), Err(_));
+
+    fs::write(&main, "a = 4").unwrap();
+    assert_eq!(next(), Ok("4".into()));
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+/// Values from documents that didn't change are reused, so the solid of
+/// `part.b` is the one built in the first run rather than a new one.
+#[test]
+fn watch_reuses_unchanged_ok() {
+    let dir = env::temp_dir().join(format!("funcad_watch_reuse_{}", process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let main = dir.join("main.fc");
+    fs::write(&main, "import part\na = part.b").unwrap();
+    fs::write(dir.join("part.fc"), "b = Translate(Cube(), 1, 0, 0)").unwrap();
+
+    let (sender, results) = mpsc::channel();
+    let watched = main.clone();
+    thread::spawn(move || {
+        watch(&watched, "a", |res| {
+            match sender.send(res.map(|(val, _)| val.to_string())) {
+                Ok(()) => ControlFlow::Continue(()),
+                Err(_) => ControlFlow::Break(()),
+            }
+        })
+    });
+    let next = || results.recv_timeout(Duration::from_secs(10)).unwrap();
+
+    assert_eq!(next(), Ok("solid#1".into()));
+
+    fs::write(&main, "import part\na = [Box(1, 2, 3), part.b]").unwrap();
+    assert_eq!(next(), Ok("[solid#2, solid#1]".into()));
+
+    fs::write(dir.join("part.fc"), "b = Translate(Cube(), 2, 0, 0)").unwrap();
+    assert_eq!(next(), Ok("[solid#2, solid#3]".into()));
+
+    fs::remove_dir_all(&dir).unwrap();
+}